@@ -0,0 +1,121 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, MultiSelect, Select};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::config::{load_config, save_local_profile_selection_to_dir};
+use crate::git::apply_local_config_to_dir;
+use crate::history::track;
+use crate::scan::{find_repositories, inspect_repository, RepoReport, RepoState};
+use crate::submodule::split_remote_url;
+
+/// Group key for a repository: `host/org` of its origin remote
+fn group_key(report: &RepoReport) -> String {
+    report
+        .remote
+        .as_deref()
+        .and_then(split_remote_url)
+        .map(|(host, path)| match path.split('/').next().filter(|org| !org.is_empty()) {
+            Some(org) if path.contains('/') => format!("{}/{}", host, org),
+            _ => host,
+        })
+        .unwrap_or_else(|| "(no remote)".to_string())
+}
+
+/// Handle the 'gix adopt' command
+pub fn handle_adopt_command(dir: PathBuf, max_depth: usize) -> Result<()> {
+    let config = load_config()?;
+    if config.profiles.is_empty() {
+        println!("\x1b[1;33m⚠ No profiles configured. Run 'gix profile add' first.\x1b[0m");
+        return Ok(());
+    }
+
+    println!("\x1b[1;36m📥 Adopting repositories under {}\x1b[0m\n", dir.display());
+
+    let mut groups: BTreeMap<String, Vec<RepoReport>> = BTreeMap::new();
+    for path in find_repositories(&dir, max_depth) {
+        let report = inspect_repository(&config, &path);
+        if matches!(report.state, RepoState::Unmanaged | RepoState::UnmanagedKnown) {
+            groups.entry(group_key(&report)).or_default().push(report);
+        }
+    }
+
+    if groups.is_empty() {
+        println!("   All repositories are already managed by gix.");
+        return Ok(());
+    }
+
+    let total: usize = groups.values().map(|g| g.len()).sum();
+    println!("   Found {} unmanaged repositories in {} group(s).\n", total, groups.len());
+
+    let mut profile_items: Vec<String> = config
+        .profiles
+        .iter()
+        .map(|p| format!("{} <{}>", p.profile_name, p.email))
+        .collect();
+    profile_items.push("Skip this group".to_string());
+
+    let mut adopted = 0;
+    for (group, reports) in &groups {
+        println!("\x1b[1m{}\x1b[0m ({} repositories)", group, reports.len());
+
+        // Suggest the profile whose email most repositories of the group already use
+        let mut votes: BTreeMap<&str, usize> = BTreeMap::new();
+        for report in reports {
+            if let Some(name) = &report.expected {
+                *votes.entry(name.as_str()).or_default() += 1;
+            }
+        }
+        let suggested = votes
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .and_then(|(name, _)| config.profiles.iter().position(|p| p.is_named(name)))
+            .unwrap_or(0);
+
+        let choice = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Profile for {}", group))
+            .items(&profile_items)
+            .default(suggested)
+            .interact()?;
+        let Some(profile) = config.profiles.get(choice) else {
+            println!();
+            continue;
+        };
+
+        let items: Vec<String> = reports
+            .iter()
+            .map(|r| {
+                format!(
+                    "{} ({})",
+                    r.path.display(),
+                    r.actual_email.as_deref().unwrap_or("no user.email")
+                )
+            })
+            .collect();
+        let defaults = vec![true; items.len()];
+        let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Repositories to configure with '{}'", profile.profile_name))
+            .items(&items)
+            .defaults(&defaults)
+            .interact()?;
+
+        for index in selected {
+            let path = &reports[index].path;
+            let result = track(path, &profile.profile_name, || {
+                save_local_profile_selection_to_dir(&profile.profile_name, path.clone())?;
+                apply_local_config_to_dir(profile, path)
+            });
+            match result {
+                Ok(()) => {
+                    adopted += 1;
+                    println!("   \x1b[1;32m✓\x1b[0m {}", path.display());
+                }
+                Err(e) => println!("   \x1b[1;31m✗\x1b[0m {}: {}", path.display(), e),
+            }
+        }
+        println!();
+    }
+
+    println!("\x1b[1;32m✓ Adopted {} of {} repositories.\x1b[0m", adopted, total);
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use directories::BaseDirs;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::cli::{AliasAction, Shell};
+use crate::config::write_atomic;
+use crate::managed_block::{self, Position};
+use crate::version::Doctor;
+
+const BLOCK_START: &str = "# >>> gix git wrapper >>>";
+const BLOCK_END: &str = "# <<< gix git wrapper <<<";
+
+/// Shell named by $SHELL, if it is one gix supports
+fn current_shell() -> Option<Shell> {
+    let shell = std::env::var("SHELL").ok()?;
+    match shell.rsplit('/').next()? {
+        "bash" => Some(Shell::Bash),
+        "zsh" => Some(Shell::Zsh),
+        "fish" => Some(Shell::Fish),
+        _ => None,
+    }
+}
+
+fn resolve_shell(shell: Option<Shell>) -> Result<Shell> {
+    shell
+        .or_else(current_shell)
+        .context("Could not detect your shell from $SHELL; pass it explicitly (bash, zsh or fish)")
+}
+
+fn rc_path(shell: Shell) -> Result<PathBuf> {
+    let home = BaseDirs::new()
+        .map(|dirs| dirs.home_dir().to_path_buf())
+        .context("Could not determine home directory")?;
+    Ok(match shell {
+        Shell::Bash => home.join(".bashrc"),
+        Shell::Zsh => home.join(".zshrc"),
+        Shell::Fish => home.join(".config").join("fish").join("config.fish"),
+    })
+}
+
+/// Managed block defining `git` as a wrapper around `gix`
+///
+/// `gix -- <args>` forwards anything gix does not intercept to git unchanged,
+/// so gix's own subcommand names never shadow git's.
+fn wrapper_block(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash | Shell::Zsh => "git() {\n  command gix -- \"$@\"\n}",
+        Shell::Fish => "function git --wraps git\n    command gix -- $argv\nend",
+    }
+}
+
+/// Rc file contents with the wrapper block replaced by `block`, or removed
+fn with_wrapper(existing: &str, block: Option<&str>, path: &Path) -> Result<String> {
+    managed_block::replace(existing, BLOCK_START, BLOCK_END, block, Position::Last)
+        .with_context(|| format!("Cannot update {}", path.display()))
+}
+
+/// Replace an rc file in one step, through the symlink of a dotfiles repository if any
+fn write_rc(path: &Path, contents: &str) -> Result<()> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    write_atomic(&target, contents, false)
+}
+
+/// Whether the rc file of `shell` contains the wrapper
+pub fn is_installed(shell: Shell) -> bool {
+    rc_path(shell)
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .is_some_and(|c| managed_block::contains(&c, BLOCK_START))
+}
+
+fn shell_name(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => "bash",
+        Shell::Zsh => "zsh",
+        Shell::Fish => "fish",
+    }
+}
+
+/// Whether a fresh interactive shell resolves `git` to a function
+fn is_active(shell: Shell) -> bool {
+    Command::new(shell_name(shell))
+        .args(["-i", "-c", "type git"])
+        .stdin(Stdio::null())
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains("function"))
+        .unwrap_or(false)
+}
+
+/// Doctor check: is the wrapper installed, and does the shell pick it up?
+pub fn doctor_check(doctor: &mut Doctor) {
+    doctor.start("git wrapper");
+    let Some(shell) = current_shell() else {
+        doctor.notice("Unknown shell; 'gix alias install <shell>' sets up the wrapper");
+        return;
+    };
+
+    if !is_installed(shell) {
+        doctor.notice(format!("Not installed for {}; run 'gix alias install' so 'git' goes through gix", shell_name(shell)));
+    } else if is_active(shell) {
+        doctor.ok(format!("'git' runs through gix in {}", shell_name(shell)));
+    } else {
+        doctor.error(format!(
+            "Wrapper is in {} but 'git' is not a function in a new shell",
+            rc_path(shell).map(|p| p.display().to_string()).unwrap_or_default()
+        ));
+    }
+}
+
+/// Handle the 'gix alias' command
+pub fn handle_alias_command(action: AliasAction) -> Result<()> {
+    match action {
+        AliasAction::Install { shell } => {
+            let shell = resolve_shell(shell)?;
+            let path = rc_path(shell)?;
+            let existing = fs::read_to_string(&path).unwrap_or_default();
+
+            // Replace an earlier block rather than stacking wrappers
+            let contents = with_wrapper(&existing, Some(wrapper_block(shell)), &path)?;
+            if contents == existing {
+                println!("\x1b[1;33m⚠ The git wrapper is already installed in {}\x1b[0m", path.display());
+                return Ok(());
+            }
+            write_rc(&path, &contents)?;
+
+            println!("\x1b[1;32m✓ Installed the git wrapper in {}\x1b[0m", path.display());
+            println!("   Open a new shell (or source the file) so 'git' runs through gix.");
+        }
+        AliasAction::Remove { shell } => {
+            let shell = resolve_shell(shell)?;
+            let path = rc_path(shell)?;
+            let existing = fs::read_to_string(&path).unwrap_or_default();
+            if !is_installed(shell) {
+                println!("\x1b[1;33m⚠ No git wrapper found in {}\x1b[0m", path.display());
+                return Ok(());
+            }
+            write_rc(&path, &with_wrapper(&existing, None, &path)?)?;
+
+            println!("\x1b[1;32m✓ Removed the git wrapper from {}\x1b[0m", path.display());
+            println!("   Open a new shell (or run 'unset -f git' / 'functions -e git') to stop using it.");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use anyhow::{Context, Result};
+use dialoguer::{theme::ColorfulTheme, Input, Password};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::{load_config, save_config};
+use crate::profile::{select_profile, AuthMethod, Profile};
+use crate::signing::SigningFormat;
+
+/// Format version of the decrypted bundle contents
+const BUNDLE_VERSION: u32 = 1;
+
+/// A key file carried by a bundle
+#[derive(Serialize, Deserialize)]
+struct BundledFile {
+    /// File name the key had, reused under ~/.ssh on the target machine
+    name: String,
+    contents: String,
+    private: bool,
+}
+
+/// Everything needed to recreate a profile on another machine
+#[derive(Serialize, Deserialize)]
+struct ProfileBundle {
+    version: u32,
+    profile: Profile,
+    #[serde(default)]
+    files: Vec<BundledFile>,
+    /// Armored OpenPGP secret key of the signing key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gpg_secret_key: Option<String>,
+}
+
+/// Run gpg with the passphrase on the first line of stdin, followed by `input`
+fn run_gpg(args: &[&str], passphrase: &str, input: &[u8]) -> Result<Vec<u8>> {
+    let mut child = Command::new("gpg")
+        .args(["--batch", "--yes", "--quiet", "--pinentry-mode", "loopback", "--passphrase-fd", "0"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("gpg is required for bundles but could not be run")?;
+
+    let mut stdin = child.stdin.take().context("Failed to talk to gpg")?;
+    stdin.write_all(format!("{}\n", passphrase).as_bytes())?;
+    stdin.write_all(input)?;
+    drop(stdin);
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let reason = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{}", reason.trim().trim_start_matches("gpg: "));
+    }
+    Ok(output.stdout)
+}
+
+/// Key files of the profile: authentication and SSH signing keys with their public halves
+fn key_files(profile: &Profile) -> Result<Vec<(String, PathBuf, bool)>> {
+    let mut keys: Vec<&str> = Vec::new();
+    keys.extend(profile.auth.ssh_keys());
+    if let Some(signing) = profile.signing.as_ref().filter(|s| s.format == SigningFormat::Ssh) {
+        keys.push(&signing.key_path);
+    }
+
+    let mut files = Vec::new();
+    for key in keys {
+        let private = crate::ssh::resolve_key_path(key);
+        if !private.exists() {
+            anyhow::bail!("SSH key not found at: {}", key);
+        }
+        let public = PathBuf::from(format!("{}.pub", private.display()));
+        for (path, is_private) in [(private, true), (public, false)] {
+            if !path.exists() || files.iter().any(|(_, p, _)| *p == path) {
+                continue;
+            }
+            let name = path.file_name().context("Invalid key path")?.to_string_lossy().to_string();
+            files.push((name, path, is_private));
+        }
+    }
+    Ok(files)
+}
+
+fn ask_new_passphrase() -> Result<String> {
+    Ok(Password::with_theme(&ColorfulTheme::default())
+        .with_prompt("Bundle passphrase")
+        .with_confirmation("Repeat the passphrase", "Passphrases don't match")
+        .validate_with(|input: &String| -> Result<(), &str> {
+            if input.chars().count() >= 8 {
+                Ok(())
+            } else {
+                Err("Use at least 8 characters")
+            }
+        })
+        .interact()?)
+}
+
+/// Handle the 'gix profile bundle' command: export a profile and its keys, encrypted
+pub fn handle_bundle_command(name: Option<String>, output: Option<PathBuf>) -> Result<()> {
+    let config = load_config()?;
+    let profile = match &name {
+        Some(n) => config
+            .find_profile(n)
+            .with_context(|| format!("Profile '{}' not found", n))?,
+        None => select_profile(&config).context("No profile selected")?,
+    };
+
+    let mut files = Vec::new();
+    for (file_name, path, private) in key_files(profile)? {
+        let contents = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        files.push(BundledFile { name: file_name, contents, private });
+    }
+
+    let gpg_secret_key = match profile.signing.as_ref().filter(|s| s.format == SigningFormat::Openpgp) {
+        Some(signing) if signing.smartcard => {
+            println!(
+                "\x1b[1;33m⚠ The signing key {} lives on a smartcard: bring the card, only a reference is bundled.\x1b[0m",
+                signing.key_path
+            );
+            None
+        }
+        Some(signing) => {
+            // gpg asks for the key's own passphrase before exporting it
+            let output = Command::new("gpg")
+                .args(["--armor", "--export-secret-keys", &signing.key_path])
+                .output()
+                .context("Failed to run gpg")?;
+            if !output.status.success() || output.stdout.is_empty() {
+                anyhow::bail!("Could not export the GPG signing key {}", signing.key_path);
+            }
+            Some(String::from_utf8_lossy(&output.stdout).to_string())
+        }
+        None => None,
+    };
+
+    for token in profile.auth.tokens() {
+        if let Some(var) = crate::profile::token_env_var(token) {
+            println!("\x1b[1;33m⚠ The token is read from ${}: set it on the target machine too.\x1b[0m", var);
+        }
+    }
+
+    let bundle = ProfileBundle {
+        version: BUNDLE_VERSION,
+        profile: profile.clone(),
+        files,
+        gpg_secret_key,
+    };
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.gixbundle", profile.profile_name)));
+    if output.exists() {
+        anyhow::bail!("{} already exists", output.display());
+    }
+
+    let passphrase = ask_new_passphrase()?;
+    let output_arg = output.to_string_lossy().to_string();
+    run_gpg(
+        &["--symmetric", "--cipher-algo", "AES256", "--output", &output_arg],
+        &passphrase,
+        serde_json::to_string(&bundle)?.as_bytes(),
+    )
+    .context("Failed to encrypt the bundle")?;
+
+    println!("\x1b[1;32m✓ Profile '{}' bundled into {}\x1b[0m", profile.profile_name, output.display());
+    let gpg = if bundle.gpg_secret_key.is_some() { ", GPG signing key" } else { "" };
+    println!("   Contains: profile settings, {} key file(s){}", bundle.files.len(), gpg);
+    println!("   Copy it to the other machine and run '\x1b[1mgix profile unbundle {}\x1b[0m'.", output.display());
+    println!("   Anyone with the file and the passphrase can use this identity: delete it once imported.");
+    Ok(())
+}
+
+/// Write a bundled key under ~/.ssh, reusing an identical existing file
+///
+/// Keys written here (not reused) are added to `created`.
+fn install_key(ssh_dir: &Path, file: &BundledFile, created: &mut Vec<PathBuf>) -> Result<PathBuf> {
+    // The name comes from the bundle: it must not point outside ~/.ssh
+    if matches!(file.name.as_str(), "" | "." | "..") || file.name.contains(['/', '\\', ':']) {
+        anyhow::bail!("The bundle has an invalid key file name: '{}'", file.name);
+    }
+    let path = ssh_dir.join(&file.name);
+    if path.exists() {
+        if fs::read_to_string(&path).ok().as_deref() == Some(file.contents.as_str()) {
+            return Ok(path);
+        }
+        anyhow::bail!("{} already exists with a different key; move it away first", path.display());
+    }
+    // Private keys get owner-only permissions, which ssh insists on
+    crate::config::write_atomic(&path, &file.contents, file.private)?;
+    created.push(path.clone());
+    Ok(path)
+}
+
+/// Install the bundle's keys, point the profile at them, validate it and import the GPG key
+///
+/// Validation comes before the GPG import, so a rejected profile leaves no key in gpg.
+fn install_bundle(
+    files: &[BundledFile],
+    gpg_secret_key: Option<&str>,
+    profile: &mut Profile,
+    ssh_dir: &Path,
+    created: &mut Vec<PathBuf>,
+) -> Result<Vec<(String, PathBuf)>> {
+    let mut installed = Vec::new();
+    for key in files {
+        installed.push((key.name.clone(), install_key(ssh_dir, key, created)?));
+    }
+
+    if let AuthMethod::SSH { key_path, host_keys } = &mut profile.auth {
+        for key_path in std::iter::once(key_path).chain(host_keys.values_mut()) {
+            if let Some(path) = relocated(key_path, &installed) {
+                *key_path = path;
+            }
+        }
+    }
+    if let Some(signing) = profile.signing.as_mut().filter(|s| s.format == SigningFormat::Ssh) {
+        if let Some(path) = relocated(&signing.key_path, &installed) {
+            signing.key_path = path;
+        }
+    }
+    profile.validate()?;
+
+    if let Some(key) = gpg_secret_key {
+        let status = Command::new("gpg")
+            .args(["--batch", "--quiet", "--import"])
+            .stdin(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                child.stdin.take().map(|mut s| s.write_all(key.as_bytes())).transpose()?;
+                child.wait()
+            })
+            .context("Failed to run gpg")?;
+        if !status.success() {
+            anyhow::bail!("Could not import the GPG signing key");
+        }
+    }
+    Ok(installed)
+}
+
+/// Path of the installed key that replaces `key_path` from the source machine
+fn relocated(key_path: &str, installed: &[(String, PathBuf)]) -> Option<String> {
+    let name = Path::new(&key_path.replace('\\', "/")).file_name()?.to_string_lossy().to_string();
+    installed
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, path)| path.to_string_lossy().to_string())
+}
+
+/// Handle the 'gix profile unbundle' command: import a bundle made with 'gix profile bundle'
+pub fn handle_unbundle_command(file: PathBuf) -> Result<()> {
+    let mut config = load_config()?;
+    let encrypted = fs::read(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+
+    let passphrase: String = Password::with_theme(&ColorfulTheme::default())
+        .with_prompt("Bundle passphrase")
+        .interact()?;
+    let decrypted = run_gpg(&["--decrypt"], &passphrase, &encrypted).context("Failed to decrypt the bundle")?;
+    let bundle: ProfileBundle = serde_json::from_slice(&decrypted).context("Not a gix profile bundle")?;
+    if bundle.version > BUNDLE_VERSION {
+        anyhow::bail!("The bundle uses format v{}; run 'gix update' to import it", bundle.version);
+    }
+
+    let mut profile = bundle.profile;
+    if config.find_profile(&profile.profile_name).is_some() {
+        let taken: Vec<String> = config.profiles.iter().map(|p| p.profile_name.clone()).collect();
+        profile.profile_name = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("A profile named '{}' exists. Import as", profile.profile_name))
+            .validate_with(|input: &String| -> Result<(), &str> {
+                if taken.iter().any(|t| crate::profile::same_profile_name(t, input)) {
+                    Err("A profile with this name already exists")
+                } else {
+                    Ok(())
+                }
+            })
+            .interact_text()?;
+    }
+
+    let ssh_dir = BaseDirs::new()
+        .context("Could not determine home directory")?
+        .home_dir()
+        .join(".ssh");
+    if !ssh_dir.exists() {
+        fs::create_dir_all(&ssh_dir)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&ssh_dir, fs::Permissions::from_mode(0o700))?;
+        }
+    }
+    // Nothing of a rejected bundle stays behind
+    let mut created = Vec::new();
+    let installed = match install_bundle(&bundle.files, bundle.gpg_secret_key.as_deref(), &mut profile, &ssh_dir, &mut created) {
+        Ok(installed) => installed,
+        Err(e) => {
+            for path in &created {
+                fs::remove_file(path).ok();
+            }
+            return Err(e);
+        }
+    };
+
+    let name = profile.profile_name.clone();
+    config.profiles.push(profile);
+    save_config(&config)?;
+
+    println!("\x1b[1;32m✓ Profile '{}' imported from {}\x1b[0m", name, file.display());
+    for (_, path) in &installed {
+        println!("   🔑 {}", path.display());
+    }
+    if bundle.gpg_secret_key.is_some() {
+        println!("   ✍️  GPG signing key imported");
+    }
+    println!("   Delete {} now that it is imported.", file.display());
+    Ok(())
+}
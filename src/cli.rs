@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 /// CLI structure and command definitions for gix
 #[derive(Parser, Debug)]
@@ -23,12 +23,38 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
 
+    /// Operate on the repository at this path instead of the current directory
+    #[arg(long, global = true, value_name = "PATH")]
+    pub repo: Option<std::path::PathBuf>,
+
+    /// Don't print gix's own messages around git commands (like GIX_BANNER=off)
+    #[arg(long, global = true)]
+    pub quiet: bool,
+
+    /// Print JSON instead of colored text (status, profile list, doctor)
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Use this workspace's profiles, rules and default (like GIX_WORKSPACE)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub workspace: Option<String>,
+
+    /// Keep gix's config and state in your own config directory, and 'gix update' installs
+    /// into ~/.local/bin: for shared or read-only installs (like GIX_USER=1)
+    #[arg(long, global = true)]
+    pub user: bool,
+
+    /// Never prompt: resolve profiles from GIX_PROFILE and rules, or fail (also when CI is set)
+    #[arg(long, global = true)]
+    pub no_input: bool,
+
     /// If no subcommand is provided, these args are passed to git
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     pub git_args: Vec<String>,
 }
 
 #[derive(Subcommand, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Commands {
     /// Manage profiles (add, list, edit, delete)
     Profile {
@@ -41,14 +67,36 @@ pub enum Commands {
     Use {
         /// Name of the profile to use
         name: Option<String>,
+        /// Only use the profile for commands talking to this remote
+        #[arg(long)]
+        remote: Option<String>,
+        /// Only use the profile for commits touching this subdirectory (monorepos)
+        #[arg(long, conflicts_with = "remote")]
+        path: Option<std::path::PathBuf>,
+        /// Write the identity and SSH key to the global git config instead of this repository
+        #[arg(long, conflicts_with_all = ["remote", "path"])]
+        global: bool,
     },
     /// Set a global default profile
     Set {
         /// Name of the profile to set as default
         name: Option<String>,
+        /// Remove the default profile
+        #[arg(long, conflicts_with = "name")]
+        clear: bool,
     },
     /// Show current profile status
-    Status,
+    Status {
+        /// Show who switched this repository between profiles, and when
+        #[arg(long)]
+        history: bool,
+    },
+    /// Print the active profile name for shell prompts (nothing and exit 1 when none)
+    Whoami {
+        /// Append the profile's email ("Work <me@work.com>")
+        #[arg(long)]
+        email: bool,
+    },
     /// Show version information
     Version,
     /// Check for updates and update gix
@@ -56,9 +104,420 @@ pub enum Commands {
         /// Force update even if already on latest version
         #[arg(short, long)]
         force: bool,
+        /// Download and verify the update without replacing the binary
+        #[arg(long)]
+        dry_run: bool,
+        /// Install without asking (required when not run in a terminal)
+        #[arg(short, long)]
+        yes: bool,
     },
     /// Run diagnostics to check gix setup
-    Doctor,
+    Doctor {
+        /// Fix problems that can be repaired automatically (e.g. key permissions)
+        #[arg(long)]
+        fix: bool,
+        /// Scan gix state, logs and managed repositories for plaintext tokens
+        #[arg(long)]
+        secrets: bool,
+    },
+    /// Verify signatures of recent commits against the profile's signing key
+    Verify {
+        /// Revision range to check (e.g. origin/main..HEAD)
+        range: Option<String>,
+        /// Number of recent commits to check when no range is given
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+        /// Write a report (JSON, CSV or HTML) to this file
+        #[arg(long, value_name = "FILE")]
+        report: Option<std::path::PathBuf>,
+        /// Report format (defaults to the file extension, then JSON)
+        #[arg(long, value_enum)]
+        format: Option<ReportFormat>,
+    },
+    /// Manage co-authors and pairing sessions for commits
+    Coauthor {
+        #[command(subcommand)]
+        action: Option<CoauthorAction>,
+    },
+    /// Create an isolated HOME with fixture profiles and a bare remote (for tests)
+    #[cfg(feature = "test-env")]
+    #[command(name = "test-env", hide = true)]
+    TestEnv {
+        /// New or empty directory for the sandbox, kept afterwards (default: a new temporary directory)
+        #[arg(long)]
+        dir: Option<std::path::PathBuf>,
+        /// Command to run inside the sandbox; a temporary sandbox is removed afterwards
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// Print a shell completion script (completes profile names from your config)
+    Completions {
+        #[arg(value_enum)]
+        shell: CompletionShell,
+    },
+    /// Print completion candidates for a partial command line (used by shell scripts)
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// Words after `gix`, the last one being completed
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        words: Vec<String>,
+    },
+    /// Restore the identity and auth configuration from before the last profile change
+    Undo,
+    /// Stop using a profile here and restore the git config from before gix
+    Unuse {
+        /// Also restore values that were changed outside gix
+        #[arg(long)]
+        force: bool,
+    },
+    /// Stop intercepting git commands (or set GIX_DISABLE=1 for one shell)
+    Off,
+    /// Resume intercepting git commands
+    On,
+    /// Show usage statistics of intercepted commands (failures, slow remotes)
+    Stats {
+        /// Report the author identities of the repository's history instead
+        #[arg(long)]
+        authors: bool,
+    },
+    /// Suggest host rules learned from the usage log (owners always used with one profile)
+    Suggest {
+        /// Create every suggested rule without asking
+        #[arg(short, long)]
+        yes: bool,
+        /// Include suggestions dismissed earlier
+        #[arg(long)]
+        all: bool,
+    },
+    /// Read or change global settings (`gix config` itself is git's config)
+    Settings {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Manage rules that map repositories to profiles (e.g. by email domain)
+    Rule {
+        #[command(subcommand)]
+        action: RuleAction,
+    },
+    /// Manage workspaces: separate sets of profiles, rules and defaults
+    Workspace {
+        #[command(subcommand)]
+        action: Option<WorkspaceAction>,
+    },
+    /// Manage SSH connections opened for profiles
+    Ssh {
+        #[command(subcommand)]
+        action: SshAction,
+    },
+    /// Print or write an .envrc block exporting the profile environment for direnv
+    Direnv {
+        /// Profile to export (defaults to the profile of the current repository)
+        name: Option<String>,
+        /// Write the block into .envrc at the repository root instead of printing it
+        #[arg(long)]
+        write: bool,
+    },
+    /// Show which profile, identity and auth gix would use for a repository or clone URL, without changing anything
+    Inspect {
+        /// Repository path or clone URL
+        target: String,
+    },
+    /// Run a command with a profile's identity, SSH key and environment variables
+    Exec {
+        /// Profile to use (defaults to the profile of the current repository)
+        #[arg(long, short)]
+        profile: Option<String>,
+        /// Command and arguments, e.g. `gix exec -- npm publish`
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Match remote URLs to the profile's protocol (`gix remote` itself is git's remote)
+    Remotes {
+        #[command(subcommand)]
+        action: RemoteAction,
+    },
+    /// Git credential helper serving profile tokens ('gix credential-helper install' sets it up)
+    CredentialHelper {
+        #[command(subcommand)]
+        action: CredentialHelperAction,
+    },
+    /// Show how gix would run a git command (profile, credentials, -c flags) without running it
+    Explain {
+        /// Git command and arguments, e.g. `gix explain push origin main`
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        args: Vec<String>,
+    },
+    /// Clone a repository with the right profile and configure it, whatever is intercepted
+    /// (`gix clone` itself is git's clone, run like any other git command)
+    CloneAs {
+        /// Profile to clone with (default: GIX_PROFILE, host rules and profile hosts, the default profile)
+        #[arg(short, long)]
+        profile: Option<String>,
+        /// Keep the URL's protocol instead of switching to the profile's (SSH or HTTPS)
+        #[arg(long)]
+        keep_url: bool,
+        /// Repository URL, optional directory and git clone options, e.g. `gix clone-as <url> --depth 1`
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        args: Vec<String>,
+    },
+    /// Maintain the repositories gix has configured
+    Repos {
+        #[command(subcommand)]
+        action: ReposAction,
+    },
+    /// Assign profiles in bulk to repositories not yet managed by gix
+    Adopt {
+        /// Directory to scan
+        #[arg(default_value = ".")]
+        dir: std::path::PathBuf,
+        /// How many directory levels to descend
+        #[arg(long, default_value_t = 5)]
+        max_depth: usize,
+    },
+    /// Audit the git identity of every repository under a directory
+    Scan {
+        /// Directory to scan
+        #[arg(default_value = ".")]
+        dir: std::path::PathBuf,
+        /// How many directory levels to descend
+        #[arg(long, default_value_t = 5)]
+        max_depth: usize,
+        /// Apply the expected profile to misconfigured repositories
+        #[arg(long)]
+        fix: bool,
+        /// Write a report (JSON, CSV or HTML) to this file
+        #[arg(long, value_name = "FILE")]
+        report: Option<std::path::PathBuf>,
+        /// Report format (defaults to the file extension, then JSON)
+        #[arg(long, value_enum)]
+        format: Option<ReportFormat>,
+        /// Number of recent commits per repository to check for the report
+        #[arg(long, default_value_t = 100)]
+        commits: usize,
+    },
+    /// Shell integration hooks (automatic profile switching on cd)
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Install or remove a shell wrapper that makes `git` run through gix
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+    /// Run end-to-end checks in a throwaway HOME and repository
+    Selftest,
+    /// Opt in to anonymous usage counts, or see exactly what would be sent
+    Telemetry {
+        #[command(subcommand)]
+        action: TelemetryAction,
+    },
+    /// Import existing accounts into gix profiles
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CoauthorAction {
+    /// Add a co-author preset ("Name <email>") to a profile
+    Add {
+        author: String,
+        /// Profile to edit (defaults to the one detected here)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Remove a co-author preset from a profile
+    Remove {
+        author: String,
+        /// Profile to edit (defaults to the one detected here)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// List co-author presets
+    List {
+        /// Profile to show (defaults to the one detected here)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// End the pairing session in this repository
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TelemetryAction {
+    /// Start counting commands and error classes (off by default)
+    On,
+    /// Stop and delete the unsent counts
+    Off,
+    /// Print the next report exactly as it would be sent
+    Show,
+    /// Send the counts now instead of waiting for the weekly report
+    Send,
+    /// Delete the unsent counts
+    Reset,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Show all settings with their current values
+    List,
+    /// Print the value of a setting
+    Get { key: String },
+    /// Change a setting
+    Set { key: String, value: String },
+    /// Edit the whole global config in $EDITOR (validated before saving)
+    Edit,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RuleAction {
+    /// List configured rules
+    List,
+    /// Map every email at a domain, or every clone URL on a host, to a profile
+    #[command(group(clap::ArgGroup::new("target").required(true).args(["domain", "host"])))]
+    Add {
+        /// Email domain (e.g. acme.com)
+        #[arg(long)]
+        domain: Option<String>,
+        /// Host or host/path pattern of clone URLs (e.g. github.com/acme/*)
+        #[arg(long)]
+        host: Option<String>,
+        /// Profile selected when the domain or host pattern matches
+        profile: String,
+    },
+    /// Remove a rule by its number in 'gix rule list'
+    Remove { index: usize },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WorkspaceAction {
+    /// List workspaces and show the active one
+    List,
+    /// Create an empty workspace
+    New { name: String },
+    /// Switch to a workspace ("default" for ~/.gix/config.json)
+    Use { name: String },
+    /// Delete a workspace with its profiles and rules
+    Remove { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReposAction {
+    /// Point every repository of a profile at its current SSH key (after a key rotation)
+    MigrateKey {
+        /// Profile whose key changed
+        profile: String,
+    },
+    /// Create a repository on GitHub, GitLab, Bitbucket or Gitea with a profile's account
+    Create {
+        /// Name of the new repository
+        name: String,
+        /// Make it public (default: private)
+        #[arg(long)]
+        public: bool,
+        /// Profile whose account owns it (default: the current repository's)
+        #[arg(long)]
+        profile: Option<String>,
+        /// Host to create it on (default: the profile's host)
+        #[arg(long)]
+        host: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CredentialHelperAction {
+    /// Make gix the first global credential helper and set credential_mode to helper
+    Install,
+    /// Remove gix from the global credential helpers and set credential_mode back to auto
+    Uninstall,
+    /// Called by git: print the username and token for the request on stdin
+    Get,
+    /// Called by git: nothing to do, tokens are kept in profiles
+    Store,
+    /// Called by git when a token was rejected: report it
+    Erase,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RemoteAction {
+    /// Rewrite remote URLs to the protocol of the profile (git@host:path for SSH, https:// for tokens)
+    Fix {
+        /// Profile whose authentication the remotes should match (defaults to the repository's)
+        #[arg(long, short)]
+        profile: Option<String>,
+        /// Rewrite without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SshAction {
+    /// Close shared SSH connections (all profiles, or one)
+    Close {
+        /// Profile whose connections to close
+        name: Option<String>,
+    },
+    /// Regenerate the gix host aliases in ~/.ssh/config
+    Sync,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HookAction {
+    /// Print a hook for your shell (eval it from your shell rc file)
+    Shell {
+        shell: Shell,
+        /// Apply the resolved profile silently instead of warning
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Compare the resolved profile with git config (run by the shell hook)
+    Check {
+        /// Apply the resolved profile silently instead of warning
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+/// File formats for scan and verify reports
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Html,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AliasAction {
+    /// Add the wrapper to your shell rc file (detected from $SHELL if omitted)
+    Install { shell: Option<Shell> },
+    /// Remove the wrapper from your shell rc file
+    Remove { shell: Option<Shell> },
+}
+
+/// Shells supported by gix integrations
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Shells 'gix completions' writes scripts for
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportSource {
+    /// Wrap accounts stored by git credential helpers as token profiles
+    Credentials,
 }
 
 #[derive(Subcommand, Debug)]
@@ -67,6 +526,14 @@ pub enum ProfileAction {
     List,
     /// Add a new profile
     Add,
+    /// Set the global default profile (same as 'gix set')
+    SetDefault {
+        /// Name of the profile to set as default
+        name: Option<String>,
+        /// Remove the default profile
+        #[arg(long, conflicts_with = "name")]
+        clear: bool,
+    },
     /// Edit an existing profile
     Edit {
         /// Name of the profile to edit
@@ -76,5 +543,47 @@ pub enum ProfileAction {
     Delete {
         /// Name of the profile to delete
         name: Option<String>,
+        /// Erase it (and its token) now instead of keeping it in the trash
+        #[arg(long)]
+        permanent: bool,
+    },
+    /// Check a profile's SSH key or token against its hosts
+    Test {
+        /// Name of the profile to test
+        name: Option<String>,
+    },
+    /// Bring back a deleted profile from the trash
+    Restore {
+        /// Name of the deleted profile
+        name: Option<String>,
+    },
+    /// Export a profile with its keys into a passphrase-encrypted file
+    Bundle {
+        /// Name of the profile to export
+        name: Option<String>,
+        /// Bundle file to write (default: <profile>.gixbundle)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Import a profile from a file made with 'gix profile bundle'
+    Unbundle {
+        /// Bundle file to import
+        file: std::path::PathBuf,
+    },
+    /// Write profiles to a portable JSON file (all profiles when none are named)
+    Export {
+        /// Profiles to export
+        names: Vec<String>,
+        /// File to write (default: standard output)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Replace tokens and other stored secrets with env: references
+        #[arg(long)]
+        no_secrets: bool,
+    },
+    /// Add the profiles of a file made with 'gix profile export'
+    Import {
+        /// Export file to read
+        file: std::path::PathBuf,
     },
 }
@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use crate::config::load_config;
+use crate::credential;
+use crate::profile::{select_profile, Profile};
+
+/// Options of `git clone` that take a value, as `--opt value` or `--opt=value`
+const LONG_VALUE_OPTIONS: [&str; 17] = [
+    "--origin",
+    "--branch",
+    "--upload-pack",
+    "--template",
+    "--config",
+    "--depth",
+    "--shallow-since",
+    "--shallow-exclude",
+    "--reference",
+    "--reference-if-able",
+    "--separate-git-dir",
+    "--jobs",
+    "--server-option",
+    "--filter",
+    "--bundle-uri",
+    "--ref-format",
+    "--revision",
+];
+
+/// Short options of `git clone` that take a value (`-b main` or `-bmain`)
+const SHORT_VALUE_OPTIONS: [char; 5] = ['o', 'b', 'u', 'c', 'j'];
+
+/// The arguments of a `git clone`, parsed the way git reads them
+#[derive(Debug, Clone)]
+pub struct CloneArgs {
+    pub url: String,
+    /// Position of the URL in the parsed arguments
+    pub url_index: usize,
+    pub directory: Option<PathBuf>,
+    /// `--bare` or `--mirror`: git names the directory `<name>.git`
+    pub bare: bool,
+}
+
+impl CloneArgs {
+    /// Parse the arguments that follow `clone`
+    pub fn parse(args: &[String]) -> Option<CloneArgs> {
+        let mut positional: Vec<(usize, &String)> = Vec::new();
+        let mut bare = false;
+        let mut options_done = false;
+        let mut i = 0;
+        while i < args.len() {
+            let arg = &args[i];
+            if options_done || !arg.starts_with('-') || arg == "-" {
+                positional.push((i, arg));
+            } else if arg == "--" {
+                options_done = true;
+            } else if let Some(long) = arg.strip_prefix("--") {
+                let name = long.split('=').next().unwrap_or(long);
+                if matches!(name, "bare" | "mirror") {
+                    bare = true;
+                }
+                if !long.contains('=') && LONG_VALUE_OPTIONS.contains(&arg.as_str()) {
+                    i += 1;
+                }
+            } else {
+                // A cluster like `-qb main`: the first value option takes the rest or the next argument
+                let cluster = &arg[1..];
+                if let Some(at) = cluster.find(|c| SHORT_VALUE_OPTIONS.contains(&c)) {
+                    if at + 1 == cluster.len() {
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        let mut positional = positional.into_iter();
+        let (url_index, url) = positional.next()?;
+        Some(CloneArgs {
+            url: url.clone(),
+            url_index,
+            directory: positional.next().map(|(_, d)| PathBuf::from(d)),
+            bare,
+        })
+    }
+
+    /// Directory git clones into: the one given, else the "humanish" part of the URL
+    pub fn target_dir(&self) -> Option<PathBuf> {
+        if let Some(dir) = &self.directory {
+            return Some(dir.clone());
+        }
+        let trimmed = self.url.trim_end_matches(['/', '\\']);
+        let trimmed = trimmed.strip_suffix("/.git").unwrap_or(trimmed);
+        // UNC and Windows sources (\\server\share\repo.git) use backslashes
+        let name = trimmed.rsplit(['/', '\\', ':']).next()?;
+        let name = name.strip_suffix(".git").or_else(|| name.strip_suffix(".bundle")).unwrap_or(name);
+        if name.is_empty() {
+            return None;
+        }
+        Some(PathBuf::from(if self.bare { format!("{}.git", name) } else { name.to_string() }))
+    }
+}
+
+/// Profile for a clone without `--profile`: GIX_PROFILE, host rules and profile hosts,
+/// the default profile, else the user's choice
+fn clone_profile(config: &crate::config::Config, git_args: &[String]) -> Result<Profile> {
+    if let Some(p) = crate::detection::env_profile(config)? {
+        return Ok(p.clone());
+    }
+    if let Some(p) = crate::detection::clone_rule_profile(config, git_args) {
+        return Ok(p.clone());
+    }
+    if let Some(p) = config.default_profile.as_deref().and_then(|name| config.find_profile(name)) {
+        return Ok(p.clone());
+    }
+    if crate::settings::no_input() {
+        std::process::exit(crate::detection::report_unresolved(config, git_args));
+    }
+    crate::settings::banner_warning("\x1b[1;33m⚠ No profile matches this URL.\x1b[0m");
+    Ok(select_profile(config).context("No profile selected")?.clone())
+}
+
+/// Handle the 'gix clone-as' command: clone with the right profile, then configure the repository
+///
+/// Unlike an intercepted `git clone`, it works whether or not `clone` is intercepted, and
+/// switches the URL to the profile's protocol so the new remote authenticates with it.
+pub fn handle_clone_command(mut profile: Option<String>, mut keep_url: bool, args: Vec<String>) -> Result<()> {
+    // Clap hands everything after the URL to git: take back gix's own flags
+    let mut rest = args.into_iter();
+    let mut args = Vec::new();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--" => {
+                args.push(arg);
+                args.extend(rest.by_ref());
+            }
+            "-p" | "--profile" => profile = rest.next(),
+            "--keep-url" => keep_url = true,
+            a => match a.strip_prefix("--profile=") {
+                Some(name) => profile = Some(name.to_string()),
+                None => args.push(arg),
+            },
+        }
+    }
+
+    let clone = CloneArgs::parse(&args).context("Give the repository URL to clone, e.g. 'gix clone-as git@github.com:org/repo.git'")?;
+    let mut config = load_config()?;
+    let mut git_args: Vec<String> = std::iter::once("clone".to_string()).chain(args).collect();
+
+    // A secret typed into the URL must not end up in .git/config or the usage log
+    let mut url_credentials = credential::find_clone_credentials(&git_args);
+    let created_profile = match &mut url_credentials {
+        Some(creds) => credential::offer_token_profile(&mut config, creds)?,
+        None => None,
+    };
+
+    let profile = match (profile, created_profile) {
+        (Some(name), _) => config.find_profile(&name).with_context(|| format!("Profile '{}' not found", name))?.clone(),
+        (None, Some(p)) => p,
+        (None, None) => clone_profile(&config, &git_args)?,
+    };
+    crate::settings::banner(format!(
+        "\x1b[1;36m🔀 Using profile:\x1b[0m \x1b[1;32m{}\x1b[0m ({})",
+        profile.profile_name, profile.email
+    ));
+
+    // SSH profiles clone over SSH and token profiles over HTTPS, so the remote keeps working
+    if !keep_url && url_credentials.is_none() {
+        if let Some(url) = crate::submodule::rewrite_url(&clone.url, &profile.auth) {
+            crate::settings::banner(format!("\x1b[1;36m🔁 Cloning from {}\x1b[0m", url));
+            git_args[clone.url_index + 1] = url;
+        }
+    }
+
+    crate::git::run_intercepted(&config, &profile, &git_args, url_credentials.as_ref())
+}
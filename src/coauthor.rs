@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use dialoguer::{theme::ColorfulTheme, MultiSelect};
+
+use crate::cli::CoauthorAction;
+use crate::config::{load_config, load_local_config, save_config, save_local_config, Config};
+use crate::git::{detect_profile, is_inside_git_repo};
+
+/// Check that a co-author is written as "Name <email>"
+fn validate_coauthor(author: &str) -> Result<()> {
+    let author = author.trim();
+    let valid = author.ends_with('>')
+        && author
+            .split_once('<')
+            .map(|(name, email)| !name.trim().is_empty() && email.contains('@'))
+            .unwrap_or(false);
+
+    if !valid {
+        anyhow::bail!("Co-author must look like 'Jane Doe <jane@example.com>', got: {}", author);
+    }
+    Ok(())
+}
+
+/// Find the index of the profile to edit: explicit name or the one detected here
+fn target_profile(config: &Config, name: Option<String>) -> Result<usize> {
+    let name = match name {
+        Some(n) => n,
+        None => detect_profile(config)
+            .map(|p| p.profile_name.clone())
+            .context("No profile detected here. Pass --profile <name>.")?,
+    };
+
+    config
+        .profiles
+        .iter()
+        .position(|p| p.is_named(&name))
+        .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", name))
+}
+
+/// `Co-authored-by` trailer lines for the current pairing session
+pub fn session_trailers() -> Vec<String> {
+    load_local_config()
+        .map(|local| {
+            local
+                .coauthors
+                .iter()
+                .map(|a| format!("Co-authored-by: {}", a))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Handle the 'gix coauthor' command
+pub fn handle_coauthor_command(action: Option<CoauthorAction>) -> Result<()> {
+    let mut config = load_config()?;
+
+    match action {
+        Some(CoauthorAction::Add { author, profile }) => {
+            validate_coauthor(&author)?;
+            let idx = target_profile(&config, profile)?;
+            let p = &mut config.profiles[idx];
+            let author = author.trim().to_string();
+
+            if p.coauthors.contains(&author) {
+                println!("\x1b[1;33m⚠ '{}' is already a co-author of {}.\x1b[0m", author, p.profile_name);
+                return Ok(());
+            }
+
+            p.coauthors.push(author.clone());
+            let profile_name = p.profile_name.clone();
+            save_config(&config)?;
+            println!("\x1b[1;32m✓ Added co-author {} to {}\x1b[0m", author, profile_name);
+        }
+        Some(CoauthorAction::Remove { author, profile }) => {
+            let idx = target_profile(&config, profile)?;
+            let p = &mut config.profiles[idx];
+            let before = p.coauthors.len();
+            p.coauthors.retain(|a| a != author.trim());
+
+            if p.coauthors.len() == before {
+                anyhow::bail!("'{}' is not a co-author of {}", author, p.profile_name);
+            }
+            save_config(&config)?;
+            println!("\x1b[1;32m✓ Removed co-author {}\x1b[0m", author);
+        }
+        Some(CoauthorAction::List { profile }) => {
+            let idx = target_profile(&config, profile)?;
+            let p = &config.profiles[idx];
+
+            println!("\x1b[1;36m👥 Co-authors for {}\x1b[0m\n", p.profile_name);
+            if p.coauthors.is_empty() {
+                println!("   None. Add one with 'gix coauthor add \"Name <email>\"'.");
+            }
+            let active = load_local_config().map(|l| l.coauthors).unwrap_or_default();
+            for author in &p.coauthors {
+                let marker = if active.contains(author) { " \x1b[1;32m(pairing)\x1b[0m" } else { "" };
+                println!("   • {}{}", author, marker);
+            }
+        }
+        Some(CoauthorAction::Clear) => {
+            let mut local = load_local_config().unwrap_or_default();
+            local.coauthors.clear();
+            save_local_config(&local)?;
+            println!("\x1b[1;32m✓ Pairing session ended.\x1b[0m");
+        }
+        None => {
+            if !is_inside_git_repo() {
+                anyhow::bail!("Not inside a git repository");
+            }
+
+            let idx = target_profile(&config, None)?;
+            let p = &config.profiles[idx];
+            if p.coauthors.is_empty() {
+                println!("\x1b[1;33m⚠ No co-authors configured for {}.\x1b[0m", p.profile_name);
+                println!("   Add one with 'gix coauthor add \"Name <email>\"'.");
+                return Ok(());
+            }
+
+            let mut local = load_local_config().unwrap_or_default();
+            let defaults: Vec<bool> = p.coauthors.iter().map(|a| local.coauthors.contains(a)).collect();
+
+            let selections = MultiSelect::with_theme(&ColorfulTheme::default())
+                .with_prompt("Who are you pairing with?")
+                .items(&p.coauthors)
+                .defaults(&defaults)
+                .interact()?;
+
+            local.coauthors = selections.into_iter().map(|i| p.coauthors[i].clone()).collect();
+            save_local_config(&local)?;
+
+            if local.coauthors.is_empty() {
+                println!("\x1b[1;32m✓ Pairing session ended.\x1b[0m");
+            } else {
+                println!(
+                    "\x1b[1;32m✓ Commits made through gix will credit:\x1b[0m {}",
+                    local.coauthors.join(", ")
+                );
+                if !config.intercepted_commands.iter().any(|c| c == "commit") {
+                    println!("\x1b[1;33m⚠ 'commit' is not intercepted. Enable it with 'gix commands'.\x1b[0m");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
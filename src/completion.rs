@@ -0,0 +1,160 @@
+use anyhow::Result;
+use clap::CommandFactory;
+
+use crate::cli::{Cli, CompletionShell};
+use crate::config::load_config;
+
+/// Subcommand paths whose next positional argument is a profile name
+const PROFILE_POSITIONALS: [&[&str]; 9] = [
+    &["use"],
+    &["set"],
+    &["direnv"],
+    &["ssh", "close"],
+    &["rule", "add"],
+    &["profile", "edit"],
+    &["profile", "delete"],
+    &["profile", "bundle"],
+    &["profile", "export"],
+];
+
+/// Options that take a profile name as value
+const PROFILE_OPTIONS: [&str; 1] = ["--profile"];
+
+/// Names of all configured profiles
+fn profile_names() -> Vec<String> {
+    load_config()
+        .map(|c| c.profiles.into_iter().map(|p| p.profile_name).collect())
+        .unwrap_or_default()
+}
+
+/// Compute completion candidates for a partial command line
+///
+/// `words` are the arguments after `gix`; the last one is the word being completed
+/// (possibly empty).
+pub fn complete(words: &[String]) -> Vec<String> {
+    let (current, previous) = match words.split_last() {
+        Some((last, rest)) => (last.as_str(), rest),
+        None => ("", &[][..]),
+    };
+
+    // Option values
+    if let Some(prev) = previous.last() {
+        if PROFILE_OPTIONS.contains(&prev.as_str()) {
+            return filter(profile_names(), current);
+        }
+    }
+
+    // Walk the clap tree as far as the typed subcommands go
+    let mut cmd = Cli::command();
+    let mut path: Vec<String> = Vec::new();
+    for word in previous.iter().filter(|w| !w.starts_with('-')) {
+        match cmd.find_subcommand(word).cloned() {
+            Some(sub) => {
+                path.push(sub.get_name().to_string());
+                cmd = sub;
+            }
+            None => break,
+        }
+    }
+
+    let positionals = previous.iter().filter(|w| !w.starts_with('-')).count() - path.len();
+
+    if PROFILE_POSITIONALS.iter().any(|p| p.iter().eq(path.iter())) && positionals == 0 {
+        return filter(profile_names(), current);
+    }
+
+    if current.starts_with('-') {
+        let options = cmd
+            .get_arguments()
+            .filter_map(|a| a.get_long().map(|l| format!("--{}", l)))
+            .collect();
+        return filter(options, current);
+    }
+
+    if positionals > 0 {
+        return vec![];
+    }
+
+    let mut candidates: Vec<String> = cmd
+        .get_subcommands()
+        .filter(|s| !s.is_hide_set())
+        .map(|s| s.get_name().to_string())
+        .collect();
+
+    // At the top level, intercepted git commands are valid too
+    if path.is_empty() {
+        if let Ok(config) = load_config() {
+            candidates.extend(config.intercepted_commands);
+        }
+    }
+
+    filter(candidates, current)
+}
+
+/// Keep candidates starting with the typed prefix, sorted and deduplicated
+fn filter(mut candidates: Vec<String>, prefix: &str) -> Vec<String> {
+    candidates.retain(|c| c.starts_with(prefix));
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// Handle the hidden 'gix __complete' command used by shell completion scripts
+pub fn handle_complete_command(words: Vec<String>) -> Result<()> {
+    for candidate in complete(&words) {
+        println!("{}", candidate);
+    }
+    Ok(())
+}
+
+/// Completion script for a shell; every script asks `gix __complete`, so
+/// profile names and intercepted commands are always current
+pub fn completion_script(shell: CompletionShell) -> &'static str {
+    match shell {
+        CompletionShell::Bash => r#"# gix completion (bash) - add to ~/.bashrc:
+#   eval "$(gix completions bash)"
+_gix() {
+  local IFS=$'\n'
+  COMPREPLY=($(command gix __complete "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null))
+}
+complete -o default -F _gix gix
+"#,
+        CompletionShell::Zsh => r#"#compdef gix
+# gix completion (zsh) - add to ~/.zshrc (after compinit):
+#   eval "$(gix completions zsh)"
+_gix() {
+  local -a candidates
+  candidates=("${(@f)$(command gix __complete "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+  candidates=(${candidates:#})
+  compadd -a candidates
+}
+compdef _gix gix
+"#,
+        CompletionShell::Fish => r#"# gix completion (fish) - save as ~/.config/fish/completions/gix.fish:
+#   gix completions fish > ~/.config/fish/completions/gix.fish
+function __gix_complete
+    set -l words (commandline -opc)
+    set -e words[1]
+    command gix __complete $words (commandline -ct) 2>/dev/null
+end
+complete -c gix -f -a '(__gix_complete)'
+"#,
+        CompletionShell::Powershell => r#"# gix completion (PowerShell) - add to $PROFILE:
+#   gix completions powershell | Out-String | Invoke-Expression
+Register-ArgumentCompleter -Native -CommandName gix -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object { $_.ToString() })
+    if ($wordToComplete -eq '') { $words += '""' }
+    & gix __complete @words 2>$null | ForEach-Object {
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+    }
+}
+"#,
+    }
+}
+
+/// Handle the 'gix completions' command
+pub fn handle_completions_command(shell: CompletionShell) -> Result<()> {
+    print!("{}", completion_script(shell));
+    Ok(())
+}
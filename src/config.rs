@@ -1,19 +1,129 @@
 use anyhow::{Context, Result};
 use directories::BaseDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::BufReader;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::profile::Profile;
+use crate::credential::CredentialMode;
+use crate::detection::{default_detection_order, DetectionSource};
+use crate::profile::{Profile, TrashedProfile};
+use crate::rules::Rule;
+use crate::settings::{BannerMode, ColorMode, PersistPrompt, UpdateChannel};
+use crate::signing::SigningPin;
+
+/// Schema version of ~/.gix/config.json written by this build
+pub const CONFIG_VERSION: u32 = 2;
+/// Schema version of .gix/config.json written by this build
+pub const LOCAL_CONFIG_VERSION: u32 = 1;
 
 /// Global configuration structure
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Config {
+    /// Schema version (0 for files written before versioning)
+    #[serde(default)]
+    pub version: u32,
     pub profiles: Vec<Profile>,
     #[serde(default = "default_intercepted_commands")]
     pub intercepted_commands: Vec<String>,
     pub default_profile: Option<String>,
+    #[serde(default)]
+    pub credential_mode: CredentialMode,
+    /// Fallback rules mapping repositories to profiles
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    /// Record intercepted commands in ~/.gix/usage.log
+    #[serde(default = "default_true")]
+    pub logging: bool,
+    #[serde(default)]
+    pub update_channel: UpdateChannel,
+    /// Set by 'gix off': git commands pass straight through
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interception_disabled: bool,
+    /// Precedence of the profile detection sources
+    #[serde(default = "default_detection_order")]
+    pub detection_order: Vec<DetectionSource>,
+    /// ssh executable used in generated ssh commands (e.g. a wrapper script or ssh.exe)
+    #[serde(default)]
+    pub ssh_program: Option<String>,
+    /// Where messages around intercepted commands go
+    #[serde(default)]
+    pub banner: BannerMode,
+    /// Deleted profiles, restorable with 'gix profile restore' until they expire
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trash: Vec<TrashedProfile>,
+    /// Send anonymous command counts (opt-in, see 'gix telemetry show')
+    #[serde(default)]
+    pub telemetry: bool,
+    /// Whether to configure a repository after a profile was picked for it
+    #[serde(default)]
+    pub persist_prompt: PersistPrompt,
+    /// Authenticate SSH profiles through host aliases in ~/.ssh/config instead of core.sshCommand
+    #[serde(default)]
+    pub ssh_aliases: bool,
+    /// Hint after intercepted commands when 'gix suggest' has rules to propose
+    #[serde(default = "default_true")]
+    pub suggestions: bool,
+    /// Colors in gix's own messages
+    #[serde(default)]
+    pub color: ColorMode,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CONFIG_VERSION,
+            profiles: vec![],
+            intercepted_commands: default_intercepted_commands(),
+            default_profile: None,
+            credential_mode: CredentialMode::default(),
+            rules: vec![],
+            logging: true,
+            update_channel: UpdateChannel::default(),
+            interception_disabled: false,
+            detection_order: default_detection_order(),
+            ssh_program: None,
+            banner: BannerMode::default(),
+            trash: vec![],
+            telemetry: false,
+            persist_prompt: PersistPrompt::default(),
+            ssh_aliases: false,
+            suggestions: true,
+            color: ColorMode::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Look up a profile by name, ignoring case and surrounding whitespace
+    pub fn find_profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.is_named(name))
+    }
+
+    /// Name of the default profile when it no longer matches any profile
+    pub fn missing_default_profile(&self) -> Option<&str> {
+        self.default_profile.as_deref().filter(|name| self.find_profile(name).is_none())
+    }
+
+    /// Trim stored names so hand-edited configs match lookups and are saved normalized
+    fn normalize(&mut self) {
+        for profile in &mut self.profiles {
+            profile.profile_name = profile.profile_name.trim().to_string();
+        }
+        if let Some(name) = &self.default_profile {
+            // Store the default with the profile's own spelling
+            self.default_profile = Some(
+                self.find_profile(name)
+                    .map(|p| p.profile_name.clone())
+                    .unwrap_or_else(|| name.trim().to_string()),
+            );
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
 }
 
 /// Default commands to intercept
@@ -22,16 +132,32 @@ fn default_intercepted_commands() -> Vec<String> {
 }
 
 /// Local repository configuration
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct LocalConfig {
+    /// Schema version (0 for files written before versioning)
+    #[serde(default)]
+    pub version: u32,
     pub selected_profile: Option<String>,
+    /// Co-authors of the current pairing session ("Name <email>")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coauthors: Vec<String>,
+    /// Profiles used for commands talking to specific remotes (remote name → profile)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remotes: BTreeMap<String, String>,
+    /// Profiles used for commits touching subdirectories (path from the repository root → profile)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub paths: BTreeMap<String, String>,
+    /// Signing behaviour pinned for this repository, whatever the profile says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningPin>,
+    /// The selected profile is set by team policy and cannot be switched with gix
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
 }
 
-/// Get the global configuration file path (~/.gix/config.json)
+/// Get the global configuration file path (~/.gix/config.json, or the active workspace's)
 pub fn get_global_config_path() -> Result<PathBuf> {
-    BaseDirs::new()
-        .map(|dirs| dirs.home_dir().join(".gix").join("config.json"))
-        .context("Could not determine home directory")
+    crate::workspace::config_path(&get_gix_home_dir()?)
 }
 
 /// Get the local repository configuration path (.gix/config.json)
@@ -48,53 +174,107 @@ pub fn load_config() -> Result<Config> {
         let reader = BufReader::new(file);
         let mut config: Config = serde_json::from_reader(reader)
             .context("Failed to parse config file. It may be corrupted.")?;
+
+        // Saving would drop whatever a newer schema added
+        if config.version > CONFIG_VERSION {
+            anyhow::bail!(
+                "{} uses config schema v{} but this gix only knows v{}. Run 'gix update'.",
+                path.display(),
+                config.version,
+                CONFIG_VERSION
+            );
+        }
         
+        // v2 added origin remote detection; saved orders predate it
+        if config.version < 2 && !config.detection_order.contains(&DetectionSource::Remote) {
+            let after_local = config
+                .detection_order
+                .iter()
+                .position(|s| *s == DetectionSource::Local)
+                .map_or(0, |i| i + 1);
+            config.detection_order.insert(after_local, DetectionSource::Remote);
+        }
+
         // Ensure intercepted_commands has defaults if empty
         if config.intercepted_commands.is_empty() {
             config.intercepted_commands = default_intercepted_commands();
         }
+        config.normalize();
+        // Dropped from the file the next time the config is saved
+        config.trash.retain(|t| !t.expired());
         
         Ok(config)
     } else {
-        Ok(Config {
-            profiles: vec![],
-            intercepted_commands: default_intercepted_commands(),
-            default_profile: None,
-        })
+        Ok(Config::default())
     }
 }
 
-/// Save global configuration to file with secure permissions
-pub fn save_config(config: &Config) -> Result<()> {
-    let path = get_global_config_path()?;
-    
-    // Create parent directory if it doesn't exist
+/// Write a file through a temporary sibling and a rename, so concurrent
+/// readers see either the old or the new contents, never a partial file
+pub fn write_atomic(path: &Path, contents: &str, private: bool) -> Result<()> {
+    let path = &crate::paths::long_path(path);
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
-    
-    // Write config to file
-    let file = File::create(&path)?;
-    serde_json::to_writer_pretty(file, config)?;
-    
-    // Set secure permissions on Unix (readable only by owner)
+
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+    fs::write(&tmp, contents).with_context(|| format!("Failed to write {}", tmp.display()))?;
+
+    // Set secure permissions on Unix (readable only by owner) before the file becomes visible
     #[cfg(unix)]
-    {
+    if private {
         use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&path)?.permissions();
-        perms.set_mode(0o600);
-        fs::set_permissions(&path, perms)?;
+        fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600))?;
     }
-    
+    #[cfg(not(unix))]
+    let _ = private;
+
+    fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))?;
     Ok(())
 }
 
+/// Save global configuration to file with secure permissions
+pub fn save_config(config: &Config) -> Result<()> {
+    let path = get_global_config_path()?;
+    ensure_writable(&path)?;
+    let config = Config { version: CONFIG_VERSION, ..config.clone() };
+    write_atomic(&path, &serde_json::to_string_pretty(&config)?, true)
+}
+
+/// Exclusive lock on a repository's gix state, held until the returned file is dropped
+///
+/// Serializes gix processes that change the same repository's git config,
+/// `.gix/config.json` and history (e.g. a post-clone setup and a shell hook).
+pub fn lock_repo(dir: &Path) -> Result<File> {
+    let path = crate::paths::long_path(&dir.join(".gix").join("config.lock"));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.lock().with_context(|| format!("Failed to lock {}", path.display()))?;
+    Ok(file)
+}
+
 /// Load local repository configuration
 pub fn load_local_config() -> Option<LocalConfig> {
-    let path = get_local_config_path();
-    
+    load_local_config_from_path(&get_local_config_path())
+}
+
+/// Load local repository configuration of a specific directory
+pub fn load_local_config_from_dir(dir: &Path) -> Option<LocalConfig> {
+    load_local_config_from_path(&dir.join(get_local_config_path()))
+}
+
+fn load_local_config_from_path(path: &Path) -> Option<LocalConfig> {
+    let path = &crate::paths::long_path(path);
     if path.exists() {
-        if let Ok(file) = File::open(&path) {
+        if let Ok(file) = File::open(path) {
             let reader = BufReader::new(file);
             return serde_json::from_reader(reader).ok();
         }
@@ -111,24 +291,130 @@ pub fn save_local_profile_selection(profile_name: &str) -> Result<()> {
 /// Save local repository configuration to a specific directory
 pub fn save_local_profile_selection_to_dir(profile_name: &str, dir: PathBuf) -> Result<()> {
     let path = dir.join(".gix").join("config.json");
-    
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
+
+    // Keep other local settings (e.g. co-authors) intact
+    let mut local_config = load_local_config_from_path(&path).unwrap_or_default();
+    local_config.selected_profile = Some(profile_name.to_string());
+
+    save_local_config_to_dir(&local_config, &dir)
+}
+
+/// Save the whole local repository configuration
+pub fn save_local_config(local_config: &LocalConfig) -> Result<()> {
+    save_local_config_to_dir(local_config, &std::env::current_dir()?)
+}
+
+/// Save the whole local repository configuration to a specific directory
+pub fn save_local_config_to_dir(local_config: &LocalConfig, dir: &Path) -> Result<()> {
+    let path = dir.join(".gix").join("config.json");
+
+    // Saving would drop whatever a newer schema added
+    if let Some(existing) = load_local_config_from_path(&path).filter(|l| l.version > LOCAL_CONFIG_VERSION) {
+        anyhow::bail!(
+            "{} uses schema v{} but this gix only knows v{}. Run 'gix update'.",
+            path.display(),
+            existing.version,
+            LOCAL_CONFIG_VERSION
+        );
     }
-    
-    let local_config = LocalConfig {
-        selected_profile: Some(profile_name.to_string()),
-    };
-    
-    let file = File::create(&path)?;
-    serde_json::to_writer_pretty(file, &local_config)?;
-    
-    Ok(())
+
+    let local_config = LocalConfig { version: LOCAL_CONFIG_VERSION, ..local_config.clone() };
+    write_atomic(&path, &serde_json::to_string_pretty(&local_config)?, false)
 }
 
-/// Get the gix directory in home
+/// Get the gix directory: ~/.gix, or the user's own copy in user scope
 pub fn get_gix_home_dir() -> Result<PathBuf> {
+    if user_scope() {
+        return user_gix_dir();
+    }
+    shared_gix_dir()
+}
+
+/// ~/.gix, which a shared workstation or system package may provide read-only
+fn shared_gix_dir() -> Result<PathBuf> {
     BaseDirs::new()
         .map(|dirs| dirs.home_dir().join(".gix"))
         .context("Could not determine home directory")
 }
+
+/// gix's files kept in the user's config directory (e.g. ~/.config/gix)
+pub fn user_gix_dir() -> Result<PathBuf> {
+    BaseDirs::new()
+        .map(|dirs| dirs.config_dir().join("gix"))
+        .context("Could not determine config directory")
+}
+
+/// Set by `--user`: read and write gix's files in the user's config directory
+pub const USER_SCOPE_ENV: &str = "GIX_USER";
+
+/// Whether `--user`/GIX_USER asked for user scope explicitly
+pub fn user_scope_requested() -> bool {
+    std::env::var(USER_SCOPE_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// User scope is in effect when requested, and from then on once the user's copy exists
+pub fn user_scope() -> bool {
+    user_scope_requested() || user_gix_dir().is_ok_and(|dir| dir.join("config.json").exists())
+}
+
+/// Switch this process and the gix processes it starts to user scope (`--user`)
+///
+/// The first time, the user's copy starts as a copy of the shared config, so the
+/// profiles already set up keep working.
+pub fn enter_user_scope() -> Result<()> {
+    std::env::set_var(USER_SCOPE_ENV, "1");
+    let dir = user_gix_dir()?;
+    let user_config = dir.join("config.json");
+    let shared_config = shared_gix_dir()?.join("config.json");
+    if !user_config.exists() && shared_config.exists() {
+        let contents = fs::read_to_string(&shared_config)
+            .with_context(|| format!("Failed to read {}", shared_config.display()))?;
+        write_atomic(&user_config, &contents, true)?;
+        crate::settings::banner(format!(
+            "\x1b[1;36m📁 Using your own copy of the gix config: {}\x1b[0m",
+            user_config.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Whether a file could be written: its directory (or the first existing parent) accepts new files
+///
+/// Probed with a real file, since permission bits alone miss ownership, ACLs and
+/// read-only mounts.
+pub fn is_writable(path: &Path) -> bool {
+    if path.exists() && fs::metadata(path).is_ok_and(|m| m.permissions().readonly()) {
+        return false;
+    }
+    let Some(mut dir) = path.parent() else {
+        return false;
+    };
+    while !dir.exists() {
+        match dir.parent() {
+            Some(parent) => dir = parent,
+            None => return false,
+        }
+    }
+    let probe = dir.join(format!(".gix-write-test.{}", std::process::id()));
+    match fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => fs::remove_file(&probe).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Fail with an explanation, and the `--user` alternative, when a gix file is read-only
+pub fn ensure_writable(path: &Path) -> Result<()> {
+    if is_writable(path) {
+        return Ok(());
+    }
+    if user_scope() {
+        anyhow::bail!("{} is not writable. Check the permissions of {}.", path.display(), path.parent().unwrap_or(path).display());
+    }
+    anyhow::bail!(
+        "{} is read-only (a shared or system-managed gix install?).\n\
+         gix keeps reading it, but cannot save changes there. Run the command again with \
+         --user to keep your own copy in {}; gix uses that copy from then on.",
+        path.display(),
+        user_gix_dir().map(|d| d.display().to_string()).unwrap_or_else(|_| "your config directory".to_string())
+    )
+}
@@ -0,0 +1,208 @@
+//! `gix credential-helper`: git's credential helper protocol, answered from profiles
+//!
+//! With `credential_mode = helper`, git asks gix for a token whenever it needs one
+//! instead of gix approving tokens into another helper before each command.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::process::Command;
+
+use crate::cli::CredentialHelperAction;
+use crate::config::{load_config, save_config, Config};
+use crate::credential::CredentialMode;
+use crate::profile::{AuthMethod, Profile};
+use crate::rules::Rule;
+
+/// Profile an intercepted command runs with, passed to the helper git starts for it
+pub const PROFILE_ENV: &str = "GIX_HELPER_PROFILE";
+
+/// HTTPS hosts that command authenticates to (comma separated), as approve mode would
+pub const HOSTS_ENV: &str = "GIX_HELPER_HOSTS";
+
+/// `credential.helper` value that runs this gix binary
+pub fn helper_value() -> Result<String> {
+    let exe = std::env::current_exe().context("Could not locate the running gix binary")?;
+    let exe = crate::paths::plain_path(&exe).display().to_string();
+    // git runs `!` helpers through the shell
+    Ok(format!("!'{}' credential-helper", exe.replace('\'', r"'\''")))
+}
+
+/// Whether a `credential.helper` value is gix's own helper
+fn is_gix_helper(value: &str) -> bool {
+    value.trim_end().ends_with(" credential-helper") && value.contains("gix")
+}
+
+/// Attributes git sends on stdin (`key=value` lines up to a blank line)
+fn read_request() -> Result<BTreeMap<String, String>> {
+    let mut attributes = BTreeMap::new();
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            attributes.insert(key.to_string(), value.to_string());
+        }
+    }
+    Ok(attributes)
+}
+
+/// Whether a profile's token may go to `host`: a host of its `hosts` patterns, of its
+/// host rules or of its `host_tokens`, or one the intercepted command authenticates to
+///
+/// The helper is installed globally, so any HTTPS request on the machine reaches it.
+fn serves_host(config: &Config, profile: &Profile, host: &str) -> bool {
+    let covers = |pattern: &str| crate::push::glob_match(&crate::rules::pattern_host(pattern), &host.to_ascii_lowercase());
+    let intercepted = std::env::var(PROFILE_ENV).is_ok_and(|name| profile.is_named(&name))
+        && std::env::var(HOSTS_ENV).is_ok_and(|hosts| hosts.split(',').any(|h| h.eq_ignore_ascii_case(host)));
+    let AuthMethod::Token { host_tokens, .. } = &profile.auth else {
+        return false;
+    };
+    intercepted
+        || profile.hosts.iter().any(|p| covers(p))
+        || host_tokens.keys().any(|h| h.eq_ignore_ascii_case(host))
+        || config.rules.iter().any(|rule| match rule {
+            Rule::Host { pattern, profile: name } => profile.is_named(name) && covers(pattern),
+            Rule::EmailDomain { .. } => false,
+        })
+}
+
+/// Token profile serving a request: the intercepted command's profile, GIX_PROFILE,
+/// the only profile whose hosts or host rules match the URL, else the repository's;
+/// only if that profile is meant for the host
+fn request_profile<'a>(config: &'a Config, url: &str, host: &str) -> Option<&'a Profile> {
+    let intercepted = std::env::var(PROFILE_ENV).ok().and_then(|name| config.find_profile(&name));
+    let profile = intercepted
+        .or_else(|| crate::detection::env_profile(config).ok().flatten())
+        .or_else(|| match crate::rules::profiles_for_url(config, url).as_slice() {
+            [profile] => Some(*profile),
+            _ => None,
+        })
+        .or_else(|| crate::detection::detect(config).map(|d| d.profile))?;
+    serves_host(config, profile, host).then_some(profile)
+}
+
+/// Answer `get`: username and token for an HTTPS host, or nothing so git asks the next helper
+fn get(config: &Config, request: &BTreeMap<String, String>) -> Result<()> {
+    let (Some("https"), Some(host)) = (request.get("protocol").map(|p| p.as_str()), request.get("host")) else {
+        return Ok(());
+    };
+    let url = format!("https://{}/{}", host, request.get("path").map(|p| p.as_str()).unwrap_or_default());
+    // The host part without a port, as profiles name hosts
+    let bare_host = host.split(':').next().unwrap_or(host);
+    let Some(profile) = request_profile(config, &url, bare_host) else {
+        return Ok(());
+    };
+    let username = crate::git::token_username(bare_host, &profile.name);
+
+    // A URL naming another account is for someone else's credentials
+    if request.get("username").is_some_and(|u| *u != username && *u != profile.name) {
+        return Ok(());
+    }
+
+    let token = crate::profile::resolve_token(profile.auth.token_for(bare_host).unwrap_or_default())?;
+    let answer = crate::secrets::SecretString::new(format!("username={}\npassword={}\n", username, &*token));
+    std::io::stdout().write_all(answer.as_bytes())?;
+    Ok(())
+}
+
+/// Answer `erase`: the token lives in the profile, so only say that it was rejected
+fn erase(config: &Config, request: &BTreeMap<String, String>) {
+    let (Some("https"), Some(host)) = (request.get("protocol").map(|p| p.as_str()), request.get("host")) else {
+        return;
+    };
+    let url = format!("https://{}/{}", host, request.get("path").map(|p| p.as_str()).unwrap_or_default());
+    if let Some(profile) = request_profile(config, &url, host.split(':').next().unwrap_or(host)) {
+        eprintln!(
+            "\x1b[1;33m⚠ {} rejected the token of profile '{}'. Update it with 'gix profile edit {}'.\x1b[0m",
+            host, profile.profile_name, profile.profile_name
+        );
+    }
+}
+
+/// `credential.helper` values of the global git config, in order (an empty value resets the list)
+fn global_helpers(dir: &Path) -> Vec<String> {
+    Command::new("git")
+        .current_dir(dir)
+        .args(["config", "--global", "--get-all", "credential.helper"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().map(|l| l.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Replace the global `credential.helper` list
+fn write_global_helpers(dir: &Path, helpers: &[String]) -> Result<()> {
+    crate::git::run_git_config(dir, "--global", &["--unset-all", "credential.helper"]).ok();
+    for helper in helpers {
+        crate::git::run_git_config(dir, "--global", &["--add", "credential.helper", helper])?;
+    }
+    Ok(())
+}
+
+/// Put gix first in the global helper list and switch to `credential_mode = helper`
+fn install() -> Result<()> {
+    let dir = std::env::current_dir()?;
+    let ours = helper_value()?;
+    let mut helpers: Vec<String> = global_helpers(&dir).into_iter().filter(|h| !is_gix_helper(h)).collect();
+    // First after the last reset, which would otherwise drop it
+    let position = helpers.iter().rposition(|h| h.is_empty()).map_or(0, |i| i + 1);
+    helpers.insert(position, ours.clone());
+    write_global_helpers(&dir, &helpers)?;
+
+    let mut config = load_config()?;
+    config.credential_mode = CredentialMode::Helper;
+    save_config(&config)?;
+
+    println!("\x1b[1;32m✓ gix is now git's credential helper for token profiles.\x1b[0m");
+    println!("   credential.helper = {}", ours);
+    println!("   Hosts without a token profile go on to your other helpers.");
+
+    // System helpers (e.g. Git for Windows' manager) are asked first
+    let before: Vec<String> = crate::credential::configured_helpers()
+        .into_iter()
+        .take_while(|h| !is_gix_helper(&h.value))
+        .map(|h| format!("{} ({})", h.value, h.origin))
+        .collect();
+    if !before.is_empty() {
+        println!("\x1b[1;33m⚠ git asks these helpers first: {}\x1b[0m", before.join(", "));
+        println!("   Intercepted commands still use gix's tokens; other tools may get those helpers' credentials.");
+    }
+    Ok(())
+}
+
+/// Remove gix from the global helper list and go back to `credential_mode = auto`
+fn uninstall() -> Result<()> {
+    let dir = std::env::current_dir()?;
+    let helpers = global_helpers(&dir);
+    let remaining: Vec<String> = helpers.iter().filter(|h| !is_gix_helper(h)).cloned().collect();
+    if remaining.len() != helpers.len() {
+        write_global_helpers(&dir, &remaining)?;
+    }
+
+    let mut config = load_config()?;
+    if config.credential_mode == CredentialMode::Helper {
+        config.credential_mode = CredentialMode::Auto;
+        save_config(&config)?;
+    }
+    println!("\x1b[1;32m✓ gix is no longer a credential helper; tokens are approved before each command again.\x1b[0m");
+    Ok(())
+}
+
+/// Handle the 'gix credential-helper' command
+pub fn handle_credential_helper_command(action: CredentialHelperAction) -> Result<()> {
+    match action {
+        CredentialHelperAction::Install => install(),
+        CredentialHelperAction::Uninstall => uninstall(),
+        CredentialHelperAction::Get => get(&load_config()?, &read_request()?),
+        CredentialHelperAction::Erase => {
+            erase(&load_config()?, &read_request()?);
+            Ok(())
+        }
+        // Tokens are kept in profiles: nothing to store
+        CredentialHelperAction::Store => read_request().map(|_| ()),
+    }
+}
@@ -0,0 +1,499 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, Select};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::config::{load_local_config, load_local_config_from_dir, Config, LocalConfig};
+use crate::git::is_inside_git_repo;
+use crate::profile::Profile;
+
+/// A mechanism that can select the profile of a repository
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionSource {
+    /// Selection saved in the repository's .gix/config.json
+    Local,
+    /// The origin remote matches a profile's host patterns
+    Remote,
+    /// Global default profile
+    Default,
+    /// Local user.email is a profile's primary or alternate address
+    Email,
+    /// Local user.email matches an email domain rule
+    Rule,
+}
+
+impl DetectionSource {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            DetectionSource::Local => "local .gix file",
+            DetectionSource::Remote => "origin remote host",
+            DetectionSource::Default => "global default",
+            DetectionSource::Email => "email match",
+            DetectionSource::Rule => "email domain rule",
+        }
+    }
+}
+
+/// Precedence used unless the user configured `detection_order`
+pub fn default_detection_order() -> Vec<DetectionSource> {
+    vec![
+        DetectionSource::Local,
+        DetectionSource::Remote,
+        DetectionSource::Default,
+        DetectionSource::Email,
+        DetectionSource::Rule,
+    ]
+}
+
+pub const ALL_SOURCES: [DetectionSource; 5] = [
+    DetectionSource::Local,
+    DetectionSource::Remote,
+    DetectionSource::Default,
+    DetectionSource::Email,
+    DetectionSource::Rule,
+];
+
+/// A profile together with the source that selected it
+#[derive(Debug, Clone, Copy)]
+pub struct Detection<'a> {
+    pub profile: &'a Profile,
+    pub source: DetectionSource,
+}
+
+impl Detection<'_> {
+    /// Why this profile was chosen, e.g. "email match (w@x.com)"
+    pub fn explain(&self, config: &Config) -> String {
+        let detail = match self.source {
+            DetectionSource::Local => crate::config::get_local_config_path().display().to_string(),
+            DetectionSource::Remote => origin_url().unwrap_or_default(),
+            DetectionSource::Default => "default_profile".to_string(),
+            DetectionSource::Email => local_email().unwrap_or_default(),
+            DetectionSource::Rule => {
+                let email = local_email().unwrap_or_default();
+                match config
+                    .rules
+                    .iter()
+                    .find(|r| r.matches(&email) && config.find_profile(r.profile()).is_some())
+                {
+                    Some(rule) => format!("{}, rule {}", email, rule.describe()),
+                    None => email,
+                }
+            }
+        };
+        format!("{} ({})", self.source.describe(), detail)
+    }
+}
+
+/// Names already warned about, so repeated lookups in one run warn once
+static WARNED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// A stale profile name would otherwise silently fall through to the next detection step
+fn warn_dangling_profile(what: &str, name: &str) {
+    warn_dangling_profile_with(what, name, "Run 'gix use' or 'gix doctor --fix'.");
+}
+
+fn warn_dangling_profile_with(what: &str, name: &str, hint: &str) {
+    let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+    if warned.iter().any(|w| w == name) {
+        return;
+    }
+    warned.push(name.to_string());
+    eprintln!("\x1b[1;33m⚠ {} ('{}') no longer exists. {}\x1b[0m", what, name, hint);
+}
+
+pub fn local_email() -> Option<String> {
+    if !is_inside_git_repo() {
+        return None;
+    }
+    let output = Command::new("git")
+        .args(["config", "--local", "user.email"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let email = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!email.is_empty()).then_some(email)
+}
+
+/// URL of the current repository's origin remote
+pub fn origin_url() -> Option<String> {
+    let root = crate::git::get_git_root()?;
+    crate::git::git_config_in(&root, "remote.origin.url")
+}
+
+/// Repository root found by walking up to the nearest `.git`, without spawning git
+///
+/// Misses repositories located through GIT_DIR; callers fall back to git for those.
+fn repo_root_from_fs(start: &Path) -> Option<PathBuf> {
+    start.ancestors().find(|dir| dir.join(".git").exists()).map(Path::to_path_buf)
+}
+
+/// The repository's .gix/config.json, read from the working directory or the repository root
+///
+/// Only touches the filesystem, so detection stays cheap when a repository has one.
+fn local_selection() -> Option<LocalConfig> {
+    load_local_config().or_else(|| {
+        let cwd = std::env::current_dir().ok()?;
+        load_local_config_from_dir(&repo_root_from_fs(&cwd)?)
+    })
+}
+
+/// Profile selected by a single source, if any
+fn resolve(config: &Config, source: DetectionSource, warn: bool) -> Option<&Profile> {
+    match source {
+        DetectionSource::Local => {
+            let name = local_selection()?.selected_profile?;
+            let profile = config.find_profile(&name);
+            if profile.is_none() && warn {
+                warn_dangling_profile("The profile selected for this repository", &name);
+            }
+            profile
+        }
+        DetectionSource::Remote => {
+            let url = origin_url()?;
+            // Overlapping patterns select nothing rather than a surprising profile
+            match crate::rules::profiles_declaring_url(config, &url).as_slice() {
+                [profile] => Some(*profile),
+                _ => None,
+            }
+        }
+        DetectionSource::Default => {
+            let name = config.default_profile.as_ref()?;
+            let profile = config.find_profile(name);
+            if profile.is_none() && warn {
+                warn_dangling_profile_with(
+                    "The default profile",
+                    name,
+                    "Pick another with 'gix profile set-default' or clear it with 'gix profile set-default --clear'.",
+                );
+            }
+            profile
+        }
+        DetectionSource::Email => {
+            let email = local_email()?;
+            config.profiles.iter().find(|p| p.owns_email(&email))
+        }
+        DetectionSource::Rule => {
+            let email = local_email()?;
+            // Rules are only a fallback for addresses no profile owns
+            if config.profiles.iter().any(|p| p.owns_email(&email)) {
+                return None;
+            }
+            crate::rules::profile_for_domain(config, &email)
+        }
+    }
+}
+
+/// First source in the configured precedence that selects a profile
+pub fn detect(config: &Config) -> Option<Detection<'_>> {
+    detect_with(config, true)
+}
+
+fn detect_with(config: &Config, warn: bool) -> Option<Detection<'_>> {
+    config
+        .detection_order
+        .iter()
+        .find_map(|&source| resolve(config, source, warn).map(|profile| Detection { profile, source }))
+}
+
+/// Handle the 'gix whoami' command
+///
+/// Runs on every prompt render: no prompts, no warnings, and no git process when
+/// the repository's .gix/config.json settles it.
+pub fn handle_whoami_command(email: bool) -> Result<()> {
+    let config = crate::config::load_config()?;
+    let profile = match env_profile(&config).ok().flatten() {
+        Some(p) => Some(p),
+        None => detect_with(&config, false).map(|d| d.profile),
+    };
+    let Some(profile) = profile else {
+        std::process::exit(1);
+    };
+
+    if crate::settings::json_output() {
+        return crate::settings::print_json(&serde_json::json!({
+            "profile": profile.profile_name,
+            "email": profile.email,
+        }));
+    }
+    if email {
+        println!("{} <{}>", profile.profile_name, profile.email);
+    } else {
+        println!("{}", profile.profile_name);
+    }
+    Ok(())
+}
+
+/// Profile pinned in .gix/config.json for the remote an intercepted push/fetch/pull talks to
+pub fn remote_override<'a>(config: &'a Config, args: &[String]) -> Option<&'a Profile> {
+    if !matches!(args.first()?.as_str(), "push" | "fetch" | "pull") {
+        return None;
+    }
+    let remotes = load_local_config()?.remotes;
+    let remote = args
+        .iter()
+        .skip(1)
+        .find(|a| !a.starts_with('-'))
+        .map(|a| a.as_str())
+        .unwrap_or("origin");
+
+    let name = remotes.get(remote)?;
+    let profile = config.find_profile(name);
+    if profile.is_none() {
+        warn_dangling_profile(&format!("The profile for remote '{}'", remote), name);
+    }
+    profile
+}
+
+/// Whether commit arguments include `-a`/`--all` (also as in `-am`)
+fn commits_all(args: &[String]) -> bool {
+    args.iter().skip(1).any(|a| {
+        a == "--all"
+            || (a.starts_with('-') && !a.starts_with("--") && a[1..].split('m').next().unwrap_or("").contains('a'))
+    })
+}
+
+/// Long options of `git commit` that take a separate value
+const COMMIT_VALUE_OPTIONS: [&str; 11] = [
+    "--message", "--file", "--reuse-message", "--reedit-message", "--template", "--author", "--date", "--cleanup",
+    "--fixup", "--squash", "--trailer",
+];
+
+/// Short options of `git commit` whose value is the rest of the cluster or the next argument
+const COMMIT_SHORT_VALUE_OPTIONS: [char; 5] = ['m', 'F', 'c', 'C', 't'];
+
+/// Pathspecs given to an intercepted commit (`git commit [--] <paths>`)
+fn commit_pathspecs(args: &[String]) -> Vec<&str> {
+    let mut pathspecs = Vec::new();
+    let mut skip_value = false;
+    let mut after_dashes = false;
+    for arg in args.iter().skip(1) {
+        if skip_value {
+            skip_value = false;
+        } else if after_dashes {
+            pathspecs.push(arg.as_str());
+        } else if arg == "--" {
+            after_dashes = true;
+        } else if arg.starts_with("--") {
+            skip_value = COMMIT_VALUE_OPTIONS.contains(&arg.as_str());
+        } else if let Some(cluster) = arg.strip_prefix('-').filter(|c| !c.is_empty()) {
+            // In `-am` the value follows; in `-mfix` it is attached
+            skip_value = cluster.find(|c| COMMIT_SHORT_VALUE_OPTIONS.contains(&c)).is_some_and(|i| i == cluster.len() - 1);
+        } else {
+            pathspecs.push(arg.as_str());
+        }
+    }
+    pathspecs
+}
+
+/// Repository-relative paths an intercepted commit will record
+///
+/// Staged changes, plus the working tree for `-a` or the changes under the pathspecs
+/// given. Falls back to the working directory when nothing is staged yet.
+fn committed_paths(args: &[String]) -> Vec<String> {
+    let list = |extra: &[&str]| -> Vec<String> {
+        Command::new("git")
+            .args(["diff", "--name-only", "-z"])
+            .args(extra)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).split('\0').filter(|l| !l.is_empty()).map(String::from).collect())
+            .unwrap_or_default()
+    };
+
+    let pathspecs = commit_pathspecs(args);
+    let mut paths = if pathspecs.is_empty() {
+        list(&["--cached"])
+    } else {
+        // `git commit <paths>` records those paths as they are in the working tree
+        let staged: Vec<&str> = ["--cached", "--"].into_iter().chain(pathspecs.iter().copied()).collect();
+        let unstaged: Vec<&str> = std::iter::once("--").chain(pathspecs.iter().copied()).collect();
+        let mut paths = list(&staged);
+        paths.extend(list(&unstaged));
+        if args.iter().any(|a| a == "-i" || a == "--include") {
+            paths.extend(list(&["--cached"]));
+        }
+        paths
+    };
+    if commits_all(args) {
+        paths.extend(list(&[]));
+    }
+    if paths.is_empty() {
+        let prefix = Command::new("git")
+            .args(["rev-parse", "--show-prefix"])
+            .output()
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().trim_end_matches('/').to_string())
+            .unwrap_or_default();
+        paths.push(prefix);
+    }
+    paths
+}
+
+/// Innermost subdirectory of `paths` containing a repository-relative path
+fn owning_dir<'p>(dirs: &'p BTreeMap<String, String>, path: &str) -> Option<&'p String> {
+    dirs.keys()
+        .filter(|dir| path == dir.as_str() || path.starts_with(&format!("{}/", dir)))
+        .max_by_key(|dir| dir.len())
+}
+
+/// Profile pinned in .gix/config.json for the subdirectory an intercepted commit touches
+///
+/// A commit mixing files of differently owned directories is refused, so it can be split.
+pub fn path_override<'a>(config: &'a Config, args: &[String]) -> Result<Option<&'a Profile>> {
+    if args.first().map(|a| a != "commit").unwrap_or(true) {
+        return Ok(None);
+    }
+    // Commits are often made from inside the subdirectory, so read the root's settings
+    let local_config = crate::git::get_git_root().and_then(|root| crate::config::load_local_config_from_dir(&root));
+    let Some(dirs) = local_config.map(|l| l.paths).filter(|p| !p.is_empty()) else {
+        return Ok(None);
+    };
+
+    // Owning profile name (None: the repository's own profile) → one example path
+    let mut owners: BTreeMap<Option<&String>, String> = BTreeMap::new();
+    for path in committed_paths(args) {
+        let owner = owning_dir(&dirs, &path).and_then(|dir| dirs.get(dir));
+        owners.entry(owner).or_insert(path);
+    }
+
+    if owners.len() > 1 {
+        let listed: Vec<String> = owners
+            .iter()
+            .map(|(owner, path)| match owner {
+                Some(name) => format!("{} ({})", path, name),
+                None => format!("{} (repository profile)", path),
+            })
+            .collect();
+        anyhow::bail!(
+            "This commit mixes directories of different identities: {}. Commit them separately.",
+            listed.join(", ")
+        );
+    }
+
+    let Some(Some(name)) = owners.into_keys().next() else {
+        return Ok(None);
+    };
+    let profile = config.find_profile(name);
+    if profile.is_none() {
+        warn_dangling_profile("The profile for this directory", name);
+    }
+    Ok(profile)
+}
+
+/// Exit code when no profile can be chosen without prompting
+pub const EXIT_NO_PROFILE: i32 = 3;
+/// Exit code when several profiles match and none can be chosen without prompting
+pub const EXIT_AMBIGUOUS_PROFILE: i32 = 4;
+
+/// Profile named by GIX_PROFILE, which overrides detection (CI jobs)
+pub fn env_profile(config: &Config) -> Result<Option<&Profile>> {
+    let Some(name) = std::env::var("GIX_PROFILE").ok().filter(|n| !n.trim().is_empty()) else {
+        return Ok(None);
+    };
+    match config.find_profile(&name) {
+        Some(p) => Ok(Some(p)),
+        None => anyhow::bail!("GIX_PROFILE names an unknown profile: '{}'", name),
+    }
+}
+
+/// Profile of the only host rule matching the URL of a clone
+pub fn clone_rule_profile<'a>(config: &'a Config, args: &[String]) -> Option<&'a Profile> {
+    let url = crate::git::clone_url(args)?;
+    match crate::rules::profiles_for_url(config, url).as_slice() {
+        [profile] => Some(profile),
+        _ => None,
+    }
+}
+
+/// Explain why no profile could be chosen without prompting, and pick the exit code
+pub fn report_unresolved(config: &Config, args: &[String]) -> i32 {
+    let matching = crate::git::clone_url(args)
+        .map(|url| crate::rules::profiles_for_url(config, url))
+        .unwrap_or_default();
+
+    if matching.len() > 1 {
+        let names: Vec<&str> = matching.iter().map(|p| p.profile_name.as_str()).collect();
+        eprintln!(
+            "\x1b[1;31m✗ Several host rules or profile hosts match ({}) and prompts are disabled.\x1b[0m",
+            names.join(", ")
+        );
+        eprintln!("  Set GIX_PROFILE or make the rules or profile hosts more specific.");
+        EXIT_AMBIGUOUS_PROFILE
+    } else {
+        eprintln!("\x1b[1;31m✗ No profile matches and prompts are disabled.\x1b[0m");
+        eprintln!("  Set GIX_PROFILE, a default profile, or 'gix rule add --host <pattern> <profile>'.");
+        EXIT_NO_PROFILE
+    }
+}
+
+/// Every source that selects a profile, whatever the precedence
+pub fn candidates(config: &Config) -> Vec<Detection<'_>> {
+    ALL_SOURCES
+        .iter()
+        .filter_map(|&source| resolve(config, source, true).map(|profile| Detection { profile, source }))
+        .collect()
+}
+
+/// Sources that disagree with the chosen profile. The global default is a
+/// fallback by design, so it only conflicts when nothing more specific applies.
+pub fn conflicts<'a>(config: &'a Config, chosen: &Detection) -> Vec<Detection<'a>> {
+    candidates(config)
+        .into_iter()
+        .filter(|d| d.source != DetectionSource::Default || chosen.source == DetectionSource::Default)
+        .filter(|d| !d.profile.is_named(&chosen.profile.profile_name))
+        .collect()
+}
+
+/// Show disagreeing sources in 'gix status' and offer to settle them
+pub fn report_conflicts(config: &Config, chosen: &Detection) -> Result<()> {
+    let conflicting = conflicts(config, chosen);
+    if conflicting.is_empty() {
+        return Ok(());
+    }
+
+    println!("\n   \x1b[1;33m⚠ Detection sources disagree:\x1b[0m");
+    println!(
+        "      {:<18} → \x1b[1;32m{}\x1b[0m (used)",
+        chosen.source.describe(),
+        chosen.profile.profile_name
+    );
+    for d in &conflicting {
+        println!("      {:<18} → {}", d.source.describe(), d.profile.profile_name);
+    }
+    let order: Vec<&str> = config.detection_order.iter().map(|s| s.describe()).collect();
+    println!("      Precedence: {}", order.join(" > "));
+
+    if !crate::settings::interactive() {
+        println!("      Run 'gix use <profile>' to settle it, or change 'gix settings set detection_order'.");
+        return Ok(());
+    }
+
+    let mut choices: Vec<&Profile> = vec![chosen.profile];
+    for d in &conflicting {
+        if !choices.iter().any(|p| p.is_named(&d.profile.profile_name)) {
+            choices.push(d.profile);
+        }
+    }
+    let mut items: Vec<String> = choices
+        .iter()
+        .map(|p| format!("Use {} <{}> for this repository", p.profile_name, p.email))
+        .collect();
+    items.push("Leave as is".to_string());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Resolve the conflict")
+        .items(&items)
+        .default(items.len() - 1)
+        .interact()?;
+    if let Some(profile) = choices.get(selection) {
+        // Saves the local selection and user.email, so local and email sources agree again
+        crate::git::apply_local_config(profile)?;
+        println!("   \x1b[1;32m✓ This repository now uses {}\x1b[0m", profile.profile_name);
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use std::fs;
+
+use crate::config::{load_config, write_atomic};
+use crate::git::{detect_profile, get_git_root};
+use crate::managed_block::{self, Position};
+use crate::profile::{AuthMethod, Profile};
+use crate::ssh;
+
+const BLOCK_START: &str = "# >>> gix profile environment >>>";
+const BLOCK_END: &str = "# <<< gix profile environment <<<";
+
+/// Single-quote a value for a POSIX shell
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Environment variables that make git use a profile's identity and SSH key
+pub fn identity_env(profile: &Profile) -> Vec<(String, String)> {
+    let mut vars = vec![
+        ("GIT_AUTHOR_NAME".to_string(), profile.name.clone()),
+        ("GIT_AUTHOR_EMAIL".to_string(), profile.email.clone()),
+        ("GIT_COMMITTER_NAME".to_string(), profile.name.clone()),
+        ("GIT_COMMITTER_EMAIL".to_string(), profile.email.clone()),
+    ];
+    if let AuthMethod::SSH { .. } = &profile.auth {
+        vars.push(("GIT_SSH_COMMAND".to_string(), ssh::profile_ssh_command(profile, None)));
+    }
+    vars
+}
+
+/// Contents of the managed `.envrc` block exporting a profile's identity
+///
+/// Tokens are never written: `.envrc` files are plain text and often committed.
+pub fn envrc_block(profile: &Profile) -> String {
+    let mut lines = vec![format!("# profile: {}", profile.profile_name)];
+    for (name, value) in identity_env(profile) {
+        lines.push(format!("export {}={}", name, quote(&value)));
+    }
+    lines.join("\n")
+}
+
+/// Handle the 'gix direnv' command
+pub fn handle_direnv_command(name: Option<String>, write: bool) -> Result<()> {
+    let config = load_config()?;
+
+    let profile = match &name {
+        Some(n) => config
+            .profiles
+            .iter()
+            .find(|p| p.is_named(n))
+            .with_context(|| format!("Profile '{}' not found", n))?,
+        None => detect_profile(&config)
+            .context("No profile detected for this directory. Pass a profile name or run 'gix use' first.")?,
+    };
+
+    let block = envrc_block(profile);
+
+    if !write {
+        print!("{}", managed_block::replace("", BLOCK_START, BLOCK_END, Some(&block), Position::Last)?);
+        return Ok(());
+    }
+
+    let dir = match get_git_root() {
+        Some(root) => root,
+        None => std::env::current_dir()?,
+    };
+    let path = dir.join(".envrc");
+
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let contents = managed_block::replace(&existing, BLOCK_START, BLOCK_END, Some(&block), Position::Last)
+        .with_context(|| format!("Cannot update {}", path.display()))?;
+    write_atomic(&path, &contents, false)?;
+
+    println!("\x1b[1;32m✓ Wrote profile '{}' environment to {}\x1b[0m", profile.profile_name, path.display());
+    println!("   Run '\x1b[1mdirenv allow\x1b[0m' to load it.");
+    Ok(())
+}
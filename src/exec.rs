@@ -0,0 +1,53 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+use crate::config::load_config;
+use crate::git::detect_profile;
+use crate::profile::{select_profile, Profile};
+
+/// Handle the 'gix exec' command: run any program with a profile's environment
+///
+/// The program gets the profile's git identity, SSH command and extra `env`,
+/// plus GIX_PROFILE so gix invoked from inside it (e.g. by a build script) agrees.
+pub fn handle_exec_command(name: Option<String>, command: Vec<String>) -> Result<()> {
+    let config = load_config()?;
+
+    let profile: &Profile = match &name {
+        Some(n) => config
+            .find_profile(n)
+            .with_context(|| format!("Profile '{}' not found", n))?,
+        None => match crate::detection::env_profile(&config)?.or_else(|| detect_profile(&config)) {
+            Some(p) => p,
+            None if crate::settings::interactive() => {
+                select_profile(&config).context("No profile available")?
+            }
+            None => {
+                let code = crate::detection::report_unresolved(&config, &[]);
+                std::process::exit(code);
+            }
+        },
+    };
+
+    let (program, args) = command.split_first().context("No command given")?;
+
+    // stdout belongs to the program
+    eprintln!(
+        "\x1b[1;36m🔀 Using profile:\x1b[0m \x1b[1;32m{}\x1b[0m ({})",
+        profile.profile_name, profile.email
+    );
+
+    let status = Command::new(program)
+        .args(args)
+        .envs(crate::direnv::identity_env(profile))
+        .envs(profile.exported_env()?)
+        .env("GIX_PROFILE", &profile.profile_name)
+        // The program is the user's, not a git command gix wraps: gix inside it runs in full
+        .env_remove(crate::settings::NESTED_ENV)
+        .status()
+        .with_context(|| format!("Failed to run {}", program))?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use anyhow::Result;
+
+use crate::config::{load_config, load_local_config};
+use crate::credential;
+use crate::git::{
+    command_config_overrides, command_remote_host, detect_cloned_dir, expand_alias, header_urls, intercepted_profile,
+    is_inside_git_repo, token_hosts, token_username, with_clone_args, with_commit_trailers, worktree_add_path,
+};
+use crate::profile::{token_env_var, AuthMethod};
+use crate::secrets::redact;
+
+/// Quote an argument for display when the shell would split it
+fn shell_word(arg: &str) -> String {
+    if arg.is_empty() || arg.chars().any(|c| c.is_whitespace() || "'\"\\$`|&;<>()*?".contains(c)) {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    } else {
+        arg.to_string()
+    }
+}
+
+fn command_line(args: &[String]) -> String {
+    std::iter::once("git".to_string()).chain(args.iter().map(|a| shell_word(a))).collect::<Vec<_>>().join(" ")
+}
+
+/// Handle the 'gix explain' command: how interception would run a git command, without running it
+///
+/// Mirrors `handle_git_command` through the same helpers, but never prompts, stores
+/// credentials or touches the repository. Secret values are never printed.
+pub fn handle_explain_command(args: Vec<String>) -> Result<()> {
+    let Some(command) = args.first() else {
+        anyhow::bail!("Give the git command to explain, e.g. 'gix explain push origin main'");
+    };
+    let config = load_config()?;
+
+    println!("\x1b[1;36m🔍 {}\x1b[0m\n", command_line(&args));
+
+    let passthrough = if crate::settings::disabled_by_env() {
+        Some("GIX_DISABLE is set".to_string())
+    } else if crate::settings::nested() {
+        Some(format!("another gix command is running it ({} is set)", crate::settings::NESTED_ENV))
+    } else if config.interception_disabled {
+        Some("interception is off ('gix on' enables it)".to_string())
+    } else if !config.intercepted_commands.contains(command)
+        && expand_alias(&args).first().is_none_or(|c| !config.intercepted_commands.contains(c))
+    {
+        Some(format!("'{}' is not in intercepted_commands ('gix commands' to change)", command))
+    } else {
+        None
+    };
+    if let Some(reason) = passthrough {
+        println!("   ⏭️  Not intercepted: {}", reason);
+        println!("   ▶️  Runs unchanged: {}", command_line(&args));
+        println!("\n   \x1b[2mNothing was executed.\x1b[0m");
+        return Ok(());
+    }
+    let args = if config.intercepted_commands.contains(command) {
+        println!("   ⚙️  Intercepted: '{}' is in intercepted_commands", command);
+        args
+    } else {
+        let expanded = expand_alias(&args);
+        println!("   ⚙️  Intercepted: alias '{}' runs {}", command, command_line(&expanded));
+        expanded
+    };
+    let command = &args[0];
+
+    if let Some(creds) = credential::find_clone_credentials(&args) {
+        println!(
+            "   🔓 The URL carries credentials: gix would offer to save the token in a profile and clone {}",
+            creds.masked_url()
+        );
+    }
+
+    // Profile choice, in the same order as interception
+    let chosen = match intercepted_profile(&config, &args)? {
+        Some((p, reason)) => Some((p, reason)),
+        None => crate::detection::clone_rule_profile(&config, &args).map(|p| (p, "host rule for the clone URL".to_string())),
+    };
+    let Some((profile, reason)) = chosen else {
+        if crate::settings::no_input() {
+            println!("   \x1b[1;33m⚠ No profile: without prompts gix would stop before running git\x1b[0m");
+        } else {
+            println!("   \x1b[1;33m⚠ No profile detected: gix would ask which profile to use\x1b[0m");
+            if is_inside_git_repo() && command != "clone" {
+                println!("      and offer to configure this repository to always use it");
+            }
+        }
+        println!("\n   \x1b[2mNothing was executed.\x1b[0m");
+        return Ok(());
+    };
+    println!("   👤 Profile: \x1b[1;32m{}\x1b[0m ({})", profile.profile_name, profile.email);
+    println!("      because: {}", reason);
+
+    // Safety checks
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let host = command_remote_host(&args, &cwd);
+    if let (Some(policy), Some(host)) = (crate::policy::load_policy()?, host.as_deref()) {
+        if policy.allows(profile, host) {
+            println!("   🏢 Host policy allows {} for this profile", host);
+        } else {
+            println!("   \x1b[1;31m✗ Host policy blocks {} for this profile: the command would be refused\x1b[0m", host);
+        }
+    }
+    let protected = crate::push::protected_targets(profile, &args);
+    if !protected.is_empty() {
+        println!("   🛡️  Pushes protected branch {}: gix would ask for confirmation", protected.join(", "));
+    }
+    if profile.private_email && command == "push" {
+        println!("   🕵️  Commits are checked for the private email before pushing to public hosts");
+    }
+
+    // Authentication and credential operations
+    let mut env: Vec<(String, String)> = Vec::new();
+    let mut flags: Vec<(String, String)> = Vec::new();
+    match &profile.auth {
+        AuthMethod::SSH { .. } => {
+            let key_path = profile.auth.key_for(host.as_deref()).unwrap_or_default();
+            println!("   🔐 SSH key: {}", key_path);
+            if !crate::ssh::resolve_key_path(key_path).exists() {
+                println!("      \x1b[1;33m⚠ The key file does not exist\x1b[0m");
+            }
+            if profile.ssh.pin_host_keys {
+                if let Some(host) = &host {
+                    println!("      Pinned host keys for {} are added to the profile's known_hosts if missing", host);
+                }
+            }
+            env.push(("GIT_SSH_COMMAND".to_string(), crate::ssh::preview_ssh_command(profile, host.as_deref())));
+        }
+        AuthMethod::Token { token, host_tokens } => {
+            let source = |token: &str| match token_env_var(token) {
+                Some(var) => format!("from ${}", var),
+                None => "stored in the profile".to_string(),
+            };
+            let hosts = token_hosts(&args, &cwd);
+            let helper = config.credential_mode == credential::CredentialMode::Helper;
+            if helper {
+                flags.push(("credential.helper".to_string(), String::new()));
+                flags.push(("credential.helper".to_string(), crate::credential_helper::helper_value()?));
+            }
+            if hosts.is_empty() {
+                println!("   🔑 Token ({}): no HTTPS host to send it to", source(token));
+            }
+            for host in &hosts {
+                let token = profile.auth.token_for(host).unwrap_or(token);
+                let source = match host_tokens.keys().any(|h| h.eq_ignore_ascii_case(host)) {
+                    true => format!("{}'s own token, {}", host, source(token)),
+                    false => source(token),
+                };
+                if helper {
+                    println!("   🔑 git asks 'gix credential-helper' for the token ({}) for https://{}", source, host);
+                } else if credential::use_gcm_interop(config.credential_mode) {
+                    println!("   🔑 Git Credential Manager picks the account for {}", host);
+                    flags.push((credential::gcm_username_key(host), profile.name.clone()));
+                } else {
+                    println!(
+                        "   🔑 git credential approve: token ({}) for https://{} as {}",
+                        source,
+                        host,
+                        token_username(host, &profile.name)
+                    );
+                }
+            }
+        }
+    }
+
+    // Environment (names only: values are often credentials)
+    let mut hidden: Vec<String> = profile.env.keys().cloned().collect();
+    let header_urls = header_urls(profile, &args, &cwd);
+    for header in &profile.http.extra_headers {
+        let name = header.split(':').next().unwrap_or(header).trim();
+        hidden.push(format!(
+            "http.extraHeader {} for {} (via GIT_CONFIG_KEY_n/GIT_CONFIG_VALUE_n)",
+            name,
+            header_urls.join(" ")
+        ));
+    }
+    for (name, value) in &env {
+        println!("   🌐 {}={}", name, redact(value));
+    }
+    if !hidden.is_empty() {
+        println!("   🌐 Environment, values hidden: {}", hidden.join(", "));
+    }
+
+    // -c settings
+    let pin = load_local_config().and_then(|l| l.signing);
+    if pin == Some(crate::signing::SigningPin::Always) && profile.signing.is_none() {
+        println!("   \x1b[1;33m⚠ This repository requires signed commits, but the profile has no signing key\x1b[0m");
+    }
+    flags.extend(command_config_overrides(profile, pin));
+    println!("   🧩 -c flags:");
+    for (key, value) in &flags {
+        println!("      {}={}", key, redact(value));
+    }
+
+    let final_args = with_clone_args(&with_commit_trailers(&args, profile), profile);
+    if final_args != args {
+        println!("   ✏️  Arguments adjusted by the profile (trailers, clone flags)");
+    }
+    println!("   ▶️  Runs: {}", command_line(&final_args));
+
+    // What happens after a successful run
+    if command == "clone" {
+        if let Some(dir) = detect_cloned_dir(&args) {
+            println!("   📌 Afterwards: {} is configured with profile '{}'", dir.display(), profile.profile_name);
+        }
+    }
+    if command == "submodule" && matches!(args.get(1).map(|a| a.as_str()), Some("add" | "update")) {
+        println!("   📌 Afterwards: submodule checkouts are configured with profile '{}'", profile.profile_name);
+    }
+    if let Some(dir) = worktree_add_path(&args) {
+        println!("   📌 Afterwards: worktree {} is configured with profile '{}'", dir.display(), profile.profile_name);
+    }
+    if config.logging {
+        println!("   📝 Logged to ~/.gix/usage.log");
+    }
+
+    println!("\n   \x1b[2mNothing was executed.\x1b[0m");
+    Ok(())
+}
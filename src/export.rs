@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::{load_config, save_config};
+use crate::profile::{token_env_var, AuthMethod, Profile};
+
+/// Format version of exported profile files
+const EXPORT_VERSION: u32 = 1;
+
+/// A portable set of profiles, as written by 'gix profile export'
+#[derive(Serialize, Deserialize)]
+struct ProfileExport {
+    version: u32,
+    profiles: Vec<Profile>,
+}
+
+/// Environment variable a stripped token is expected in on the target machine
+fn token_placeholder(profile_name: &str) -> String {
+    let name: String = profile_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("env:GIX_TOKEN_{}", name)
+}
+
+/// Replace secrets stored in the profile by `env:` references; returns what was removed
+fn strip_secrets(profile: &mut Profile) -> Vec<String> {
+    let mut removed = Vec::new();
+    if let AuthMethod::Token { token, host_tokens } = &mut profile.auth {
+        if token_env_var(token).is_none() {
+            *token = token_placeholder(&profile.profile_name);
+            removed.push(format!("token (now {})", token));
+        }
+        for (host, token) in host_tokens.iter_mut().filter(|(_, t)| token_env_var(t).is_none()) {
+            *token = token_placeholder(&format!("{}_{}", profile.profile_name, host));
+            removed.push(format!("token for {} (now {})", host, token));
+        }
+    }
+    let literal_env: Vec<String> = profile
+        .env
+        .iter()
+        .filter(|(_, v)| token_env_var(v).is_none())
+        .map(|(k, _)| k.clone())
+        .collect();
+    for name in literal_env {
+        profile.env.insert(name.clone(), format!("env:{}", name));
+        removed.push(format!("env {} (now env:{})", name, name));
+    }
+    for header in &mut profile.http.extra_headers {
+        let (name, value) = header.split_once(':').unwrap_or((header.as_str(), ""));
+        if token_env_var(value.trim()).is_none() {
+            let name = name.trim().to_string();
+            *header = format!("{}: env:{}", name, name.to_ascii_uppercase().replace('-', "_"));
+            removed.push(format!("header {}", name));
+        }
+    }
+    removed
+}
+
+/// Handle the 'gix profile export' command
+pub fn handle_export_command(names: Vec<String>, output: Option<PathBuf>, no_secrets: bool) -> Result<()> {
+    let config = load_config()?;
+    let mut profiles: Vec<Profile> = if names.is_empty() {
+        config.profiles.clone()
+    } else {
+        names
+            .iter()
+            .map(|n| config.find_profile(n).cloned().with_context(|| format!("Profile '{}' not found", n)))
+            .collect::<Result<_>>()?
+    };
+    if profiles.is_empty() {
+        anyhow::bail!("No profiles to export");
+    }
+
+    let mut stripped = Vec::new();
+    if no_secrets {
+        for profile in &mut profiles {
+            for what in strip_secrets(profile) {
+                stripped.push(format!("{}: {}", profile.profile_name, what));
+            }
+        }
+    }
+
+    let export = ProfileExport { version: EXPORT_VERSION, profiles };
+    let json = serde_json::to_string_pretty(&export)?;
+    match &output {
+        Some(path) => {
+            // Secrets may be inside, so the file is private unless they were stripped
+            crate::config::write_atomic(path, &json, !no_secrets)?;
+            println!("\x1b[1;32m✓ Exported {} profile(s) to {}\x1b[0m", export.profiles.len(), path.display());
+        }
+        None => println!("{}", json),
+    }
+
+    // With the JSON on stdout, notes go to stderr so the output can be redirected
+    let notes: fn(&str) = if output.is_some() { |m| println!("{}", m) } else { |m| eprintln!("{}", m) };
+    for line in &stripped {
+        notes(&format!("   🔒 Removed {}", line));
+    }
+    if !no_secrets && export.profiles.iter().any(|p| p.auth.tokens().iter().any(|t| token_env_var(t).is_none())) {
+        notes("\x1b[1;33m⚠ The export contains tokens in plain text. Use --no-secrets to leave them out.\x1b[0m");
+    }
+    notes("   SSH and signing keys are referenced by path, not copied ('gix profile bundle' moves them too).");
+    Ok(())
+}
+
+/// What to do with an imported profile whose name is taken
+enum Conflict {
+    Skip,
+    Replace,
+    Rename(String),
+}
+
+fn ask_conflict(name: &str, taken: &[String]) -> Result<Conflict> {
+    let choice = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("A profile named '{}' already exists", name))
+        .items(&["Skip it", "Replace the existing profile", "Import under another name"])
+        .default(0)
+        .interact()?;
+    Ok(match choice {
+        1 => Conflict::Replace,
+        2 => Conflict::Rename(
+            Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("New profile name")
+                .validate_with(|input: &String| -> Result<(), &str> {
+                    if taken.iter().any(|t| crate::profile::same_profile_name(t, input)) {
+                        Err("A profile with this name already exists")
+                    } else {
+                        Ok(())
+                    }
+                })
+                .interact_text()?,
+        ),
+        _ => Conflict::Skip,
+    })
+}
+
+/// Handle the 'gix profile import' command
+pub fn handle_import_command(file: PathBuf) -> Result<()> {
+    let mut config = load_config()?;
+    let content = fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let export: ProfileExport = serde_json::from_str(&content).context("Not a gix profile export")?;
+    if export.version > EXPORT_VERSION {
+        anyhow::bail!("The file uses export format v{}; run 'gix update' to import it", export.version);
+    }
+
+    let mut imported = 0;
+    for mut profile in export.profiles {
+        let mut replace = None;
+        if let Some(idx) = config.profiles.iter().position(|p| p.is_named(&profile.profile_name)) {
+            if !crate::settings::interactive() {
+                println!("\x1b[1;33m⚠ Skipped '{}': a profile with that name exists.\x1b[0m", profile.profile_name);
+                continue;
+            }
+            let taken: Vec<String> = config.profiles.iter().map(|p| p.profile_name.clone()).collect();
+            match ask_conflict(&profile.profile_name, &taken)? {
+                Conflict::Skip => continue,
+                Conflict::Replace => replace = Some(idx),
+                Conflict::Rename(name) => profile.profile_name = name.trim().to_string(),
+            }
+        }
+
+        if let Err(e) = profile.validate() {
+            println!("\x1b[1;33m⚠ Skipped '{}': {}\x1b[0m", profile.profile_name, e);
+            continue;
+        }
+        println!("\x1b[1;32m✓ Imported '{}'\x1b[0m ({})", profile.profile_name, profile.email);
+        match replace {
+            Some(idx) => config.profiles[idx] = profile,
+            None => config.profiles.push(profile),
+        }
+        imported += 1;
+    }
+
+    if imported == 0 {
+        println!("Nothing imported.");
+        return Ok(());
+    }
+    save_config(&config)?;
+    crate::signing::sync_allowed_signers(&config)?;
+    crate::ssh::sync_profile_configs(&config)?;
+    println!("\n\x1b[1;32m✓ Imported {} profile(s).\x1b[0m", imported);
+    Ok(())
+}
@@ -1,11 +1,14 @@
 use anyhow::{Context, Result};
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::config::{load_config, load_local_config, save_local_profile_selection, Config};
+use crate::config::{load_config, load_local_config, save_local_config, save_local_profile_selection, Config};
+use crate::credential;
 use crate::profile::{select_profile, AuthMethod, Profile};
+use crate::signing;
+use crate::ssh;
 
 /// Check if currently inside a git repository
 pub fn is_inside_git_repo() -> bool {
@@ -16,6 +19,48 @@ pub fn is_inside_git_repo() -> bool {
         .unwrap_or(false)
 }
 
+/// Environment variables that redirect git to other config files
+const CONFIG_FILE_VARS: [&str; 2] = ["GIT_CONFIG_GLOBAL", "GIT_CONFIG_SYSTEM"];
+
+/// Make relative GIT_CONFIG_GLOBAL / GIT_CONFIG_SYSTEM paths absolute
+///
+/// git resolves them against its working directory, and gix runs git from other
+/// directories (`--repo`, scans, post-clone setup), which would silently switch files.
+/// Call before changing the working directory.
+pub fn pin_git_config_env() {
+    let Ok(cwd) = std::env::current_dir() else {
+        return;
+    };
+    for var in CONFIG_FILE_VARS {
+        if let Some(value) = std::env::var_os(var).filter(|v| !v.is_empty()) {
+            let path = PathBuf::from(&value);
+            if path.is_relative() {
+                std::env::set_var(var, cwd.join(path));
+            }
+        }
+    }
+}
+
+/// Global git config files in effect, as `git config --global` sees them
+pub fn global_git_config_files() -> Vec<PathBuf> {
+    if let Some(path) = std::env::var_os("GIT_CONFIG_GLOBAL").filter(|v| !v.is_empty()) {
+        return vec![PathBuf::from(path)];
+    }
+    let Some(dirs) = directories::BaseDirs::new() else {
+        return vec![];
+    };
+    let xdg = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| dirs.home_dir().join(".config"));
+    vec![dirs.home_dir().join(".gitconfig"), xdg.join("git").join("config")]
+}
+
+/// Whether GIT_CONFIG_NOSYSTEM tells git to skip the system config
+pub fn system_git_config_disabled() -> bool {
+    std::env::var("GIT_CONFIG_NOSYSTEM").is_ok_and(|v| !v.is_empty() && v != "0" && !v.eq_ignore_ascii_case("false"))
+}
+
 /// Get the root path of the current git repository
 pub fn get_git_root() -> Option<PathBuf> {
     Command::new("git")
@@ -23,89 +68,28 @@ pub fn get_git_root() -> Option<PathBuf> {
         .output()
         .ok()
         .filter(|o| o.status.success())
-        .map(|o| PathBuf::from(String::from_utf8_lossy(&o.stdout).trim()))
+        .map(|o| crate::paths::from_git_path(String::from_utf8_lossy(&o.stdout).trim()))
 }
 
 /// Detect which profile is configured for the current repository
 pub fn detect_profile(config: &Config) -> Option<&Profile> {
-    // 1. Check local .gix/config.json
-    if let Some(local_config) = load_local_config() {
-        if let Some(name) = local_config.selected_profile {
-            if let Some(p) = config.profiles.iter().find(|p| p.profile_name == name) {
-                return Some(p);
-            }
-        }
-    }
-
-    // 2. Check global default profile
-    if let Some(default_name) = &config.default_profile {
-        if let Some(p) = config.profiles.iter().find(|p| &p.profile_name == default_name) {
-            return Some(p);
-        }
-    }
-
-    // 3. Fallback to git config
-    if !is_inside_git_repo() {
-        return None;
-    }
-
-    // Try to read local git config
-    let output = Command::new("git")
-        .args(["config", "--local", "user.email"])
-        .output()
-        .ok()?;
-
-    if !output.status.success() {
-        return None;
-    }
-
-    let email = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if email.is_empty() {
-        return None;
-    }
-
-    config.profiles.iter().find(|p| p.email == email)
+    crate::detection::detect(config).map(|d| d.profile)
 }
 
 /// Apply profile configuration to the local repository
 pub fn apply_local_config(profile: &Profile) -> Result<()> {
-    // Save to .gix/config.json
-    save_local_profile_selection(&profile.profile_name)?;
-
-    // Configure git user settings
-    Command::new("git")
-        .args(["config", "--local", "user.name", &profile.name])
-        .output()
-        .context("Failed to set user.name")?;
-
-    Command::new("git")
-        .args(["config", "--local", "user.email", &profile.email])
-        .output()
-        .context("Failed to set user.email")?;
+    let dir = std::env::current_dir()?;
 
-    // Configure authentication
-    match &profile.auth {
-        AuthMethod::SSH { key_path } => {
-            let ssh_command = format!("ssh -i {} -o IdentitiesOnly=yes", key_path);
-            Command::new("git")
-                .args(["config", "--local", "core.sshCommand", &ssh_command])
-                .output()
-                .context("Failed to set core.sshCommand")?;
-        }
-        AuthMethod::Token { .. } => {
-            // Unset SSH command if previously set
-            Command::new("git")
-                .args(["config", "--local", "--unset", "core.sshCommand"])
-                .output()
-                .ok(); // Ignore if not present
-        }
-    }
+    crate::history::track(&dir, &profile.profile_name, || {
+        // Save to .gix/config.json
+        save_local_profile_selection(&profile.profile_name)?;
 
-    Ok(())
+        apply_local_config_to_dir(profile, &dir)
+    })
 }
 
 /// Handle the 'gix use' command
-pub fn handle_use_command(name: Option<String>) -> Result<()> {
+pub fn handle_use_command(name: Option<String>, remote: Option<String>, path: Option<PathBuf>) -> Result<()> {
     if !is_inside_git_repo() {
         println!("\x1b[1;31m✗ Not inside a git repository. Cannot apply local config.\x1b[0m");
         return Ok(());
@@ -120,9 +104,7 @@ pub fn handle_use_command(name: Option<String>) -> Result<()> {
 
     let profile = if let Some(n) = name {
         config
-            .profiles
-            .iter()
-            .find(|p| p.profile_name == n)
+            .find_profile(&n)
             .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", n))?
             .clone()
     } else {
@@ -131,18 +113,131 @@ pub fn handle_use_command(name: Option<String>) -> Result<()> {
             .clone()
     };
 
+    if let Some(remote) = remote {
+        let mut local_config = load_local_config().unwrap_or_default();
+        if local_config.locked {
+            anyhow::bail!("This repository's profiles are locked by team policy (.gix/config.json)");
+        }
+        local_config.remotes.insert(remote.clone(), profile.profile_name.clone());
+        save_local_config(&local_config)?;
+        println!(
+            "\x1b[1;32m✓ Commands for remote '{}' will use profile: {} ({})\x1b[0m",
+            remote, profile.profile_name, profile.email
+        );
+        return Ok(());
+    }
+
+    if let Some(path) = path {
+        let dir = repo_relative_dir(&path)?;
+        let root = get_git_root().context("Not inside a git repository")?;
+        let mut local_config = crate::config::load_local_config_from_dir(&root).unwrap_or_default();
+        if local_config.locked {
+            anyhow::bail!("This repository's profiles are locked by team policy (.gix/config.json)");
+        }
+        local_config.paths.insert(dir.clone(), profile.profile_name.clone());
+        crate::config::save_local_config_to_dir(&local_config, &root)?;
+        println!(
+            "\x1b[1;32m✓ Commits in '{}/' will use profile: {} ({})\x1b[0m",
+            dir, profile.profile_name, profile.email
+        );
+        if !config.intercepted_commands.iter().any(|c| c == "commit") {
+            println!("\x1b[1;33m⚠ 'commit' is not intercepted, so this has no effect yet. Enable it with 'gix commands'.\x1b[0m");
+        }
+        return Ok(());
+    }
+
     apply_local_config(&profile)?;
     
     println!(
         "\n\x1b[1;32m✓ Switched to profile: {} ({})\x1b[0m",
         profile.profile_name, profile.email
     );
+
+    crate::overrides::print_identity_report(&crate::overrides::check_identity(&profile, &std::env::current_dir()?));
+
+    if !profile.trailers.is_empty() && !config.intercepted_commands.iter().any(|c| c == "commit") {
+        println!("\x1b[1;33m⚠ This profile adds commit trailers, but 'commit' is not intercepted. Enable it with 'gix commands'.\x1b[0m");
+    }
     
     Ok(())
 }
 
+/// Handle 'gix use --global': write a profile's identity to the global git config
+///
+/// For users who mostly work as one identity: repositories without their own
+/// settings use it, and 'gix use' in a repository still takes precedence.
+pub fn handle_use_global_command(name: Option<String>) -> Result<()> {
+    let config = load_config()?;
+    if config.profiles.is_empty() {
+        println!("\x1b[1;33m⚠ No profiles configured. Run 'gix profile add' first.\x1b[0m");
+        return Ok(());
+    }
+
+    let profile = match name {
+        Some(n) => config.find_profile(&n).ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", n))?,
+        None => select_profile(&config).ok_or_else(|| anyhow::anyhow!("No profile selected"))?,
+    };
+
+    let dir = std::env::current_dir()?;
+    let set = |key: &str, value: &str| {
+        run_git_config(&dir, "--global", &[key, value]).with_context(|| format!("Failed to set {}", key))
+    };
+    set("user.name", &profile.name)?;
+    set("user.email", &profile.email)?;
+    match &profile.auth {
+        AuthMethod::SSH { .. } => set("core.sshCommand", &ssh::profile_ssh_command(profile, None))?,
+        AuthMethod::Token { .. } => {
+            // Only drop an SSH command some gix profile wrote, never one of the user's own
+            let current = git_lines(&dir, &["config", "--global", "--get", "core.sshCommand"]);
+            let from_gix = config.profiles.iter().any(|p| match &p.auth {
+                AuthMethod::SSH { .. } => current.first() == Some(&ssh::preview_ssh_command(p, None)),
+                AuthMethod::Token { .. } => false,
+            });
+            if from_gix {
+                run_git_config(&dir, "--global", &["--unset", "core.sshCommand"]).ok();
+            }
+        }
+    }
+
+    println!(
+        "\x1b[1;32m✓ Global git config now uses profile: {} ({})\x1b[0m",
+        profile.profile_name, profile.email
+    );
+    if is_inside_git_repo() {
+        if let Some(email) = get_local_git_config(&dir, "user.email").filter(|e| *e != profile.email) {
+            println!("\x1b[1;33m⚠ This repository keeps its own identity ({}), which takes precedence.\x1b[0m", email);
+        }
+    }
+    Ok(())
+}
+
+/// A subdirectory as stored in .gix/config.json: relative to the repository root, `/`-separated
+fn repo_relative_dir(path: &Path) -> Result<String> {
+    let root = get_git_root().context("Not inside a git repository")?;
+    let canonical = |p: &Path| {
+        std::fs::canonicalize(crate::paths::long_path(p))
+            .map(|c| crate::paths::plain_path(&c))
+            .with_context(|| format!("Cannot access {}", p.display()))
+    };
+    let dir = canonical(path)?;
+    if !dir.is_dir() {
+        anyhow::bail!("{} is not a directory", path.display());
+    }
+    let relative = dir
+        .strip_prefix(canonical(&root)?)
+        .map_err(|_| anyhow::anyhow!("{} is outside this repository", path.display()))?;
+    if relative.as_os_str().is_empty() {
+        anyhow::bail!("That is the repository root: run 'gix use' without --path");
+    }
+    let parts: Vec<String> = relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+    Ok(parts.join("/"))
+}
+
 /// Handle the 'gix status' command
-pub fn handle_status_command() -> Result<()> {
+pub fn handle_status_command(history: bool) -> Result<()> {
+    if crate::settings::json_output() {
+        return crate::settings::print_json(&status_json(history)?);
+    }
     if !is_inside_git_repo() {
         println!("\x1b[1;33m⚠ Not inside a git repository.\x1b[0m");
         return Ok(());
@@ -151,29 +246,54 @@ pub fn handle_status_command() -> Result<()> {
     let config = load_config()?;
     
     println!("\x1b[1;36m📊 Repository Status\x1b[0m\n");
+
+    if crate::settings::disabled_by_env() || config.interception_disabled {
+        println!("   \x1b[1;33m⏸  Interception is off: git runs without profile injection ('gix on' to resume)\x1b[0m");
+    }
     
     if let Some(root) = get_git_root() {
         println!("   📁 Repository: {}", root.display());
     }
+    let workspace = crate::workspace::active_workspace();
+    if workspace != crate::workspace::DEFAULT_WORKSPACE {
+        println!("   🗂  Workspace: {}", workspace);
+    }
 
-    if let Some(profile) = detect_profile(&config) {
+    if let Some(detection) = crate::detection::detect(&config) {
+        let profile = detection.profile;
         println!(
             "   👤 Profile: \x1b[1;32m{}\x1b[0m",
             profile.profile_name
         );
+        println!("   🧭 Detected via: {}", detection.explain(&config));
         println!("   📧 Email: {}", profile.email);
         println!("   🏷️  Name: {}", profile.name);
         
         match &profile.auth {
-            AuthMethod::SSH { key_path } => {
-                let exists = PathBuf::from(key_path).exists();
+            AuthMethod::SSH { key_path, host_keys } => {
+                let exists = ssh::resolve_key_path(key_path).exists();
                 let status = if exists { "\x1b[1;32m✓\x1b[0m" } else { "\x1b[1;31m✗\x1b[0m" };
                 println!("   🔐 Auth: SSH {} {}", key_path, status);
+                if !host_keys.is_empty() {
+                    let hosts: Vec<&str> = host_keys.keys().map(|h| h.as_str()).collect();
+                    println!("      Own keys for: {}", hosts.join(", "));
+                }
             }
-            AuthMethod::Token { .. } => {
-                println!("   🔑 Auth: HTTPS Token");
+            AuthMethod::Token { token, host_tokens } => {
+                match crate::profile::token_env_var(token) {
+                    Some(var) => println!("   🔑 Auth: HTTPS Token (from ${})", var),
+                    None => println!("   🔑 Auth: HTTPS Token"),
+                }
+                if !host_tokens.is_empty() {
+                    let hosts: Vec<&str> = host_tokens.keys().map(|h| h.as_str()).collect();
+                    println!("      Own tokens for: {}", hosts.join(", "));
+                }
             }
         }
+
+        crate::detection::report_conflicts(&config, &detection)?;
+        print_local_policy();
+        crate::history::print_drift(&std::env::current_dir()?);
     } else {
         println!("   \x1b[1;33m⚠ No known profile detected.\x1b[0m");
         
@@ -191,59 +311,353 @@ pub fn handle_status_command() -> Result<()> {
         
         println!("\n   Run '\x1b[1mgix use\x1b[0m' to configure a profile for this repository.");
     }
+
+    println!();
+    crate::profile::print_default_profile(&config);
+
+    if history {
+        println!();
+        crate::registry::print_history(&std::env::current_dir()?);
+    }
     
     println!();
     Ok(())
 }
 
+/// 'gix status --json': the same facts as the text output, for scripts and prompts
+fn status_json(history: bool) -> Result<serde_json::Value> {
+    use serde_json::{json, Value};
+
+    let config = load_config()?;
+    let mut status = json!({
+        "repository": Value::Null,
+        "workspace": crate::workspace::active_workspace(),
+        "interception": !(crate::settings::disabled_by_env() || config.interception_disabled),
+        "profile": Value::Null,
+        "default_profile": config.default_profile,
+        "default_profile_missing": config.missing_default_profile().is_some(),
+    });
+    if !is_inside_git_repo() {
+        return Ok(status);
+    }
+    status["repository"] = json!(get_git_root());
+
+    let Some(detection) = crate::detection::detect(&config) else {
+        status["git_email"] = json!(crate::detection::local_email());
+        return Ok(status);
+    };
+    let profile = detection.profile;
+    let auth = match &profile.auth {
+        AuthMethod::SSH { key_path, host_keys } => json!({
+            "method": "ssh",
+            "key_path": key_path,
+            "key_exists": ssh::resolve_key_path(key_path).exists(),
+            "key_hosts": host_keys.keys().collect::<Vec<_>>(),
+        }),
+        AuthMethod::Token { token, host_tokens } => json!({
+            "method": "token",
+            "token_env": crate::profile::token_env_var(token),
+            "token_hosts": host_tokens.keys().collect::<Vec<_>>(),
+        }),
+    };
+    status["profile"] = json!({
+        "name": profile.profile_name,
+        "user_name": profile.name,
+        "email": profile.email,
+        "auth": auth,
+    });
+    status["detected_via"] = json!({ "source": detection.source, "detail": detection.explain(&config) });
+    status["conflicts"] = crate::detection::conflicts(&config, &detection)
+        .iter()
+        .map(|d| json!({ "source": d.source, "profile": d.profile.profile_name }))
+        .collect();
+    if let Some(local_config) = load_local_config() {
+        status["policy"] = json!({
+            "locked": local_config.locked,
+            "signing": local_config.signing,
+            "remotes": local_config.remotes,
+            "paths": local_config.paths,
+        });
+    }
+    if history {
+        let cwd = std::env::current_dir()?;
+        let registry = crate::registry::load_registry();
+        status["history"] = json!(registry.find(&cwd).map(|r| r.switches.as_slice()).unwrap_or_default());
+    }
+    Ok(status)
+}
+
+/// Whether to configure the current repository with the profile just picked (`persist_prompt`)
+fn confirm_persistence(config: &Config) -> Result<bool> {
+    use crate::settings::PersistPrompt;
+
+    let root = get_git_root().unwrap_or_default();
+    match config.persist_prompt {
+        PersistPrompt::Always => return Ok(true),
+        PersistPrompt::Never => return Ok(false),
+        PersistPrompt::Once if crate::registry::persistence_declined(&root) => return Ok(false),
+        PersistPrompt::Ask | PersistPrompt::Once => {}
+    }
+
+    let confirm = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Configure this repository to always use this profile?")
+        .default(true)
+        .interact()?;
+    if !confirm && config.persist_prompt == PersistPrompt::Once {
+        crate::registry::record_declined(&root)?;
+        crate::settings::banner("   gix won't ask again for this repository ('gix use' configures it later).");
+    }
+    Ok(confirm)
+}
+
+/// Expand gitconfig aliases (`alias.p = push`) into the command they run
+///
+/// Like git, builtins and `git-*` programs win over an alias of the same name, and
+/// `!` aliases run a shell command: those keep the arguments as typed.
+pub fn expand_alias(args: &[String]) -> Vec<String> {
+    let mut args = args.to_vec();
+    let mut seen: Vec<String> = Vec::new();
+    while let Some(name) = args.first().cloned() {
+        if name.starts_with('-') || seen.contains(&name) {
+            break;
+        }
+        let Some(value) = git_lines(Path::new("."), &["config", "--get", &format!("alias.{}", name)]).pop() else {
+            break;
+        };
+        if value.starts_with('!') || git_lines(Path::new("."), &["--list-cmds=main,others"]).contains(&name) {
+            break;
+        }
+        let words = split_alias(&value);
+        if words.is_empty() {
+            break;
+        }
+        seen.push(name);
+        args.splice(0..1, words);
+    }
+    args
+}
+
+/// Split an alias value into words the way git does: quotes group, backslashes escape
+fn split_alias(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                }
+                in_word = true;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// The git executable, skipping gix itself when it is installed as `git` on PATH
+///
+/// Running plain "git" from such a shim would start gix again for every command.
+pub fn real_git() -> &'static Path {
+    static REAL_GIT: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+    REAL_GIT.get_or_init(|| {
+        let own = std::env::current_exe().and_then(|p| p.canonicalize()).ok();
+        let name = format!("git{}", std::env::consts::EXE_SUFFIX);
+        std::env::var_os("PATH")
+            .iter()
+            .flat_map(std::env::split_paths)
+            .map(|dir| dir.join(&name))
+            .find(|candidate| candidate.is_file() && candidate.canonicalize().ok() != own)
+            .unwrap_or_else(|| PathBuf::from("git"))
+    })
+}
+
+/// Run git exactly as invoked, without interception
+fn run_git_passthrough(args: &[String]) -> Result<()> {
+    let status = Command::new(real_git())
+        .args(args)
+        .status()
+        .context("Failed to run git command")?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Repository-level settings of .gix/config.json shown by 'gix status'
+fn print_local_policy() {
+    let Some(local_config) = load_local_config() else {
+        return;
+    };
+    if local_config.locked {
+        println!("   🔒 Profile locked by team policy");
+    }
+    if let Some(pin) = local_config.signing {
+        println!("   ✍️  Signing pinned: {}", format!("{:?}", pin).to_lowercase());
+    }
+    for (remote, profile) in &local_config.remotes {
+        println!("   🌐 Remote '{}' uses profile {}", remote, profile);
+    }
+    for (dir, profile) in &local_config.paths {
+        println!("   📂 Commits in '{}/' use profile {}", dir, profile);
+    }
+}
+
+/// Pass config to git through GIT_CONFIG_KEY_n/GIT_CONFIG_VALUE_n: like `-c`, but out of
+/// the process list. Entries already inherited from the environment are kept.
+fn add_config_env(git_cmd: &mut Command, values: &[(String, String)]) {
+    if values.is_empty() {
+        return;
+    }
+    let inherited: usize = std::env::var("GIT_CONFIG_COUNT").ok().and_then(|c| c.parse().ok()).unwrap_or(0);
+    for (i, (key, value)) in values.iter().enumerate() {
+        git_cmd.env(format!("GIT_CONFIG_KEY_{}", inherited + i), key);
+        git_cmd.env(format!("GIT_CONFIG_VALUE_{}", inherited + i), value);
+    }
+    git_cmd.env("GIT_CONFIG_COUNT", (inherited + values.len()).to_string());
+}
+
+/// Profile an intercepted command uses without asking, with the reason
+///
+/// GIX_PROFILE, then a profile pinned for the remote or path the command touches, then
+/// the repository's detection order. Clones may still fall back to a host rule.
+pub fn intercepted_profile<'a>(config: &'a Config, args: &[String]) -> Result<Option<(&'a Profile, String)>> {
+    if let Some(p) = crate::detection::env_profile(config)? {
+        return Ok(Some((p, "GIX_PROFILE environment variable".to_string())));
+    }
+    if let Some(p) = crate::detection::remote_override(config, args) {
+        return Ok(Some((p, "profile pinned for the remote in .gix/config.json".to_string())));
+    }
+    if let Some(p) = crate::detection::path_override(config, args)? {
+        return Ok(Some((p, "profile pinned for the staged paths in .gix/config.json".to_string())));
+    }
+    Ok(crate::detection::detect(config).map(|d| (d.profile, d.explain(config))))
+}
+
+/// `-c` settings an intercepted command runs with: identity, repository defaults and
+/// extra config, send-email, HTTP user agent and signing
+pub fn command_config_overrides(profile: &Profile, pin: Option<signing::SigningPin>) -> Vec<(String, String)> {
+    let mut values = vec![
+        ("user.name".to_string(), profile.name.clone()),
+        ("user.email".to_string(), profile.email.clone()),
+    ];
+    // init.defaultBranch covers init/clone
+    values.extend(profile.git_config_values());
+    if let Some(sendemail) = &profile.sendemail {
+        values.extend(sendemail.git_config(profile));
+    }
+    if let Some(agent) = &profile.http.user_agent {
+        values.push(("http.userAgent".to_string(), agent.clone()));
+    }
+    values.extend(signing::pinned_signing_git_config(profile, pin));
+    values
+}
+
 /// Handle git command passthrough with profile injection
 pub fn handle_git_command(args: Vec<String>) -> Result<()> {
-    let config = load_config()?;
+    // GIX_DISABLE=1 bypasses gix entirely, without even reading the config
+    if crate::settings::disabled_by_env() {
+        return run_git_passthrough(&args);
+    }
+
+    // Re-entered from a hook or script of an intercepted command: the outer gix already
+    // chose the profile, and its -c settings reach this git through GIT_CONFIG_PARAMETERS
+    if crate::settings::nested() {
+        return run_git_passthrough(&args);
+    }
+
+    let mut config = load_config()?;
 
     // Check if we should intercept this command
-    if let Some(cmd) = args.first() {
-        if !config.intercepted_commands.contains(cmd) {
-            // Pass-through without interception
-            let status = Command::new("git")
-                .args(&args)
-                .status()
-                .context("Failed to run git command")?;
-            
-            if !status.success() {
-                std::process::exit(status.code().unwrap_or(1));
+    if config.interception_disabled {
+        return run_git_passthrough(&args);
+    }
+    let args = match args.first() {
+        Some(cmd) if !config.intercepted_commands.contains(cmd) => {
+            // `git p` with `alias.p = push` is intercepted as the push it runs
+            let expanded = expand_alias(&args);
+            if expanded.first().is_some_and(|c| !config.intercepted_commands.contains(c)) {
+                return run_git_passthrough(&args);
             }
-            return Ok(());
+            expanded
         }
+        _ => args,
+    };
+
+    // `git <cmd> -q` asks for silence: gix stays quiet too
+    if args.iter().skip(1).any(|a| a == "-q" || a == "--quiet") {
+        crate::settings::set_quiet();
     }
 
+    // A secret typed into a clone URL must not end up in .git/config or the usage log
+    let mut url_credentials = credential::find_clone_credentials(&args);
+    let created_profile = match &mut url_credentials {
+        Some(creds) => credential::offer_token_profile(&mut config, creds)?,
+        None => None,
+    };
+
     // Interception logic
-    let current_profile = detect_profile(&config);
+    let detected = intercepted_profile(&config, &args)?.map(|(p, _)| p);
+    let current_profile = created_profile.as_ref().or(detected);
     let is_clone = args.first().map(|s| s == "clone").unwrap_or(false);
 
     let profile = if let Some(p) = current_profile {
         // If we are cloning, we might want to confirm if we really want to use the default profile
         // but for now let's respect the default if it exists.
-        println!(
+        crate::settings::banner(format!(
             "\x1b[1;36m🔀 Using profile:\x1b[0m \x1b[1;32m{}\x1b[0m ({})",
             p.profile_name, p.email
-        );
+        ));
         
         // Warn if SSH key is missing
-        if let AuthMethod::SSH { key_path } = &p.auth {
-            if !PathBuf::from(key_path).exists() {
-                println!(
+        let host = command_remote_host(&args, &std::env::current_dir().unwrap_or_default());
+        if let Some(key_path) = p.auth.key_for(host.as_deref()) {
+            if !ssh::resolve_key_path(key_path).exists() {
+                crate::settings::banner_warning(format!(
                     "\x1b[1;33m⚠ Warning: SSH key not found at: {}\x1b[0m",
                     key_path
-                );
+                ));
             }
         }
         p.clone()
+    } else if let Some(p) = crate::detection::clone_rule_profile(&config, &args) {
+        crate::settings::banner(format!(
+            "\x1b[1;36m🔀 Using profile:\x1b[0m \x1b[1;32m{}\x1b[0m ({}) via host rule",
+            p.profile_name, p.email
+        ));
+        p.clone()
     } else {
+        if crate::settings::no_input() {
+            let code = crate::detection::report_unresolved(&config, &args);
+            std::process::exit(code);
+        }
         if is_clone {
-             println!("\x1b[1;36m⬇️ Cloning repository...\x1b[0m");
-             println!("\x1b[1;33m⚠ No default profile configured.\x1b[0m");
+             crate::settings::banner("\x1b[1;36m⬇️ Cloning repository...\x1b[0m");
+             crate::settings::banner_warning("\x1b[1;33m⚠ No default profile configured.\x1b[0m");
         } else {
-             println!("\x1b[1;33m⚠ No profile detected for this repository.\x1b[0m");
+             crate::settings::banner_warning("\x1b[1;33m⚠ No profile detected for this repository.\x1b[0m");
         }
         
         let p = select_profile(&config)
@@ -251,49 +665,130 @@ pub fn handle_git_command(args: Vec<String>) -> Result<()> {
 
         // Ask to save persistence ONLY if we are inside a repo AND NOT cloning
         // If we are cloning, we handle persistence AFTER the clone
-        if is_inside_git_repo() && !is_clone {
-            let confirm = Confirm::with_theme(&ColorfulTheme::default())
-                .with_prompt("Configure this repository to always use this profile?")
-                .default(true)
-                .interact()?;
-
-            if confirm {
-                apply_local_config(p)?;
-                println!(
-                    "\x1b[1;32m✓ Repository configured!\x1b[0m Future commands will use this profile."
-                );
-            }
+        if is_inside_git_repo() && !is_clone && confirm_persistence(&config)? {
+            apply_local_config(p)?;
+            crate::settings::banner("\x1b[1;32m✓ Repository configured!\x1b[0m Future commands will use this profile.");
         }
         p.clone()
     };
 
-    // Log usage
-    log_usage(&profile, &args)?;
+    run_intercepted(&config, &profile, &args, url_credentials.as_ref())
+}
+
+/// Run an intercepted git command as `profile`
+///
+/// Safety checks, credentials and identity for the command, then the configuration of
+/// what it created (a clone, submodule checkouts, a worktree).
+pub fn run_intercepted(
+    config: &Config,
+    profile: &Profile,
+    args: &[String],
+    url_credentials: Option<&credential::UrlCredentials>,
+) -> Result<()> {
+    let is_clone = args.first().map(|s| s == "clone").unwrap_or(false);
+    let is_submodule = args.first().map(|s| s == "submodule").unwrap_or(false);
+
+    // Safety checks before anything is sent
+    let cwd = std::env::current_dir().unwrap_or_default();
+    crate::policy::enforce(profile, command_remote_host(args, &cwd).as_deref(), args)?;
+    crate::leak::check_push(profile, args)?;
+    crate::push::confirm_protected_push(profile, args)?;
 
     // Construct Git Command
-    let mut git_cmd = Command::new("git");
+    let mut git_cmd = Command::new(real_git());
+
+    // Non-git credentials the profile carries (e.g. NPM_TOKEN for hooks)
+    git_cmd.envs(profile.exported_env()?);
 
     // Set authentication
     match &profile.auth {
-        AuthMethod::SSH { key_path } => {
-            let ssh_cmd = format!("ssh -i {} -o IdentitiesOnly=yes", key_path);
+        AuthMethod::SSH { .. } => {
+            let host = command_remote_host(args, &cwd);
+            if profile.ssh.pin_host_keys {
+                if let Some(host) = &host {
+                    match crate::known_hosts::ensure_host_keys(profile, host) {
+                        Ok(0) => {}
+                        Ok(_) => crate::settings::banner(format!("\x1b[1;36m🔏 Pinned host keys added for {}\x1b[0m", host)),
+                        Err(e) => eprintln!("\x1b[1;33m⚠ Could not update known hosts: {}\x1b[0m", e),
+                    }
+                }
+            }
+            let ssh_cmd = ssh::profile_ssh_command(profile, host.as_deref());
+            // git-lfs honours GIT_SSH_COMMAND too, so LFS over SSH uses the same key
             git_cmd.env("GIT_SSH_COMMAND", ssh_cmd);
         }
-        AuthMethod::Token { token } => {
-            // Use git credential approve to inject token
-            inject_token_credential(&profile.name, token)?;
+        AuthMethod::Token { .. } if config.credential_mode == credential::CredentialMode::Helper => {
+            // git asks gix for the token, ahead of (and instead of) any other helper
+            git_cmd.env(crate::credential_helper::PROFILE_ENV, &profile.profile_name);
+            git_cmd.env(crate::credential_helper::HOSTS_ENV, token_hosts(args, &cwd).join(","));
+            git_cmd.arg("-c").arg("credential.helper=");
+            git_cmd.arg("-c").arg(format!("credential.helper={}", crate::credential_helper::helper_value()?));
+        }
+        AuthMethod::Token { .. } => {
+            for host in &token_hosts(args, &cwd) {
+                if credential::use_gcm_interop(config.credential_mode) {
+                    // Let Git Credential Manager select the account instead of fighting it
+                    git_cmd
+                        .arg("-c")
+                        .arg(format!("{}={}", credential::gcm_username_key(host), profile.name));
+                } else {
+                    // Use git credential approve to inject token
+                    let token = profile.auth.token_for(host).unwrap_or_default();
+                    inject_token_credential(host, &profile.name, &crate::profile::resolve_token(token)?)?;
+                }
+            }
         }
     }
 
-    // Set user config for this command
-    git_cmd.arg("-c").arg(format!("user.name={}", profile.name));
-    git_cmd.arg("-c").arg(format!("user.email={}", profile.email));
+    // HTTP headers may carry tokens, so they go through the environment
+    // (like -c, but not visible in the process list), and only to the profile's hosts
+    let urls = header_urls(profile, args, &cwd);
+    let mut hidden_config: Vec<(String, String)> = Vec::new();
+    for header in profile.http.resolved_headers()? {
+        for url in &urls {
+            hidden_config.push((format!("http.{}.extraHeader", url), header.clone()));
+        }
+    }
+
+    // Set signing config for this command
+    let pin = load_local_config().and_then(|l| l.signing);
+    if pin == Some(signing::SigningPin::Always) && profile.signing.is_none() {
+        eprintln!(
+            "\x1b[1;33m⚠ This repository requires signed commits, but profile '{}' has no signing key.\x1b[0m",
+            profile.profile_name
+        );
+    }
+    signing::check_smartcard(profile, &signing::pinned_signing_git_config(profile, pin), args)?;
+
+    // Identity, repository defaults, send-email, HTTP and signing settings for this command;
+    // a value that looks like a secret joins the headers in the environment
+    for (key, value) in command_config_overrides(profile, pin) {
+        if crate::secrets::contains_secret(&value) {
+            hidden_config.push((key, value));
+        } else {
+            git_cmd.arg("-c").arg(format!("{}={}", key, value));
+        }
+    }
+    add_config_env(&mut git_cmd, &hidden_config);
 
-    // Append original args
-    git_cmd.args(&args);
+    // Append original args (plus any commit trailers from the profile)
+    git_cmd.args(with_clone_args(&with_commit_trailers(args, profile), profile));
 
     // Execute
+    let started = std::time::Instant::now();
     let status = git_cmd.status().context("Failed to run git command")?;
+    let duration = started.elapsed();
+
+    // Log usage (unless disabled); a log problem must not change the outcome of the git command
+    if config.logging {
+        let logged_args: Vec<String> = match &url_credentials {
+            Some(creds) => args.iter().map(|a| if *a == creds.url { creds.masked_url() } else { a.clone() }).collect(),
+            None => args.to_vec(),
+        };
+        if let Err(e) = log_usage(profile, &logged_args, status.code(), duration) {
+            eprintln!("\x1b[1;33m⚠ Could not write usage log: {}\x1b[0m", e);
+        }
+    }
 
     if !status.success() {
         std::process::exit(status.code().unwrap_or(1));
@@ -302,152 +797,722 @@ pub fn handle_git_command(args: Vec<String>) -> Result<()> {
     // Post-clone configuration
     if is_clone && status.success() {
         // Try to detect the directory created by git clone
-        if let Some(dir) = detect_cloned_dir(&args) {
-            println!("\x1b[1;36m⚙️  Configuring new repository...\x1b[0m");
-            match crate::config::save_local_profile_selection_to_dir(&profile.profile_name, dir.clone()) {
-                Ok(_) => {
-                     // Also apply git local config
-                     if let Err(e) = apply_local_config_to_dir(&profile, &dir) {
-                         println!("\x1b[1;33m⚠ Failed to apply local git config: {}\x1b[0m", e);
-                     } else {
-                         println!("\x1b[1;32m✓ Repository '{}' configured with profile '{}'\x1b[0m", dir.display(), profile.profile_name);
-                     }
-                },
-                Err(e) => println!("\x1b[1;33m⚠ Failed to save profile config: {}\x1b[0m", e),
+        if let Some(dir) = detect_cloned_dir(args) {
+            crate::settings::banner("\x1b[1;36m⚙️  Configuring new repository...\x1b[0m");
+            if let Some(creds) = url_credentials {
+                // Without a profile or helper holding it, the URL is the only copy of the secret
+                if !credential::can_scrub(creds.saved, !credential::configured_helpers_in(&dir).is_empty()) {
+                    crate::settings::banner_warning(format!(
+                        "\x1b[1;33m⚠ The remote URL keeps its secret: no gix profile or credential helper holds it. Configure a helper or create a token profile, then run 'git remote set-url origin {}'\x1b[0m",
+                        creds.clean_url
+                    ));
+                } else {
+                    match credential::scrub_remote_urls(&dir, creds) {
+                        Ok(()) => crate::settings::banner(format!("\x1b[1;32m✓ Remote URL set to {}\x1b[0m", creds.clean_url)),
+                        Err(e) => crate::settings::banner_warning(format!("\x1b[1;33m⚠ Could not remove the secret from the remote URL: {}\x1b[0m", e)),
+                    }
+                }
+            }
+            // Tracked like 'gix use', so 'gix unuse' can restore the clone's own values
+            let result = crate::history::track(&dir, &profile.profile_name, || {
+                crate::config::save_local_profile_selection_to_dir(&profile.profile_name, dir.clone())?;
+                apply_local_config_to_dir(profile, &dir)
+            });
+            match result {
+                Ok(()) => crate::settings::banner(format!("\x1b[1;32m✓ Repository '{}' configured with profile '{}'\x1b[0m", dir.display(), profile.profile_name)),
+                Err(e) => crate::settings::banner_warning(format!("\x1b[1;33m⚠ Failed to apply local git config: {}\x1b[0m", e)),
+            }
+        }
+    }
+
+    // Post-submodule configuration: checkouts made by `add`/`update` get the profile too
+    if is_submodule && matches!(args.get(1).map(|a| a.as_str()), Some("add" | "update")) {
+        if let Some(root) = get_git_root() {
+            match crate::submodule::configure_submodule_checkouts(profile, &root) {
+                Ok(0) => {}
+                Ok(n) => crate::settings::banner(format!("\x1b[1;32m✓ {} submodule(s) configured with profile '{}'\x1b[0m", n, profile.profile_name)),
+                Err(e) => crate::settings::banner_warning(format!("\x1b[1;33m⚠ Failed to configure submodules: {}\x1b[0m", e)),
             }
         }
     }
 
+    // Post-worktree configuration
+    if let Some(dir) = worktree_add_path(args) {
+        crate::settings::banner("\x1b[1;36m⚙️  Configuring new worktree...\x1b[0m");
+        match setup_worktree(profile, &dir) {
+            Ok(()) => crate::settings::banner(format!("\x1b[1;32m✓ Worktree '{}' configured with profile '{}'\x1b[0m", dir.display(), profile.profile_name)),
+            Err(e) => crate::settings::banner_warning(format!("\x1b[1;33m⚠ Failed to configure the worktree: {}\x1b[0m", e)),
+        }
+    }
+
+    crate::suggest::hint_after_command(config);
+
     Ok(())
 }
 
-/// Detect directory created by git clone
-fn detect_cloned_dir(args: &[String]) -> Option<PathBuf> {
-    // Determine the directory name
-    // git clone [options] <repository> [<directory>]
-    
-    // 1. Check if the last arg is a directory (not strictly reliable if flags follow, but standard practice)
-    if let Some(last) = args.last() {
-        if !last.starts_with('-') && !last.starts_with("http") && !last.starts_with("git@") && !last.ends_with(".git") {
-            // Likely a directory argument
-            let path = PathBuf::from(last);
-            if path.exists() && path.is_dir() {
-                return Some(path);
+/// Directory created by `git worktree add [options] <path> [<commit-ish>]`
+pub fn worktree_add_path(args: &[String]) -> Option<PathBuf> {
+    if args.first().map(|a| a != "worktree").unwrap_or(true) || args.get(1).map(|a| a != "add").unwrap_or(true) {
+        return None;
+    }
+    let mut rest = args.iter().skip(2);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            // Options that take the next argument as their value
+            "-b" | "-B" | "--reason" => {
+                rest.next();
             }
+            "--" => return rest.next().map(PathBuf::from),
+            a if a.starts_with('-') => {}
+            path => return Some(PathBuf::from(path)),
         }
     }
-    
-    // 2. Try to derive from repository URL
-    // Find the arg that looks like a repo URL
-    for arg in args.iter().rev() {
-        if arg.ends_with(".git") || arg.starts_with("git@") || arg.starts_with("http") {
-             // Extract name from URL
-             // e.g. https://github.com/user/repo.git -> repo
-             let name = arg.split('/').last()?
-                .trim_end_matches(".git");
-             
-             let path = PathBuf::from(name);
-             if path.exists() && path.is_dir() {
-                 return Some(path);
-             }
+    None
+}
+
+/// Give a new worktree the settings of the one it was created from
+///
+/// Worktrees share `.git/config`, but not `.gix/config.json` (it lives in the working
+/// directory) nor `config.worktree` values when `extensions.worktreeConfig` is on.
+fn setup_worktree(profile: &Profile, dir: &Path) -> Result<()> {
+    let parent = get_git_root().context("Cannot find the current worktree")?;
+    crate::history::track(dir, &profile.profile_name, || {
+        let mut local_config = crate::config::load_local_config_from_dir(&parent).unwrap_or_default();
+        local_config.selected_profile = Some(profile.profile_name.clone());
+        crate::config::save_local_config_to_dir(&local_config, dir)?;
+        copy_worktree_config(&parent, dir)?;
+        apply_local_config_to_dir(profile, dir)
+    })
+}
+
+/// Copy per-worktree git config values (`git config --worktree`) to another worktree
+fn copy_worktree_config(from: &Path, to: &Path) -> Result<()> {
+    let enabled = git_config_in(from, "extensions.worktreeConfig")
+        .is_some_and(|v| matches!(v.to_ascii_lowercase().as_str(), "true" | "yes" | "on" | "1"));
+    if !enabled {
+        return Ok(());
+    }
+    // -z format: "<key>\n<value>\0" per entry
+    let list = |dir: &Path| -> Result<Vec<(String, String)>> {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(["config", "--worktree", "-z", "--list"])
+            .output()?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|e| !e.is_empty())
+            .map(|e| {
+                let (key, value) = e.split_once('\n').unwrap_or((e, ""));
+                (key.to_string(), value.to_string())
+            })
+            .collect())
+    };
+
+    // Newer git versions copy config.worktree themselves
+    let existing: Vec<String> = list(to)?.into_iter().map(|(key, _)| key).collect();
+    for (key, value) in list(from)? {
+        // Describe where a worktree is, not what it uses
+        if matches!(key.as_str(), "core.worktree" | "core.bare") || existing.contains(&key) {
+            continue;
+        }
+        let status = Command::new("git")
+            .current_dir(to)
+            .args(["config", "--worktree", "--add", &key, &value])
+            .status()?;
+        if !status.success() {
+            anyhow::bail!("Failed to copy {} to the new worktree", key);
         }
     }
-    
-    None
+    Ok(())
+}
+
+/// Insert the profile and pairing `--trailer` options right after `commit`
+pub fn with_commit_trailers(args: &[String], profile: &Profile) -> Vec<String> {
+    let mut result = args.to_vec();
+    if args.first().map(|s| s == "commit").unwrap_or(false) {
+        let trailers = profile
+            .trailer_lines()
+            .into_iter()
+            .chain(crate::coauthor::session_trailers())
+            .flat_map(|t| ["--trailer".to_string(), t]);
+        result.splice(1..1, trailers);
+    }
+    result
+}
+
+/// Insert the profile's default clone flags right after `clone`
+///
+/// Flags the user passed explicitly (or negated with `--no-`) win over the profile.
+pub fn with_clone_args(args: &[String], profile: &Profile) -> Vec<String> {
+    let mut result = args.to_vec();
+    if args.first().map(|s| s == "clone").unwrap_or(false) {
+        let option_name = |arg: &str| arg.split('=').next().unwrap_or(arg).trim_start_matches("--").to_string();
+        let given: Vec<String> = args[1..]
+            .iter()
+            .filter(|a| a.starts_with("--"))
+            .map(|a| option_name(a).trim_start_matches("no-").to_string())
+            .collect();
+
+        let extra: Vec<String> = profile
+            .clone_args
+            .iter()
+            .filter(|a| !given.contains(&option_name(a).trim_start_matches("no-").to_string()))
+            .cloned()
+            .collect();
+        result.splice(1..1, extra);
+    }
+    result
+}
+
+/// Directory created by a successful `git clone`, if it exists
+pub fn detect_cloned_dir(args: &[String]) -> Option<PathBuf> {
+    if args.first().map(|s| s != "clone").unwrap_or(true) {
+        return None;
+    }
+    let dir = crate::clone::CloneArgs::parse(&args[1..])?.target_dir()?;
+    crate::paths::long_path(&dir).is_dir().then_some(dir)
 }
 
 /// Apply profile configuration to a specific directory
-fn apply_local_config_to_dir(profile: &Profile, dir: &PathBuf) -> Result<()> {
+pub fn apply_local_config_to_dir(profile: &Profile, dir: &Path) -> Result<()> {
+    let config = load_config().unwrap_or_default();
+
     // Configure git user settings
-    Command::new("git")
+    set_local_git_config(dir, "user.name", &profile.name)?;
+    set_local_git_config(dir, "user.email", &profile.email)?;
+
+    // Point SSH remotes at the profile's host alias, or back at the real host
+    crate::ssh_config::rewrite_remotes(&config, profile, dir)?;
+
+    // Configure authentication
+    match &profile.auth {
+        AuthMethod::SSH { .. } if crate::ssh_config::can_alias(&config, profile) => {
+            // The ~/.ssh/config alias picks the key
+            crate::ssh_config::sync(&config, Some(dir))?;
+            unset_local_git_config(dir, "core.sshCommand");
+        }
+        AuthMethod::SSH { .. } => {
+            // Per-host keys go through the profile's generated ssh_config, so every remote
+            // and submodule gets its own key
+            let host = origin_host(dir).map(|host| crate::ssh_config::real_host(&config, &host));
+            let ssh_command = ssh::profile_ssh_command(profile, host.as_deref());
+            set_local_git_config(dir, "core.sshCommand", &ssh_command)?;
+        }
+        AuthMethod::Token { .. } => {
+            // Unset SSH command if previously set
+            unset_local_git_config(dir, "core.sshCommand");
+
+            // Let Git Credential Manager pick the profile's account for this host
+            if credential::use_gcm_interop(config.credential_mode) {
+                let mut hosts: Vec<String> = remote_names(dir)
+                    .iter()
+                    .flat_map(|r| [remote_urls(dir, r, false), remote_urls(dir, r, true)].concat())
+                    .filter_map(|u| https_host(&u))
+                    .chain(crate::lfs::lfs_https_host(dir))
+                    .collect();
+                hosts.sort();
+                hosts.dedup();
+                for host in hosts {
+                    set_local_git_config(dir, &credential::gcm_username_key(&host), &profile.name)?;
+                }
+            }
+        }
+    }
+
+    // Configure repository defaults and extra config
+    for (key, value) in profile.git_config_values() {
+        if key == "init.defaultBranch" {
+            // Only meaningful for a repository without commits yet
+            rename_unborn_branch(dir, &value);
+        } else {
+            set_local_git_config(dir, &key, &value)?;
+        }
+    }
+
+    // Drop extra config another gix profile put there, unless the user changed it since
+    for other in config.profiles.iter().filter(|p| !p.is_named(&profile.profile_name)) {
+        for (key, value) in other.git_config.iter().filter(|(k, _)| !profile.git_config.contains_key(*k)) {
+            if get_local_git_config(dir, key).as_deref() == Some(value.as_str()) {
+                unset_local_git_config(dir, key);
+            }
+        }
+    }
+
+    // Make submodules authenticate like the superproject
+    if profile.repo_defaults.rewrite_submodules {
+        crate::submodule::rewrite_submodule_urls(profile, dir)?;
+    }
+
+    // Configure the outgoing mail identity
+    match &profile.sendemail {
+        Some(sendemail) => {
+            let values = sendemail.git_config(profile);
+            // The SMTP settings belong together: leave none of the previous server's behind
+            for key in crate::sendemail::SENDEMAIL_KEYS {
+                if !values.iter().any(|(k, _)| k == key) {
+                    unset_local_git_config(dir, key);
+                }
+            }
+            for (key, value) in values {
+                set_local_git_config(dir, &key, &value)?;
+            }
+        }
+        None => {
+            // Only drop SMTP settings that another gix profile put there
+            let current = get_local_git_config(dir, "sendemail.smtpServer");
+            if current.is_some_and(|server| crate::sendemail::is_managed_smtp_server(&config, &server)) {
+                for key in crate::sendemail::SENDEMAIL_KEYS {
+                    unset_local_git_config(dir, key);
+                }
+            }
+        }
+    }
+
+    // Configure commit signing
+    let pin = crate::config::load_local_config_from_dir(dir).and_then(|l| l.signing);
+    let signing_config = signing::pinned_signing_git_config(profile, pin);
+    if signing_config.is_empty() {
+        // Only drop signing settings that another gix profile put there
+        let current = get_local_git_config(dir, "user.signingkey");
+        if current.is_some_and(|key| signing::is_managed_signing_key(&config, &key)) {
+            for key in signing::SIGNING_KEYS {
+                unset_local_git_config(dir, key);
+            }
+        }
+    } else {
+        for (key, value) in &signing_config {
+            set_local_git_config(dir, key, value)?;
+        }
+        signing::sync_allowed_signers(&config)?;
+    }
+
+    Ok(())
+}
+
+/// Point an unborn HEAD at the given branch name
+fn rename_unborn_branch(dir: &Path, branch: &str) {
+    let has_commits = Command::new("git")
+        .current_dir(dir)
+        .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(true);
+
+    if !has_commits {
+        Command::new("git")
+            .current_dir(dir)
+            .args(["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)])
+            .output()
+            .ok();
+    }
+}
+
+/// Read the effective value of a git config key as seen from a directory
+pub fn git_config_in(dir: &Path, key: &str) -> Option<String> {
+    let out = Command::new("git")
+        .current_dir(dir)
+        .args(["config", "--get", key])
+        .output()
+        .ok()?;
+
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Read a value from a repository's local git config
+pub fn get_local_git_config(dir: &Path, key: &str) -> Option<String> {
+    let out = Command::new("git")
         .current_dir(dir)
-        .args(["config", "--local", "user.name", &profile.name])
+        .args(["config", "--local", "--get", key])
         .output()
-        .context("Failed to set user.name")?;
+        .ok()?;
+
+    if !out.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+/// Write a value to a repository's local git config
+pub fn set_local_git_config(dir: &Path, key: &str, value: &str) -> Result<()> {
+    run_local_git_config(dir, &[key, value]).with_context(|| format!("Failed to set {}", key))
+}
+
+/// Remove a value from a repository's local git config, ignoring missing keys
+pub fn unset_local_git_config(dir: &Path, key: &str) {
+    run_local_git_config(dir, &["--unset", key]).ok();
+}
+
+/// Run `git config --local <args>`, retrying while another process holds `.git/config.lock`
+pub fn run_local_git_config(dir: &Path, args: &[&str]) -> Result<()> {
+    run_git_config(dir, "--local", args)
+}
 
+/// Run `git config <scope> <args>`, retrying while another process holds the lock file
+pub fn run_git_config(dir: &Path, scope: &str, args: &[&str]) -> Result<()> {
+    let mut attempts = 0;
+    loop {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(["config", scope])
+            .args(args)
+            .output()
+            .context("Failed to run git config")?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        attempts += 1;
+        if !stderr.contains("could not lock config file") || attempts >= 20 {
+            anyhow::bail!("git config {} failed: {}", args.join(" "), stderr.trim());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+}
+
+/// Host of an HTTPS URL, without any embedded userinfo
+fn https_host(url: &str) -> Option<String> {
+    let host = url.strip_prefix("https://")?.split('/').next()?;
+    let host = host.rsplit('@').next().unwrap_or(host);
+    Some(host.to_string()).filter(|h| !h.is_empty())
+}
+
+/// Output lines of a git command run in `dir`; empty if it fails
+pub fn git_lines(dir: &Path, args: &[&str]) -> Vec<String> {
     Command::new("git")
         .current_dir(dir)
-        .args(["config", "--local", "user.email", &profile.email])
+        .args(args)
         .output()
-        .context("Failed to set user.email")?;
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+        .unwrap_or_default()
+}
 
-    // Configure authentication
-    match &profile.auth {
-        AuthMethod::SSH { key_path } => {
-            let ssh_command = format!("ssh -i {} -o IdentitiesOnly=yes", key_path);
-            Command::new("git")
-                .current_dir(dir)
-                .args(["config", "--local", "core.sshCommand", &ssh_command])
-                .output()
-                .context("Failed to set core.sshCommand")?;
+/// `remote.<name>.url`/`pushurl` entries of a repository, as (key, url)
+pub fn remote_url_entries(dir: &Path) -> Vec<(String, String)> {
+    git_lines(dir, &["config", "--local", "--get-regexp", r"^remote\..*\.(url|pushurl)$"])
+        .into_iter()
+        .filter_map(|line| line.split_once(' ').map(|(k, v)| (k.to_string(), v.to_string())))
+        .collect()
+}
+
+/// Names of the remotes configured in a repository
+fn remote_names(dir: &Path) -> Vec<String> {
+    git_lines(dir, &["remote"])
+}
+
+/// URLs git contacts for a remote, after `insteadOf` rewriting (pushes go to the push
+/// URLs when there are any)
+///
+/// A remote group (`remotes.<group>`) expands to its members; anything that is not a
+/// configured remote is taken to be a URL itself (`git push https://...`).
+fn remote_urls(dir: &Path, remote: &str, push: bool) -> Vec<String> {
+    if !remote_names(dir).iter().any(|r| r == remote) {
+        let group = git_config_in(dir, &format!("remotes.{}", remote)).unwrap_or_default();
+        if !group.is_empty() {
+            return group.split_whitespace().flat_map(|r| remote_urls(dir, r, push)).collect();
         }
-        AuthMethod::Token { .. } => {
-             Command::new("git")
-                .current_dir(dir)
-                .args(["config", "--local", "--unset", "core.sshCommand"])
-                .output()
-                .ok(); 
+        return git_lines(dir, &["ls-remote", "--get-url", remote]);
+    }
+    if push {
+        git_lines(dir, &["remote", "get-url", "--push", "--all", remote])
+    } else {
+        git_lines(dir, &["remote", "get-url", "--all", remote])
+    }
+}
+
+/// Options of push/fetch/pull/ls-remote and `submodule add` that take a separate value
+const VALUE_OPTIONS: [&str; 22] = [
+    "-o", "--push-option", "--server-option", "--receive-pack", "--upload-pack", "--exec", "--depth", "--deepen",
+    "--shallow-since", "--shallow-exclude", "-j", "--jobs", "--refmap", "--negotiation-tip", "-s", "--strategy", "-X",
+    "-b", "--branch", "--name", "--reference", "--ref-format",
+];
+
+/// Arguments after the subcommand that are not options or option values
+pub fn positional_args(args: &[String]) -> Vec<&str> {
+    let mut positional = Vec::new();
+    let mut skip_value = false;
+    for arg in args.iter().skip(1) {
+        if skip_value {
+            skip_value = false;
+        } else if arg == "--" {
+            continue;
+        } else if arg.starts_with('-') {
+            skip_value = VALUE_OPTIONS.contains(&arg.as_str());
+        } else {
+            positional.push(arg.as_str());
         }
     }
+    positional
+}
 
-    Ok(())
+/// Remote git picks when a command names none: for pushes branch.<b>.pushRemote and
+/// remote.pushDefault first, then the branch's upstream remote, then origin
+fn default_remote(dir: &Path, push: bool) -> String {
+    let branch = Command::new("git")
+        .current_dir(dir)
+        .args(["symbolic-ref", "--short", "-q", "HEAD"])
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|b| !b.is_empty());
+    let branch_key = |key: &str| branch.as_ref().and_then(|b| git_config_in(dir, &format!("branch.{}.{}", b, key)));
+
+    let push_remote = if push {
+        branch_key("pushRemote").or_else(|| git_config_in(dir, "remote.pushDefault"))
+    } else {
+        None
+    };
+    push_remote
+        .or_else(|| branch_key("remote"))
+        // "." is the local repository: nothing to authenticate
+        .filter(|r| r != ".")
+        .unwrap_or_else(|| "origin".to_string())
 }
 
-/// Inject token credential into git credential cache
-fn inject_token_credential(username: &str, token: &str) -> Result<()> {
-    let output = Command::new("git")
-        .args(["remote", "get-url", "origin"])
-        .output();
-
-    if let Ok(out) = output {
-        if out.status.success() {
-            let url = String::from_utf8_lossy(&out.stdout).trim().to_string();
-            if url.starts_with("https://") {
-                if let Some(host_start) = url.strip_prefix("https://") {
-                    let host = host_start.split('/').next().unwrap_or("github.com");
-
-                    let mut child = Command::new("git")
-                        .args(["credential", "approve"])
-                        .stdin(std::process::Stdio::piped())
-                        .spawn()?;
-
-                    if let Some(mut stdin) = child.stdin.take() {
-                        write!(
-                            stdin,
-                            "protocol=https\nhost={}\nusername={}\npassword={}\n",
-                            host, username, token
-                        )?;
-                    }
-                    child.wait()?;
-                }
+/// Remotes (names, groups or URLs) an intercepted command talks to
+///
+/// push/fetch/pull/ls-remote use the remote they were given, else the one git would
+/// pick; `fetch --all` and `remote update` use every remote, `fetch --multiple` each one
+/// listed. Other commands go to origin.
+fn command_remotes(args: &[String], cwd: &Path) -> Vec<String> {
+    let command = args.first().map(|a| a.as_str()).unwrap_or_default();
+    let positional = positional_args(args);
+    let has = |flag: &str| args.iter().skip(1).any(|a| a == flag);
+    let repo_option = args.iter().find_map(|a| a.strip_prefix("--repo="));
+
+    match command {
+        "fetch" if has("--all") => remote_names(cwd),
+        "fetch" if has("--multiple") => positional.iter().map(|r| r.to_string()).collect(),
+        "remote" if positional.first() == Some(&"update") => {
+            let listed: Vec<String> = positional[1..].iter().map(|r| r.to_string()).collect();
+            if listed.is_empty() {
+                remote_names(cwd)
+            } else {
+                listed
+            }
+        }
+        "push" if repo_option.is_some() && positional.is_empty() => repo_option.into_iter().map(String::from).collect(),
+        "push" | "fetch" | "pull" | "ls-remote" => match positional.first() {
+            Some(remote) => vec![remote.to_string()],
+            None => vec![default_remote(cwd, command == "push")],
+        },
+        _ => vec!["origin".to_string()],
+    }
+}
+
+/// URL argument of commands that talk to a repository given on the command line
+///
+/// A clone (or `submodule add`) has no remote configured yet, and the directory it
+/// starts from may be an unrelated repository, so its origin must not be used.
+fn command_url_argument(args: &[String]) -> Option<&str> {
+    match args.first().map(|a| a.as_str()) {
+        Some("clone") => clone_url(args),
+        Some("submodule") if positional_args(args).first() == Some(&"add") => positional_args(args).get(1).copied(),
+        _ => None,
+    }
+}
+
+/// URLs an intercepted command talks to, in the order of `command_remotes`
+fn command_urls(args: &[String], cwd: &Path) -> Vec<String> {
+    if let Some(url) = command_url_argument(args) {
+        // `ls-remote --get-url` applies url.<base>.insteadOf, even outside a repository
+        return git_lines(cwd, &["ls-remote", "--get-url", url]);
+    }
+    let push = args.first().is_some_and(|c| c == "push");
+    let mut urls: Vec<String> = Vec::new();
+    for remote in command_remotes(args, cwd) {
+        for url in remote_urls(cwd, &remote, push) {
+            if !urls.contains(&url) {
+                urls.push(url);
             }
         }
     }
+    urls
+}
+
+/// HTTPS hosts an intercepted command may need credentials for
+///
+/// Empty when the repository has no matching remote (e.g. no origin yet).
+fn command_https_hosts(args: &[String], cwd: &Path) -> Vec<String> {
+    let mut hosts: Vec<String> = Vec::new();
+    for host in command_urls(args, cwd).iter().filter_map(|u| https_host(u)) {
+        if !hosts.contains(&host) {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+/// Host an intercepted command talks to
+///
+/// A clone's remote is its URL argument, not the repository it was started from;
+/// push/fetch/pull use the remote (name or URL) they were given, else the one git picks.
+pub fn command_remote_host(args: &[String], cwd: &Path) -> Option<String> {
+    let url = command_urls(args, cwd).into_iter().next()?;
+    crate::submodule::split_remote_url(&url).map(|(host, _)| host)
+}
+
+/// Host of a repository's origin remote
+pub fn origin_host(dir: &Path) -> Option<String> {
+    let url = git_config_in(dir, "remote.origin.url")?;
+    crate::submodule::split_remote_url(&url).map(|(host, _)| host)
+}
+
+/// The repository URL argument of a `git clone`
+pub fn clone_url(args: &[String]) -> Option<&str> {
+    if args.first().map(|s| s != "clone").unwrap_or(true) {
+        return None;
+    }
+    let clone = crate::clone::CloneArgs::parse(&args[1..])?;
+    let url = args[clone.url_index + 1].as_str();
+    crate::submodule::split_remote_url(url).is_some().then_some(url)
+}
+
+/// HTTPS hosts a token profile authenticates to for an intercepted command
+///
+/// The command's remotes, plus a separate LFS endpoint and, for `git submodule`,
+/// submodules on other hosts: they are fetched with the same account.
+pub fn token_hosts(args: &[String], cwd: &Path) -> Vec<String> {
+    let command = args.first().map(|a| a.as_str());
+    let mut hosts = command_https_hosts(args, cwd);
+    // A clone's LFS endpoint is unknown until it exists; cwd's would be another repo's
+    let lfs_host = if command == Some("clone") { None } else { crate::lfs::lfs_https_host(cwd) };
+    if let Some(host) = lfs_host.filter(|h| !hosts.contains(h)) {
+        hosts.push(host);
+    }
+    if command == Some("submodule") {
+        for host in crate::submodule::submodule_https_hosts(&get_git_root().unwrap_or(cwd.to_path_buf()), args) {
+            if !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+    }
+    hosts
+}
+
+/// URL prefixes a profile's extra HTTP headers are scoped to (`http.<url>.extraHeader`)
+///
+/// The profile's hosts and the hosts it has a token for, else the HTTPS hosts this
+/// command talks to: never every host git may contact (submodules, redirects, LFS).
+pub fn header_urls(profile: &Profile, args: &[String], cwd: &Path) -> Vec<String> {
+    let mut urls: Vec<String> = profile.hosts.iter().map(|p| header_url(p)).collect();
+    if let AuthMethod::Token { host_tokens, .. } = &profile.auth {
+        urls.extend(host_tokens.keys().map(|h| header_url(h)));
+    }
+    if urls.is_empty() {
+        urls = token_hosts(args, cwd).iter().map(|h| header_url(h)).collect();
+    }
+    urls.sort();
+    urls.dedup();
+    urls
+}
+
+/// git's URL prefix for a host pattern: `github.com/acme/*` → `https://github.com/acme/`
+///
+/// git takes `*` in the host (`*.corp.com`) but only literal path prefixes.
+fn header_url(pattern: &str) -> String {
+    let pattern = crate::rules::normalize_host_pattern(pattern);
+    let mut parts = pattern.split('/');
+    let mut url = format!("https://{}/", parts.next().unwrap_or_default());
+    for part in parts.take_while(|p| !p.is_empty() && !p.contains(['*', '?', '['])) {
+        url.push_str(part);
+        url.push('/');
+    }
+    url
+}
+
+/// User name a token is stored under: the provider's convention for token logins
+/// where it has one, else `fallback` (the profile's name)
+pub fn token_username(host: &str, fallback: &str) -> String {
+    crate::provider::for_host(host)
+        .map(|p| p.token_username(fallback))
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+/// Inject token credential into git credential cache
+///
+/// The token only travels on git's stdin (never in arguments or the environment), from a
+/// buffer that is zeroed once written.
+fn inject_token_credential(host: &str, fallback: &str, token: &str) -> Result<()> {
+    let username = token_username(host, fallback);
+    let mut child = Command::new("git")
+        .args(["credential", "approve"])
+        .stdin(std::process::Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let payload = crate::secrets::SecretString::new(format!(
+            "protocol=https\nhost={}\nusername={}\npassword={}\n",
+            host, username, token
+        ));
+        stdin.write_all(payload.as_bytes())?;
+    }
+    if !child.wait()?.success() {
+        anyhow::bail!("git credential approve failed for {}", host);
+    }
 
     Ok(())
 }
 
 /// Log profile usage to ~/.gix/usage.log
-fn log_usage(profile: &Profile, args: &[String]) -> Result<()> {
+///
+/// Each entry is written as one locked `write_all` on an append-only handle, so
+/// concurrent gix processes (IDE and terminal) never interleave lines.
+fn log_usage(
+    profile: &Profile,
+    args: &[String],
+    exit_code: Option<i32>,
+    duration: std::time::Duration,
+) -> Result<()> {
     use chrono::Local;
     use std::fs::OpenOptions;
 
     let log_path = crate::config::get_gix_home_dir()?.join("usage.log");
-
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(log_path)?;
+    if let Some(parent) = log_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
 
     let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
     let command = args.join(" ");
     let cwd = std::env::current_dir().unwrap_or_default();
+    // Killed by a signal: no exit code
+    let exit = exit_code.map(|c| c.to_string()).unwrap_or_else(|| "signal".to_string());
+
+    let outcome = if exit_code == Some(0) { "ok" } else { "failed" };
 
-    writeln!(
-        file,
+    let mut line = format!(
         "[{}] Profile: {} | Cmd: git {} | Dir: {:?}",
         timestamp, profile.profile_name, command, cwd
-    )?;
+    );
+    if let Some(host) = command_remote_host(args, &cwd) {
+        line.push_str(&format!(" | Remote: {}", host));
+    }
+    line.push_str(&format!(
+        " | Exit: {} | Duration: {:.2}s | Result: {}\n",
+        exit,
+        duration.as_secs_f64(),
+        outcome
+    ));
+    // Arguments may carry a token (`-c http.extraHeader=...`, URLs): the log never does
+    let line = crate::secrets::redact(&line);
+
+    let mut file = match OpenOptions::new().create(true).append(true).open(log_path) {
+        Ok(file) => file,
+        // A read-only shared install: usage is simply not logged
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem) => {
+            return Ok(())
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    file.lock()?;
+    file.write_all(line.as_bytes())?;
+    file.unlock()?;
 
     Ok(())
 }
@@ -459,7 +1524,7 @@ pub fn handle_commands_config() -> Result<()> {
     let mut config = load_config()?;
 
     let all_commands = vec![
-        "pull", "push", "clone", "fetch", "commit", "merge", "rebase", "checkout",
+        "pull", "push", "clone", "fetch", "commit", "merge", "rebase", "checkout", "worktree", "submodule",
     ];
 
     let defaults: Vec<bool> = all_commands
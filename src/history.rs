@@ -0,0 +1,346 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::{load_local_config_from_dir, save_local_config_to_dir};
+use crate::git::run_local_git_config;
+
+/// How many profile changes are kept per repository
+const MAX_ENTRIES: usize = 20;
+
+/// A local git config value before and after a profile was applied
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigChange {
+    pub key: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// One applied profile state
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub timestamp: String,
+    pub profile: String,
+    /// Profile selected in .gix/config.json before the change
+    pub previous_profile: Option<String>,
+    pub changes: Vec<ConfigChange>,
+}
+
+/// A local git config value gix wrote, and what the repository had before gix touched it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManagedValue {
+    pub value: Option<String>,
+    pub original: Option<String>,
+}
+
+/// Per-repository record of gix-written values (.gix/managed.json)
+fn managed_path(dir: &Path) -> PathBuf {
+    dir.join(".gix").join("managed.json")
+}
+
+pub fn load_managed(dir: &Path) -> BTreeMap<String, ManagedValue> {
+    fs::read_to_string(crate::paths::long_path(&managed_path(dir)))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_managed(dir: &Path, managed: &BTreeMap<String, ManagedValue>) -> Result<()> {
+    if managed.is_empty() {
+        let _ = fs::remove_file(managed_path(dir));
+        return Ok(());
+    }
+    crate::config::write_atomic(&managed_path(dir), &serde_json::to_string_pretty(managed)?, false)
+}
+
+/// Fold changes into the managed record, keeping the first pre-gix value of each key
+fn record_managed(managed: &mut BTreeMap<String, ManagedValue>, changes: &[ConfigChange]) {
+    for change in changes {
+        let entry = managed.entry(change.key.clone()).or_insert_with(|| ManagedValue {
+            value: None,
+            original: change.before.clone(),
+        });
+        entry.value = change.after.clone();
+        // Back to what the repository had before gix: nothing left to manage
+        if entry.value == entry.original {
+            managed.remove(&change.key);
+        }
+    }
+}
+
+/// Managed keys whose current value is no longer what gix wrote
+fn drifted(dir: &Path) -> Vec<(String, ManagedValue, Option<String>)> {
+    let current = local_config_snapshot(dir);
+    load_managed(dir)
+        .into_iter()
+        .filter_map(|(key, managed)| {
+            let now = current.get(&key).cloned();
+            (now != managed.value).then_some((key, managed, now))
+        })
+        .collect()
+}
+
+/// Warn in 'gix status' about gix-managed values changed by something else
+pub fn print_drift(dir: &Path) {
+    let drift = drifted(dir);
+    if drift.is_empty() {
+        return;
+    }
+    println!("\n   \x1b[1;33m⚠ Changed outside gix:\x1b[0m");
+    for (key, managed, now) in drift {
+        println!(
+            "      {}: gix set {}, now {}",
+            key,
+            managed.value.as_deref().unwrap_or("(unset)").replace('\n', ", "),
+            now.as_deref().unwrap_or("(unset)").replace('\n', ", ")
+        );
+    }
+    println!("      Run 'gix use' to re-apply the profile, or 'gix unuse' to restore pre-gix values.");
+}
+
+/// Per-repository history file (.gix/history.json)
+fn history_path(dir: &Path) -> PathBuf {
+    dir.join(".gix").join("history.json")
+}
+
+pub fn load_history(dir: &Path) -> Vec<HistoryEntry> {
+    fs::read_to_string(crate::paths::long_path(&history_path(dir)))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_history(dir: &Path, history: &[HistoryEntry]) -> Result<()> {
+    crate::config::write_atomic(&history_path(dir), &serde_json::to_string_pretty(history)?, false)
+}
+
+/// All local git config values of a repository (multi-valued keys joined by newlines)
+fn local_config_snapshot(dir: &Path) -> BTreeMap<String, String> {
+    let mut values: BTreeMap<String, String> = BTreeMap::new();
+
+    let Ok(out) = Command::new("git")
+        .current_dir(dir)
+        .args(["config", "--local", "--list", "-z"])
+        .output()
+    else {
+        return values;
+    };
+
+    for item in String::from_utf8_lossy(&out.stdout).split('\0').filter(|i| !i.is_empty()) {
+        let (key, value) = item.split_once('\n').unwrap_or((item, ""));
+        values
+            .entry(key.to_string())
+            .and_modify(|v| {
+                v.push('\n');
+                v.push_str(value);
+            })
+            .or_insert_with(|| value.to_string());
+    }
+    values
+}
+
+/// Run `apply` and remember what it changed so `gix undo` can revert it
+pub fn track<F: FnOnce() -> Result<()>>(dir: &Path, profile_name: &str, apply: F) -> Result<()> {
+    let _lock = crate::config::lock_repo(dir)?;
+    let local_config = load_local_config_from_dir(dir).unwrap_or_default();
+    if let Some(locked) = local_config.selected_profile.as_deref().filter(|_| local_config.locked) {
+        if !crate::profile::same_profile_name(locked, profile_name) {
+            anyhow::bail!(
+                "{} is locked to profile '{}' by team policy (.gix/config.json)",
+                dir.display(),
+                locked
+            );
+        }
+    }
+    let previous_profile = local_config.selected_profile;
+    let before = local_config_snapshot(dir);
+
+    apply()?;
+
+    let after = local_config_snapshot(dir);
+    let changes: Vec<ConfigChange> = before
+        .keys()
+        .chain(after.keys())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .map(|key| ConfigChange {
+            key: key.clone(),
+            before: before.get(key).cloned(),
+            after: after.get(key).cloned(),
+        })
+        .collect();
+
+    crate::registry::record_switch(dir, previous_profile.as_deref(), profile_name)?;
+
+    let mut managed = load_managed(dir);
+    record_managed(&mut managed, &changes);
+    save_managed(dir, &managed)?;
+
+    if changes.is_empty() && previous_profile.as_deref() == Some(profile_name) {
+        return Ok(());
+    }
+
+    let mut history = load_history(dir);
+    history.push(HistoryEntry {
+        timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        profile: profile_name.to_string(),
+        previous_profile,
+        changes,
+    });
+    if history.len() > MAX_ENTRIES {
+        history.drain(..history.len() - MAX_ENTRIES);
+    }
+    save_history(dir, &history)
+}
+
+/// Restore one local git config key to a recorded value
+fn restore_value(dir: &Path, key: &str, value: Option<&str>) -> Result<()> {
+    run_local_git_config(dir, &["--unset-all", key]).ok();
+
+    for v in value.map(|v| v.split('\n').collect::<Vec<_>>()).unwrap_or_default() {
+        run_local_git_config(dir, &["--add", key, v]).with_context(|| format!("Failed to restore {}", key))?;
+    }
+    Ok(())
+}
+
+/// Revert the change `track` just recorded for a repository and forget it
+///
+/// For batches that are applied all or nothing. The last entry is only reverted while
+/// the repository still holds the values it wrote.
+pub fn rollback_last(dir: &Path) -> Result<()> {
+    let _lock = crate::config::lock_repo(dir)?;
+    let mut history = load_history(dir);
+    let current = local_config_snapshot(dir);
+    let Some(entry) = history.last().filter(|e| e.changes.iter().all(|c| current.get(&c.key) == c.after.as_ref())) else {
+        anyhow::bail!("{} changed since; restore its git config by hand", dir.display());
+    };
+
+    for change in &entry.changes {
+        restore_value(dir, &change.key, change.before.as_deref())?;
+    }
+    let reverted: Vec<ConfigChange> = entry
+        .changes
+        .iter()
+        .map(|c| ConfigChange {
+            key: c.key.clone(),
+            before: c.after.clone(),
+            after: c.before.clone(),
+        })
+        .collect();
+    let mut managed = load_managed(dir);
+    record_managed(&mut managed, &reverted);
+    save_managed(dir, &managed)?;
+
+    history.pop();
+    save_history(dir, &history)
+}
+
+/// Handle the 'gix undo' command
+pub fn handle_undo_command() -> Result<()> {
+    if !crate::git::is_inside_git_repo() {
+        println!("\x1b[1;31m✗ Not inside a git repository.\x1b[0m");
+        return Ok(());
+    }
+
+    let dir = std::env::current_dir()?;
+    let _lock = crate::config::lock_repo(&dir)?;
+    let mut local_config = load_local_config_from_dir(&dir).unwrap_or_default();
+    if local_config.locked {
+        anyhow::bail!("This repository's profile is locked by team policy (.gix/config.json)");
+    }
+    let mut history = load_history(&dir);
+    let Some(entry) = history.pop() else {
+        println!("\x1b[1;33m⚠ Nothing to undo: no profile changes recorded for this repository.\x1b[0m");
+        return Ok(());
+    };
+
+    println!(
+        "\x1b[1;36m↩️  Undoing profile '{}' applied at {}\x1b[0m\n",
+        entry.profile, entry.timestamp
+    );
+
+    for change in &entry.changes {
+        restore_value(&dir, &change.key, change.before.as_deref())?;
+        match &change.before {
+            Some(v) => println!("   {} = {}", change.key, v.replace('\n', ", ")),
+            None => println!("   {} (unset)", change.key),
+        }
+    }
+
+    // Undoing is itself a change relative to what gix last wrote
+    let reverted: Vec<ConfigChange> = entry
+        .changes
+        .iter()
+        .map(|c| ConfigChange {
+            key: c.key.clone(),
+            before: c.after.clone(),
+            after: c.before.clone(),
+        })
+        .collect();
+    let mut managed = load_managed(&dir);
+    record_managed(&mut managed, &reverted);
+    save_managed(&dir, &managed)?;
+
+    local_config.selected_profile = entry.previous_profile.clone();
+    save_local_config_to_dir(&local_config, &dir)?;
+    save_history(&dir, &history)?;
+    if let Some(previous) = &entry.previous_profile {
+        crate::registry::record_switch(&dir, Some(&entry.profile), previous)?;
+    }
+
+    match &entry.previous_profile {
+        Some(p) => println!("\n\x1b[1;32m✓ Repository is back on profile '{}'.\x1b[0m", p),
+        None => println!("\n\x1b[1;32m✓ Repository restored; no profile selected.\x1b[0m"),
+    }
+    Ok(())
+}
+
+/// Handle the 'gix unuse' command: restore the values the repository had before gix
+pub fn handle_unuse_command(force: bool) -> Result<()> {
+    if !crate::git::is_inside_git_repo() {
+        println!("\x1b[1;31m✗ Not inside a git repository.\x1b[0m");
+        return Ok(());
+    }
+
+    let dir = std::env::current_dir()?;
+    let _lock = crate::config::lock_repo(&dir)?;
+    let mut local_config = load_local_config_from_dir(&dir).unwrap_or_default();
+    if local_config.locked {
+        anyhow::bail!("This repository's profile is locked by team policy (.gix/config.json)");
+    }
+
+    let managed = load_managed(&dir);
+    if managed.is_empty() && local_config.selected_profile.is_none() {
+        println!("\x1b[1;33m⚠ gix has not configured this repository.\x1b[0m");
+        return Ok(());
+    }
+
+    println!("\x1b[1;36m↩️  Restoring pre-gix configuration\x1b[0m\n");
+
+    let current = local_config_snapshot(&dir);
+    let mut kept = BTreeMap::new();
+    for (key, value) in managed {
+        if current.get(&key) != value.value.as_ref() && !force {
+            // Someone else changed it since; restoring would lose their value
+            println!("   \x1b[1;33m⚠ {} was changed outside gix; left as is (--force to restore)\x1b[0m", key);
+            kept.insert(key, value);
+            continue;
+        }
+        restore_value(&dir, &key, value.original.as_deref())?;
+        match &value.original {
+            Some(v) => println!("   {} = {}", key, v.replace('\n', ", ")),
+            None => println!("   {} (unset)", key),
+        }
+    }
+    save_managed(&dir, &kept)?;
+
+    local_config.selected_profile = None;
+    save_local_config_to_dir(&local_config, &dir)?;
+
+    println!("\n\x1b[1;32m✓ This repository no longer uses a gix profile.\x1b[0m");
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::config::{load_config, load_local_config, Config};
+use crate::detection::{candidates, ALL_SOURCES};
+use crate::git::{get_git_root, git_config_in, is_inside_git_repo};
+use crate::profile::{AuthMethod, Profile};
+use crate::rules::Rule;
+
+/// Handle the 'gix inspect' command: explain the profile choice without changing anything
+pub fn handle_inspect_command(target: String) -> Result<()> {
+    let config = load_config()?;
+
+    let is_url = !Path::new(&target).exists() && crate::submodule::split_remote_url(&target).is_some();
+    if is_url {
+        inspect_url(&config, &target)
+    } else {
+        inspect_repo(&config, Path::new(&target))
+    }
+}
+
+/// Marker for one step of the evaluation
+fn mark(matched: bool) -> &'static str {
+    if matched {
+        "\x1b[1;32m✓\x1b[0m"
+    } else {
+        "\x1b[2m·\x1b[0m"
+    }
+}
+
+/// GIX_PROFILE comes first for every command; returns the profile it names
+fn print_env_step(config: &Config, step: usize) -> Option<String> {
+    match crate::detection::env_profile(config) {
+        Ok(Some(p)) => {
+            println!("   {}. {} GIX_PROFILE → {}", step, mark(true), p.profile_name);
+            Some(p.profile_name.clone())
+        }
+        Ok(None) => {
+            println!("   {}. {} GIX_PROFILE not set", step, mark(false));
+            None
+        }
+        Err(e) => {
+            println!("   {}. \x1b[1;31m✗\x1b[0m {}", step, e);
+            None
+        }
+    }
+}
+
+fn inspect_repo(config: &Config, path: &Path) -> Result<()> {
+    // Detection reads the repository from the working directory, like `--repo`
+    std::env::set_current_dir(path).with_context(|| format!("Cannot access {}", path.display()))?;
+    if !is_inside_git_repo() {
+        anyhow::bail!("{} is not a git repository", path.display());
+    }
+    let root = get_git_root().unwrap_or_else(|| path.to_path_buf());
+    let origin = git_config_in(&root, "remote.origin.url");
+    let email = crate::detection::local_email();
+
+    println!("\x1b[1;36m🔍 Inspecting repository {}\x1b[0m\n", root.display());
+    if let Some(url) = &origin {
+        println!("   Remote origin: {}", masked(url));
+    }
+    println!("   Local user.email: {}\n", email.as_deref().unwrap_or("(not set)"));
+
+    println!("\x1b[1mProfile selection (first match wins):\x1b[0m");
+    let mut chosen = print_env_step(config, 1);
+    let found = candidates(config);
+    for (i, source) in config.detection_order.iter().enumerate() {
+        let hit = found.iter().find(|d| d.source == *source);
+        match hit {
+            Some(d) => {
+                let note = if chosen.is_none() { "  ← chosen" } else { "  (lower precedence)" };
+                println!("   {}. {} {} → {}{}", i + 2, mark(true), d.explain(config), d.profile.profile_name, note);
+                chosen.get_or_insert_with(|| d.profile.profile_name.clone());
+            }
+            None => println!("   {}. {} {}: no match", i + 2, mark(false), source.describe()),
+        }
+    }
+    let skipped: Vec<&str> = ALL_SOURCES
+        .iter()
+        .filter(|s| !config.detection_order.contains(s))
+        .map(|s| s.describe())
+        .collect();
+    if !skipped.is_empty() {
+        println!("      Disabled by detection_order: {}", skipped.join(", "));
+    }
+
+    print_rules(config, email.as_deref(), origin.as_deref());
+
+    if let Some(local) = load_local_config() {
+        if !local.remotes.is_empty() || !local.paths.is_empty() {
+            println!("\n\x1b[1mOverrides for some commands:\x1b[0m");
+            for (remote, profile) in &local.remotes {
+                println!("   push/fetch/pull to '{}' → {}", remote, profile);
+            }
+            for (dir, profile) in &local.paths {
+                println!("   commits in '{}/' → {}", dir, profile);
+            }
+        }
+    }
+
+    print_result(config, chosen.as_deref(), "an intercepted command");
+    Ok(())
+}
+
+fn inspect_url(config: &Config, url: &str) -> Result<()> {
+    println!("\x1b[1;36m🔍 Inspecting clone of {}\x1b[0m\n", masked(url));
+    if crate::credential::split_url_credentials(url).is_some() {
+        println!("   \x1b[1;33m⚠ The URL embeds credentials: gix would offer to move them to a token profile.\x1b[0m\n");
+    }
+
+    println!("\x1b[1mProfile selection for a clone (first match wins):\x1b[0m");
+    let mut chosen = print_env_step(config, 1);
+
+    // Clones made from inside another repository see that repository's profile first
+    match crate::detection::detect(config) {
+        Some(d) => {
+            let note = if chosen.is_none() { "  ← chosen" } else { "  (lower precedence)" };
+            println!("   2. {} {} → {}{}", mark(true), d.explain(config), d.profile.profile_name, note);
+            chosen.get_or_insert_with(|| d.profile.profile_name.clone());
+        }
+        None => println!("   2. {} repository detection / default profile: no match", mark(false)),
+    }
+
+    let matching = crate::rules::profiles_for_url(config, url);
+    match matching.as_slice() {
+        [] => println!("   3. {} host rules: no match", mark(false)),
+        [p] => {
+            let note = if chosen.is_none() { "  ← chosen" } else { "  (lower precedence)" };
+            println!("   3. {} host rule → {}{}", mark(true), p.profile_name, note);
+            chosen.get_or_insert_with(|| p.profile_name.clone());
+        }
+        several => {
+            let names: Vec<&str> = several.iter().map(|p| p.profile_name.as_str()).collect();
+            println!("   3. \x1b[1;33m⚠\x1b[0m host rules are ambiguous ({})", names.join(", "));
+        }
+    }
+
+    print_rules(config, None, Some(url));
+    print_result(config, chosen.as_deref(), "the clone");
+    Ok(())
+}
+
+/// Rule-by-rule evaluation against the repository email and remote URL
+fn print_rules(config: &Config, email: Option<&str>, url: Option<&str>) {
+    if config.rules.is_empty() {
+        return;
+    }
+    println!("\n\x1b[1mRules:\x1b[0m");
+    for rule in &config.rules {
+        let (matched, against) = match rule {
+            Rule::EmailDomain { .. } => (email.is_some_and(|e| rule.matches(e)), "email"),
+            Rule::Host { .. } => (url.is_some_and(|u| rule.matches_url(u)), "URL, clones only"),
+        };
+        let missing = if config.find_profile(rule.profile()).is_none() { " \x1b[1;31m(profile missing)\x1b[0m" } else { "" };
+        println!("   {} {} [{}]{}", mark(matched), rule.describe(), against, missing);
+    }
+}
+
+/// The identity and auth the chosen profile results in
+fn print_result(config: &Config, chosen: Option<&str>, what: &str) {
+    println!();
+    let Some(profile) = chosen.and_then(|name| config.find_profile(name)) else {
+        println!("\x1b[1;33m⚠ No profile matches:\x1b[0m gix would prompt for {} (exit code {} with --no-input).", what, crate::detection::EXIT_NO_PROFILE);
+        return;
+    };
+    println!("\x1b[1mResult:\x1b[0m profile \x1b[1;32m{}\x1b[0m", profile.profile_name);
+    print_identity(profile);
+    println!("\n   Nothing was changed.");
+}
+
+fn print_identity(profile: &Profile) {
+    println!("   Identity: {} <{}>", profile.name, profile.email);
+    match &profile.auth {
+        AuthMethod::SSH { key_path, host_keys } => {
+            println!("   Auth: SSH key {}", key_path);
+            for (host, key) in host_keys {
+                println!("      {}: SSH key {}", host, key);
+            }
+            println!("   ssh command: {}", crate::ssh::preview_ssh_command(profile, None));
+        }
+        AuthMethod::Token { token, host_tokens } => {
+            match crate::profile::token_env_var(token) {
+                Some(var) => println!("   Auth: HTTPS token from ${}", var),
+                None => println!("   Auth: HTTPS token (stored in the gix config)"),
+            }
+            for (host, token) in host_tokens {
+                match crate::profile::token_env_var(token) {
+                    Some(var) => println!("         {}: own token from ${}", host, var),
+                    None => println!("         {}: own token (stored in the gix config)", host),
+                }
+            }
+        }
+    }
+    if let Some(signing) = &profile.signing {
+        println!("   Signing: {}", signing.git_signing_key());
+    }
+    if let Some(sendemail) = &profile.sendemail {
+        println!("   Send-email: {}", sendemail.describe());
+    }
+}
+
+/// A URL safe to print
+fn masked(url: &str) -> String {
+    match crate::credential::split_url_credentials(url) {
+        Some(creds) => creds.masked_url(),
+        None => url.to_string(),
+    }
+}
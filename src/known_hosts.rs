@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::profile::Profile;
+
+/// Published ed25519 host keys of the big providers
+///
+/// SHA256 fingerprints as documented by each provider:
+/// github.com    +DiY3wvvV6TuJJhbpZisF/zLDA0zPMSvHdkr4UvCOqU
+/// gitlab.com    eUXGGm1YGsMAS7vkcx6JOJdOGHPem5gQp4taiCfCLB8
+/// bitbucket.org ybgmFkzwOSotHTHLJgHO0QN8L0xErw6vd0VhFA9m3SM
+/// codeberg.org  mIlxA9k46MmM6qdJOdMnAQpzGxF4WIVVL+fj+wZbw0g
+const PINNED_KEYS: [(&str, &str); 4] = [
+    ("github.com", "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl"),
+    ("gitlab.com", "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIAfuCHKVTjquxvt6CM6tdG4SLp1Btn/nOeHHE5UOzRdf"),
+    ("bitbucket.org", "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIIazEu89wgQZ4bqs3d63QSMzYVa0MuJ2e2gKTKqu+UUO"),
+    ("codeberg.org", "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIIVIC02vnjFyL+I4RHfvIGNtOgJMe769VTF1VR4EB3ZB"),
+];
+
+/// known_hosts file gix maintains next to the user's own (~/.gix/known_hosts)
+pub fn known_hosts_path() -> Option<PathBuf> {
+    crate::config::get_gix_home_dir().ok().map(|d| d.join("known_hosts"))
+}
+
+/// known_hosts lines for a host: built-in provider keys plus the profile's own entries
+fn entries_for(profile: &Profile, host: &str) -> Vec<String> {
+    let builtin = PINNED_KEYS
+        .iter()
+        .filter(|(h, _)| *h == host)
+        .map(|(h, key)| format!("{} {}", h, key));
+    let custom = profile
+        .ssh
+        .known_hosts
+        .iter()
+        .filter(|line| line.split_whitespace().next() == Some(host))
+        .cloned();
+    builtin.chain(custom).collect()
+}
+
+/// Validate a custom entry ("host key-type base64-key")
+pub fn validate_entry(line: &str) -> Result<()> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    match fields[..] {
+        [_, kind, _] if kind.starts_with("ssh-") || kind.starts_with("ecdsa-") || kind.starts_with("sk-") => Ok(()),
+        _ => anyhow::bail!("Invalid known_hosts entry '{}': expected 'host key-type key'", line),
+    }
+}
+
+/// Add the pinned keys of `host` to gix's known_hosts the first time it is used
+///
+/// Returns how many entries were added.
+pub fn ensure_host_keys(profile: &Profile, host: &str) -> Result<usize> {
+    let entries = entries_for(profile, host);
+    let Some(path) = known_hosts_path().filter(|_| !entries.is_empty()) else {
+        return Ok(0);
+    };
+
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let missing: Vec<&String> = entries.iter().filter(|e| !existing.lines().any(|l| l.trim() == e.as_str())).collect();
+    if missing.is_empty() {
+        return Ok(0);
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.lock()?;
+    for entry in &missing {
+        writeln!(file, "{}", entry)?;
+    }
+    file.unlock()?;
+    Ok(missing.len())
+}
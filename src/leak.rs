@@ -0,0 +1,113 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::process::Command;
+
+use crate::profile::Profile;
+use crate::push::PushTarget;
+use crate::submodule::split_remote_url;
+
+/// Hosts where pushed commits become publicly visible
+const PUBLIC_HOSTS: [&str; 5] = ["github.com", "gitlab.com", "bitbucket.org", "codeberg.org", "sr.ht"];
+
+fn is_public_host(host: &str) -> bool {
+    PUBLIC_HOSTS.iter().any(|h| host == *h || host.ends_with(&format!(".{}", h)))
+}
+
+/// A commit about to be pushed that exposes the private address
+#[derive(Debug)]
+struct LeakingCommit {
+    hash: String,
+    subject: String,
+}
+
+fn leaking_commits(profile: &Profile, remote: &str, refs: &[String]) -> Vec<LeakingCommit> {
+    if refs.is_empty() {
+        return vec![];
+    }
+    let mut cmd = Command::new("git");
+    cmd.args(["log", "--format=%H%x1f%ae%x1f%ce%x1f%s"]);
+    cmd.args(refs);
+    // Everything the remote already has is public anyway
+    cmd.args(["--not", &format!("--remotes={}", remote), "--"]);
+
+    let Ok(out) = cmd.output() else {
+        return vec![];
+    };
+    if !out.status.success() {
+        return vec![];
+    }
+
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\x1f').collect();
+            let [hash, author, committer, subject] = fields[..] else {
+                return None;
+            };
+            let leaks = author.eq_ignore_ascii_case(&profile.email) || committer.eq_ignore_ascii_case(&profile.email);
+            leaks.then(|| LeakingCommit {
+                hash: hash[..hash.len().min(8)].to_string(),
+                subject: subject.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Stop an intercepted push that would publish a private email on a public host
+pub fn check_push(profile: &Profile, args: &[String]) -> Result<()> {
+    if !profile.private_email || args.first().map(|a| a != "push").unwrap_or(true) {
+        return Ok(());
+    }
+
+    let target = PushTarget::parse(args);
+    let (remote, refs) = (target.remote.clone(), target.sources());
+    let url = Command::new("git")
+        .args(["remote", "get-url", "--push", &remote])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        // The remote may also be given as a URL directly
+        .unwrap_or_else(|| remote.clone());
+    let Some((host, _)) = split_remote_url(&url) else {
+        return Ok(());
+    };
+    if !is_public_host(&host) {
+        return Ok(());
+    }
+
+    let commits = leaking_commits(profile, &remote, &refs);
+    if commits.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!(
+        "\x1b[1;31m🔒 {} commit(s) to be pushed to {} expose the private email {}:\x1b[0m",
+        commits.len(),
+        host,
+        profile.email
+    );
+    for commit in commits.iter().take(10) {
+        eprintln!("   {} {}", commit.hash, commit.subject);
+    }
+    if commits.len() > 10 {
+        eprintln!("   ... and {} more", commits.len() - 10);
+    }
+
+    let public = profile.alternate_emails.first().map(|e| e.as_str()).unwrap_or("<public email>");
+    eprintln!("\n   To rewrite them with a public address:");
+    eprintln!("     git config user.email {}", public);
+    eprintln!("     git rebase -r {}/<branch> --exec 'git commit --amend --no-edit --reset-author'", remote);
+
+    let interactive = crate::settings::interactive();
+    if interactive
+        && Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Push anyway?")
+            .default(false)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    anyhow::bail!("Push blocked: private email of profile '{}' would be published", profile.profile_name)
+}
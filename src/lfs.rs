@@ -0,0 +1,32 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::git::git_config_in;
+
+/// Whether a repository tracks files with Git LFS
+pub fn uses_lfs(dir: &Path) -> bool {
+    let attributes = fs::read_to_string(dir.join(".gitattributes")).unwrap_or_default();
+    attributes.contains("filter=lfs") || dir.join(".git").join("lfs").is_dir()
+}
+
+/// Version string of the installed git-lfs, if any
+pub fn lfs_version() -> Option<String> {
+    Command::new("git")
+        .args(["lfs", "version"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+/// HTTPS host of an explicitly configured LFS endpoint (`lfs.url`)
+///
+/// Without `lfs.url` git-lfs talks to the remote's own host, which the regular
+/// credential handling already covers.
+pub fn lfs_https_host(dir: &Path) -> Option<String> {
+    let url = git_config_in(dir, "lfs.url")?;
+    let host = url.strip_prefix("https://")?.split('/').next()?;
+    // Drop any embedded userinfo
+    Some(host.rsplit('@').next().unwrap_or(host).to_string())
+}
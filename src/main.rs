@@ -9,39 +9,194 @@
 //! - Automatic profile detection per repository
 //! - Seamless git command interception
 
+// gix's messages carry ANSI colors: print them through the `color` setting
+macro_rules! println {
+    () => {
+        std::println!()
+    };
+    ($($arg:tt)*) => {
+        std::println!("{}", $crate::settings::paint(format!($($arg)*), false))
+    };
+}
+
+macro_rules! eprintln {
+    () => {
+        std::eprintln!()
+    };
+    ($($arg:tt)*) => {
+        std::eprintln!("{}", $crate::settings::paint(format!($($arg)*), true))
+    };
+}
+
+mod adopt;
+mod alias;
+mod bundle;
 mod cli;
+mod clone;
+mod coauthor;
+mod completion;
 mod config;
+mod credential;
+mod credential_helper;
+mod detection;
+mod direnv;
+mod exec;
+mod explain;
+mod export;
 mod git;
+mod history;
+mod inspect;
+mod known_hosts;
+mod leak;
+mod lfs;
+mod managed_block;
+mod overrides;
+mod paths;
+mod policy;
 mod profile;
+mod provider;
+mod push;
+mod registry;
+mod remote;
+mod report;
+mod rules;
+mod scan;
+mod secrets;
+mod selftest;
+mod sendemail;
+mod settings;
+mod shell;
+mod signing;
+mod ssh;
+mod ssh_config;
+mod stats;
+mod submodule;
+mod suggest;
+mod telemetry;
 mod version;
+mod workspace;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{CommandFactory, FromArgMatches};
 
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, HookAction, ImportSource, SshAction};
 use git::{handle_commands_config, handle_git_command, handle_status_command, handle_use_command};
 use profile::handle_profile_command;
 use version::{handle_doctor, handle_update, show_version};
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    settings::enter_nesting()?;
+
+    if let Some(name) = &cli.workspace {
+        workspace::set_workspace(name);
+    }
+    if cli.user {
+        config::enter_user_scope()?;
+    }
+
+    // Relative config overrides must keep pointing at the same files after `--repo`
+    git::pin_git_config_env();
+
+    // Like `git -C`: everything below resolves the repository from the working directory
+    if let Some(repo) = &cli.repo {
+        std::env::set_current_dir(repo)
+            .with_context(|| format!("Cannot access repository path {}", repo.display()))?;
+    }
 
+    if cli.no_input {
+        settings::set_no_input();
+    }
+    if cli.quiet {
+        settings::set_quiet();
+    }
+    if cli.json {
+        settings::set_json();
+    }
+
+    // Counted up front: intercepted git commands exit with git's status
+    telemetry::record_command(&telemetry::command_label(&matches, &cli.git_args));
+
+    if let Err(e) = run(cli) {
+        telemetry::record_error(&e);
+        // Messages may quote URLs, headers or command lines: never print a secret
+        eprintln!("Error: {}", secrets::redact(&format!("{:?}", e)));
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<()> {
     match cli.command {
         Some(Commands::Profile { action }) => handle_profile_command(action),
-        Some(Commands::Set { name }) => profile::handle_set_command(name),
+        // With --repo, `set` configures that repository rather than the global default
+        Some(Commands::Set { name, clear: false }) if cli.repo.is_some() => handle_use_command(name, None, None),
+        Some(Commands::Set { clear: true, .. }) if cli.repo.is_some() => {
+            anyhow::bail!("'gix set --clear' removes the global default profile and takes no --repo")
+        }
+        Some(Commands::Set { name, clear }) => profile::handle_set_command(name, clear),
         Some(Commands::Commands) => handle_commands_config(),
-        Some(Commands::Use { name }) => handle_use_command(name),
-        Some(Commands::Status) => handle_status_command(),
+        Some(Commands::Use { name, global: true, .. }) => git::handle_use_global_command(name),
+        Some(Commands::Use { name, remote, path, .. }) => handle_use_command(name, remote, path),
+        Some(Commands::Status { history }) => handle_status_command(history),
+        Some(Commands::Whoami { email }) => detection::handle_whoami_command(email),
         Some(Commands::Version) => {
             show_version();
             Ok(())
         }
-        Some(Commands::Update { force }) => handle_update(force),
-        Some(Commands::Doctor) => handle_doctor(),
+        Some(Commands::Update { force, dry_run, yes }) => handle_update(force, dry_run, yes),
+        Some(Commands::Doctor { fix, secrets: true }) => secrets::handle_secrets_check(fix),
+        Some(Commands::Doctor { fix, .. }) => handle_doctor(fix),
+        Some(Commands::Verify { range, count, report, format }) => {
+            signing::handle_verify_command(range, count, report, format)
+        }
+        Some(Commands::Coauthor { action }) => coauthor::handle_coauthor_command(action),
+        Some(Commands::Undo) => history::handle_undo_command(),
+        Some(Commands::Unuse { force }) => history::handle_unuse_command(force),
+        Some(Commands::Off) => settings::handle_interception_toggle(false),
+        Some(Commands::On) => settings::handle_interception_toggle(true),
+        // With --authors, `stats` compares this repository's commit authors with its profile instead of reading the usage log
+        Some(Commands::Stats { authors: true }) => stats::handle_repo_stats_command(),
+        Some(Commands::Stats { authors: false }) => stats::handle_stats_command(),
+        Some(Commands::Suggest { yes, all }) => suggest::handle_suggest_command(yes, all),
+        Some(Commands::Settings { action }) => settings::handle_config_command(action),
+        Some(Commands::Rule { action }) => rules::handle_rule_command(action),
+        Some(Commands::Workspace { action }) => workspace::handle_workspace_command(action),
+        Some(Commands::Ssh { action }) => match action {
+            SshAction::Close { name } => ssh::handle_ssh_close(name),
+            SshAction::Sync => ssh_config::handle_ssh_sync(),
+        },
+        Some(Commands::Repos { action }) => registry::handle_repos_command(action),
+        Some(Commands::Direnv { name, write }) => direnv::handle_direnv_command(name, write),
+        Some(Commands::Inspect { target }) => inspect::handle_inspect_command(target),
+        Some(Commands::Exec { profile, command }) => exec::handle_exec_command(profile, command),
+        Some(Commands::CredentialHelper { action }) => credential_helper::handle_credential_helper_command(action),
+        Some(Commands::Remotes { action }) => remote::handle_remote_command(action),
+        Some(Commands::Explain { args }) => explain::handle_explain_command(args),
+        Some(Commands::CloneAs { profile, keep_url, args }) => clone::handle_clone_command(profile, keep_url, args),
+        Some(Commands::Adopt { dir, max_depth }) => adopt::handle_adopt_command(dir, max_depth),
+        Some(Commands::Scan { dir, max_depth, fix, report, format, commits }) => {
+            scan::handle_scan_command(dir, max_depth, fix, report, format, commits)
+        }
+        Some(Commands::Hook { action }) => match action {
+            HookAction::Shell { shell, apply } => shell::handle_hook_shell(shell, apply),
+            HookAction::Check { apply } => shell::handle_hook_check(apply),
+        },
+        Some(Commands::Alias { action }) => alias::handle_alias_command(action),
+        Some(Commands::Selftest) => selftest::handle_selftest(),
+        Some(Commands::Telemetry { action }) => telemetry::handle_telemetry_command(action),
+        #[cfg(feature = "test-env")]
+        Some(Commands::TestEnv { dir, command }) => selftest::handle_test_env(dir, command),
+        Some(Commands::Completions { shell }) => completion::handle_completions_command(shell),
+        Some(Commands::Complete { words }) => completion::handle_complete_command(words),
+        Some(Commands::Import { source }) => match source {
+            ImportSource::Credentials => credential::handle_import_credentials(),
+        },
         None => {
             if cli.git_args.is_empty() {
                 // If no args, show help
-                use clap::CommandFactory;
                 Cli::command().print_help()?;
                 println!();
                 Ok(())
@@ -0,0 +1,120 @@
+//! Blocks of lines gix manages inside files it shares with the user
+//!
+//! A block sits between a start and an end marker line (e.g. `# >>> gix ssh profiles >>>`).
+//! gix only ever rewrites what is between them and leaves every other line as it was.
+
+use anyhow::Result;
+
+/// Where a block goes in a file that has none yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// Before everything else (ssh_config: the first value of an option wins)
+    First,
+    Last,
+}
+
+/// Whether `contents` holds a block opened by `start`
+pub fn contains(contents: &str, start: &str) -> bool {
+    contents.lines().any(|line| line.trim() == start)
+}
+
+/// `existing` with its managed block replaced by `body`, or removed when `body` is None
+///
+/// An existing block is replaced where it is; a new one goes at `new_at`. A start marker
+/// without its end marker is an error: everything after it could be the user's, so
+/// guessing where the block ends would drop their lines.
+pub fn replace(existing: &str, start: &str, end: &str, body: Option<&str>, new_at: Position) -> Result<String> {
+    let mut before: Vec<&str> = Vec::new();
+    let mut after: Vec<&str> = Vec::new();
+    let mut found = false;
+    let mut in_block = false;
+    for line in existing.lines() {
+        match line.trim() {
+            l if l == start && !in_block => {
+                in_block = true;
+                found = true;
+            }
+            l if l == end && in_block => in_block = false,
+            _ if in_block => {}
+            _ if found => after.push(line),
+            _ => before.push(line),
+        }
+    }
+    if in_block {
+        anyhow::bail!("'{}' is never closed by '{}'; fix or remove that line by hand", start, end);
+    }
+    if !found && new_at == Position::First {
+        after = std::mem::take(&mut before);
+    }
+
+    let mut lines = before;
+    if let Some(body) = body {
+        lines.push(start);
+        lines.extend(body.lines());
+        lines.push(end);
+    }
+    lines.extend(after);
+
+    let mut contents = lines.join("\n");
+    if !contents.is_empty() {
+        contents.push('\n');
+    }
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "# >>> gix test >>>";
+    const END: &str = "# <<< gix test <<<";
+
+    #[test]
+    fn adds_a_block_last_or_first() {
+        assert_eq!(replace("a\n", START, END, Some("x"), Position::Last).unwrap(), format!("a\n{}\nx\n{}\n", START, END));
+        assert_eq!(replace("a\n", START, END, Some("x"), Position::First).unwrap(), format!("{}\nx\n{}\na\n", START, END));
+        assert_eq!(replace("", START, END, Some("x"), Position::Last).unwrap(), format!("{}\nx\n{}\n", START, END));
+    }
+
+    #[test]
+    fn replaces_a_block_in_place() {
+        let existing = format!("a\n{}\nold\n{}\nb\n", START, END);
+        let expected = format!("a\n{}\nnew\nlines\n{}\nb\n", START, END);
+        assert_eq!(replace(&existing, START, END, Some("new\nlines"), Position::First).unwrap(), expected);
+        assert_eq!(replace(&expected, START, END, Some("new\nlines"), Position::Last).unwrap(), expected);
+    }
+
+    #[test]
+    fn removes_a_block_and_keeps_the_rest() {
+        let existing = format!("a\n  {}\nold\n{}  \nb", START, END);
+        assert_eq!(replace(&existing, START, END, None, Position::Last).unwrap(), "a\nb\n");
+        assert_eq!(replace("a\nb\n", START, END, None, Position::Last).unwrap(), "a\nb\n");
+        assert_eq!(replace(&format!("{}\n{}\n", START, END), START, END, None, Position::Last).unwrap(), "");
+    }
+
+    #[test]
+    fn merges_duplicate_blocks_into_the_first() {
+        let existing = format!("{s}\n1\n{e}\na\n{s}\n2\n{e}\nb\n", s = START, e = END);
+        let expected = format!("{}\nx\n{}\na\nb\n", START, END);
+        assert_eq!(replace(&existing, START, END, Some("x"), Position::Last).unwrap(), expected);
+    }
+
+    #[test]
+    fn refuses_an_unterminated_block() {
+        let existing = format!("a\n{}\nmine\nalso mine\n", START);
+        assert!(replace(&existing, START, END, Some("x"), Position::Last).is_err());
+        assert!(replace(&existing, START, END, None, Position::Last).is_err());
+    }
+
+    #[test]
+    fn ignores_a_stray_end_marker() {
+        let existing = format!("a\n{}\nb\n", END);
+        assert_eq!(replace(&existing, START, END, None, Position::Last).unwrap(), existing);
+    }
+
+    #[test]
+    fn finds_a_block() {
+        assert!(contains(&format!("a\n  {}\n", START), START));
+        assert!(!contains("a\n", START));
+    }
+}
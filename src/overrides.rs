@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::profile::{AuthMethod, Profile};
+
+/// Environment variables that win over every git config file for a key
+fn env_overrides(key: &str) -> &'static [&'static str] {
+    match key {
+        "user.name" => &["GIT_AUTHOR_NAME", "GIT_COMMITTER_NAME"],
+        "user.email" => &["GIT_AUTHOR_EMAIL", "GIT_COMMITTER_EMAIL"],
+        "core.sshcommand" => &["GIT_SSH_COMMAND", "GIT_SSH"],
+        _ => &[],
+    }
+}
+
+/// One value of a key as git reads it, in precedence order (last wins)
+struct ConfigEntry {
+    scope: String,
+    origin: String,
+    key: String,
+    value: String,
+}
+
+impl ConfigEntry {
+    /// "~/.gitconfig-work (global, via include)"
+    fn describe(&self) -> String {
+        let file = self.origin.trim_start_matches("file:");
+        let included = self.scope == "global"
+            && !crate::git::global_git_config_files()
+                .iter()
+                .any(|f| f.to_string_lossy() == file);
+        if included {
+            format!("{} ({}, via include/includeIf)", file, self.scope)
+        } else {
+            format!("{} ({})", file, self.scope)
+        }
+    }
+}
+
+/// Identity and auth values of every config scope, for the keys a profile sets
+fn identity_entries(dir: &Path) -> Vec<ConfigEntry> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args([
+            "config",
+            "--show-scope",
+            "--show-origin",
+            "-z",
+            "--get-regexp",
+            r"^(user\.(name|email|signingkey)|core\.sshcommand)$",
+        ])
+        .output();
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+
+    // -z format: "<scope>\0<origin>\0<key>\n<value>\0" per entry
+    let text = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = text.split('\0').collect();
+    fields
+        .chunks(3)
+        .filter_map(|chunk| match chunk {
+            [scope, origin, entry] => {
+                let (key, value) = entry.split_once('\n').unwrap_or((entry, ""));
+                Some(ConfigEntry {
+                    scope: scope.to_string(),
+                    origin: origin.to_string(),
+                    key: key.to_lowercase(),
+                    value: value.to_string(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Values the profile puts in a repository's local config (lowercase keys)
+fn expected_values(profile: &Profile, dir: &Path) -> Vec<(&'static str, String)> {
+    let mut values = vec![("user.name", profile.name.clone()), ("user.email", profile.email.clone())];
+    if matches!(profile.auth, AuthMethod::SSH { .. }) {
+        let host = crate::git::origin_host(dir);
+        values.push(("core.sshcommand", crate::ssh::preview_ssh_command(profile, host.as_deref())));
+    }
+    if let Some(signing) = &profile.signing {
+        values.push(("user.signingkey", signing.git_signing_key()));
+    }
+    values
+}
+
+/// Settings outside gix's control that change the identity git uses in `dir`
+pub struct IdentityReport {
+    /// Values git will actually use instead of the profile's
+    pub conflicts: Vec<String>,
+    /// Other values that lose to the repository's local config here but apply elsewhere
+    pub shadowed: Vec<String>,
+}
+
+/// Compare a profile against the environment and every git config scope of a repository
+pub fn check_identity(profile: &Profile, dir: &Path) -> IdentityReport {
+    let entries = identity_entries(dir);
+    let mut report = IdentityReport { conflicts: Vec::new(), shadowed: Vec::new() };
+
+    for (key, expected) in expected_values(profile, dir) {
+        for var in env_overrides(key) {
+            if let Some(value) = std::env::var(var).ok().filter(|v| !v.is_empty() && *v != expected) {
+                report.conflicts.push(format!(
+                    "{}: ${} = '{}' in your environment overrides every config file (profile: '{}')",
+                    key, var, value, expected
+                ));
+            }
+        }
+
+        let values: Vec<&ConfigEntry> = entries.iter().filter(|e| e.key == key).collect();
+        match values.last() {
+            Some(effective) if effective.value != expected => report.conflicts.push(format!(
+                "{} resolves to '{}' from {}, not the profile's '{}'",
+                key,
+                effective.value,
+                effective.describe(),
+                expected
+            )),
+            Some(effective) => {
+                for other in values.iter().filter(|e| e.value != expected && e.scope != effective.scope) {
+                    report.shadowed.push(format!(
+                        "{} = '{}' from {} (overridden here by {} config)",
+                        key,
+                        other.value,
+                        other.describe(),
+                        effective.scope
+                    ));
+                }
+            }
+            None => {}
+        }
+    }
+    report
+}
+
+/// Print what overrides or shadows the profile
+pub fn print_identity_report(report: &IdentityReport) {
+    for conflict in &report.conflicts {
+        println!("   \x1b[1;31m✗\x1b[0m {}", conflict);
+    }
+    for shadowed in &report.shadowed {
+        println!("   \x1b[1;36mℹ\x1b[0m {}", shadowed);
+    }
+    if !report.conflicts.is_empty() || !report.shadowed.is_empty() {
+        println!("     Precedence: environment > worktree > local (.git/config) > global (~/.gitconfig and its includes) > system");
+    }
+    if !report.conflicts.is_empty() {
+        println!("     Unset those variables or values, or run 'gix use' to write the profile to the local config.");
+    }
+}
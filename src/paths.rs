@@ -0,0 +1,80 @@
+//! Windows path handling: long (> MAX_PATH) and UNC paths
+//!
+//! Win32 file APIs reject paths longer than MAX_PATH unless they use the verbatim
+//! `\\?\` form, while git and users expect the plain form. gix keeps plain paths
+//! everywhere and only switches to the verbatim form when touching the filesystem.
+//! On other platforms every helper returns the path unchanged.
+
+use std::path::{Path, PathBuf};
+
+/// Longest path the Win32 APIs take without the `\\?\` prefix
+/// (MAX_PATH minus room for an 8.3 file name, the limit for directories)
+const MAX_PLAIN_PATH: usize = 248;
+
+const VERBATIM: &str = r"\\?\";
+const VERBATIM_UNC: &str = r"\\?\UNC\";
+
+/// Verbatim form of an absolute Windows path (`C:\x` or `\\server\share\x`)
+fn to_verbatim(path: &str) -> Option<String> {
+    if path.starts_with(VERBATIM) {
+        return Some(path.to_string());
+    }
+    let path = path.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return Some(format!("{}{}", VERBATIM_UNC, unc));
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\' {
+        return Some(format!("{}{}", VERBATIM, path));
+    }
+    None
+}
+
+/// Plain form of a verbatim Windows path, as git and users write it
+fn from_verbatim(path: &str) -> Option<String> {
+    if let Some(unc) = path.strip_prefix(VERBATIM_UNC) {
+        return Some(format!(r"\\{}", unc));
+    }
+    let rest = path.strip_prefix(VERBATIM)?;
+    // Only drive paths; verbatim device paths (e.g. \\?\Volume{...}) have no plain form
+    let bytes = rest.as_bytes();
+    (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':').then(|| rest.to_string())
+}
+
+/// Path to hand to file system calls
+///
+/// On Windows, paths too long for the Win32 APIs are made absolute and turned into
+/// their `\\?\` (or `\\?\UNC\`) form. Short paths stay as they are so messages stay readable.
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) || (path.as_os_str().len() < MAX_PLAIN_PATH && path.is_absolute()) {
+        return path.to_path_buf();
+    }
+    // Verbatim paths skip `.`/`..` resolution, so normalize before prefixing
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    if absolute.as_os_str().len() < MAX_PLAIN_PATH {
+        return absolute;
+    }
+    to_verbatim(&absolute.to_string_lossy()).map(PathBuf::from).unwrap_or(absolute)
+}
+
+/// Path to hand to git, store in gix state or show to the user
+///
+/// Strips the `\\?\` prefix that `fs::canonicalize` adds on Windows, which git
+/// does not understand and which would not compare equal to paths git reports.
+pub fn plain_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+    from_verbatim(&path.to_string_lossy()).map(PathBuf::from).unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Path printed by git (`C:/x`, `//server/share/x`) in the native form
+pub fn from_git_path(path: &str) -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(path.replace('/', "\\"))
+    } else {
+        PathBuf::from(path)
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use crate::profile::{same_profile_name, Profile};
+
+/// Overrides the location of the system policy in test builds only: in a release build
+/// any user could point it elsewhere and escape the machine policy
+#[cfg(any(test, feature = "test-env"))]
+const POLICY_ENV: &str = "GIX_POLICY_FILE";
+
+/// Machine-wide policy restricting which hosts each profile may talk to
+///
+/// ```json
+/// { "profiles": { "Work": ["github.com", "*.corp.example", "10.0.0.0/8"] },
+///   "unlisted_profiles": "deny", "audit_log": "/var/log/gix-audit.log" }
+/// ```
+#[derive(Deserialize, Debug, Default)]
+pub struct Policy {
+    /// Allowed host patterns per profile: exact names, `*` globs or CIDR ranges
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Vec<String>>,
+    /// What profiles without an entry may do
+    #[serde(default)]
+    pub unlisted_profiles: Unlisted,
+    /// Where blocked attempts are recorded (default ~/.gix/audit.log)
+    ///
+    /// gix writes it as the user running git, so the user can also edit it: it is a
+    /// record of attempts, not tamper-proof evidence.
+    #[serde(default)]
+    pub audit_log: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Unlisted {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// System policy location
+pub fn policy_path() -> PathBuf {
+    #[cfg(any(test, feature = "test-env"))]
+    if let Some(path) = std::env::var_os(POLICY_ENV) {
+        return PathBuf::from(path);
+    }
+    if cfg!(windows) {
+        let base = std::env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        PathBuf::from(base).join("gix").join("policy.json")
+    } else {
+        PathBuf::from("/etc/gix/policy.json")
+    }
+}
+
+/// The system policy, if one is installed
+///
+/// A policy that exists but cannot be read is an error: failing open would
+/// silently disable enforcement.
+pub fn load_policy() -> Result<Option<Policy>> {
+    let path = policy_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read policy {}", path.display()))?;
+    let policy = serde_json::from_str(&content).with_context(|| format!("Invalid policy {}", path.display()))?;
+    Ok(Some(policy))
+}
+
+/// Whether an IP address lies inside a CIDR range such as `10.0.0.0/8`
+fn in_cidr(ip: IpAddr, cidr: &str) -> bool {
+    let Some((net, bits)) = cidr.split_once('/') else {
+        return false;
+    };
+    let (Ok(net), Ok(bits)) = (net.parse::<IpAddr>(), bits.parse::<u32>()) else {
+        return false;
+    };
+    match (ip, net) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) if bits <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) if bits <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - bits).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if pattern.contains('/') {
+        return host.parse::<IpAddr>().is_ok_and(|ip| in_cidr(ip, pattern));
+    }
+    crate::push::glob_match(&pattern.to_ascii_lowercase(), &host.to_ascii_lowercase())
+}
+
+impl Policy {
+    /// Whether the profile may contact the host
+    pub fn allows(&self, profile: &Profile, host: &str) -> bool {
+        match self.profiles.iter().find(|(name, _)| same_profile_name(name, &profile.profile_name)) {
+            Some((_, patterns)) => patterns.iter().any(|p| host_matches(p, host)),
+            None => self.unlisted_profiles == Unlisted::Allow,
+        }
+    }
+
+    fn audit_path(&self) -> Option<PathBuf> {
+        self.audit_log
+            .clone()
+            .or_else(|| crate::config::get_gix_home_dir().ok().map(|d| d.join("audit.log")))
+    }
+}
+
+/// Record a blocked attempt
+fn audit(policy: &Policy, profile: &Profile, host: &str, args: &[String]) -> Result<()> {
+    let Some(path) = policy.audit_path() else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let user = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_else(|_| "unknown".to_string());
+    let line = format!(
+        "[{}] BLOCKED | User: {} | Profile: {} | Host: {} | Cmd: git {} | Dir: {:?}\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        user,
+        profile.profile_name,
+        host,
+        args.join(" "),
+        std::env::current_dir().unwrap_or_default()
+    );
+    // Arguments may carry a token (`-c http.extraHeader=...`, URLs)
+    let line = crate::secrets::redact(&line);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open audit log {}", path.display()))?;
+    file.lock()?;
+    file.write_all(line.as_bytes())?;
+    file.unlock()?;
+    Ok(())
+}
+
+/// Refuse an intercepted command whose remote host the policy does not allow for the profile
+pub fn enforce(profile: &Profile, host: Option<&str>, args: &[String]) -> Result<()> {
+    let Some(policy) = load_policy()? else {
+        return Ok(());
+    };
+    let Some(host) = host else {
+        return Ok(());
+    };
+    if policy.allows(profile, host) {
+        return Ok(());
+    }
+
+    if let Err(e) = audit(&policy, profile, host, args) {
+        eprintln!("\x1b[1;33m⚠ Could not write audit log: {}\x1b[0m", e);
+    }
+    anyhow::bail!(
+        "Blocked by policy ({}): profile '{}' may not connect to {}",
+        policy_path().display(),
+        profile.profile_name,
+        host
+    )
+}
@@ -2,17 +2,108 @@ use anyhow::{Context, Result};
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Password, Select};
 use directories::BaseDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
 use std::process::Command;
 
 use crate::config::{load_config, save_config, Config};
+use crate::provider::KeyUse;
+use crate::secrets::SecretString;
+use crate::sendemail::SendEmailConfig;
+use crate::signing::{SigningConfig, SigningFormat};
+use crate::ssh::{HostKeyChecking, SshOptions};
 
 /// Authentication method for Git operations
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum AuthMethod {
-    SSH { key_path: String },
-    Token { token: String },
+    SSH {
+        key_path: String,
+        /// Keys for specific hosts (e.g. "gitlab.university.edu"); `key_path` serves the rest
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        host_keys: BTreeMap<String, String>,
+    },
+    Token {
+        token: String,
+        /// Tokens for specific hosts (e.g. "gitlab.example.com"); `token` serves the rest
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        host_tokens: BTreeMap<String, String>,
+    },
+}
+
+impl AuthMethod {
+    /// Token sent to `host`: its entry in `host_tokens`, else the profile's main token
+    pub fn token_for(&self, host: &str) -> Option<&str> {
+        match self {
+            AuthMethod::Token { token, host_tokens } => Some(
+                host_tokens
+                    .iter()
+                    .find(|(h, _)| h.eq_ignore_ascii_case(host))
+                    .map_or(token.as_str(), |(_, t)| t.as_str()),
+            ),
+            AuthMethod::SSH { .. } => None,
+        }
+    }
+
+    /// SSH key used for `host`: its entry in `host_keys`, else the profile's main key
+    /// (also when the host is unknown)
+    pub fn key_for(&self, host: Option<&str>) -> Option<&str> {
+        match self {
+            AuthMethod::SSH { key_path, host_keys } => Some(
+                host.and_then(|host| host_keys.iter().find(|(h, _)| h.eq_ignore_ascii_case(host)))
+                    .map_or(key_path.as_str(), |(_, k)| k.as_str()),
+            ),
+            AuthMethod::Token { .. } => None,
+        }
+    }
+
+    /// Every SSH key the profile holds, the main one first
+    pub fn ssh_keys(&self) -> Vec<&str> {
+        match self {
+            AuthMethod::SSH { key_path, host_keys } => {
+                std::iter::once(key_path.as_str()).chain(host_keys.values().map(|k| k.as_str())).collect()
+            }
+            AuthMethod::Token { .. } => vec![],
+        }
+    }
+
+    /// Every token the profile holds, the main one first
+    pub fn tokens(&self) -> Vec<&str> {
+        match self {
+            AuthMethod::Token { token, host_tokens } => {
+                std::iter::once(token.as_str()).chain(host_tokens.values().map(|t| t.as_str())).collect()
+            }
+            AuthMethod::SSH { .. } => vec![],
+        }
+    }
+}
+
+/// Environment variable named by an `env:NAME` token, read when the token is used
+pub fn token_env_var(token: &str) -> Option<&str> {
+    token.strip_prefix("env:").map(str::trim)
+}
+
+/// The token to send: `env:NAME` tokens are read from the environment at runtime
+///
+/// The value is registered with the redaction layer, so it never reaches a log or an
+/// error message, and is zeroed once dropped.
+pub fn resolve_token(token: &str) -> Result<SecretString> {
+    let value = match token_env_var(token) {
+        Some(var) => std::env::var(var)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .with_context(|| format!("Environment variable {} (the profile's token) is not set", var))?,
+        None => token.to_string(),
+    };
+    crate::secrets::register_secret(&value);
+    Ok(SecretString::new(value))
+}
+
+/// Whether `name` can be exported as an environment variable
+fn is_env_var_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
 /// User profile containing Git identity and authentication
@@ -20,11 +111,334 @@ pub enum AuthMethod {
 pub struct Profile {
     pub name: String,
     pub email: String,
+    /// Other addresses of the same identity (e.g. a GitHub noreply address), used for detection only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternate_emails: Vec<String>,
+    /// Block pushes to public hosts of commits carrying the primary email
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub private_email: bool,
+    /// Branch patterns (e.g. "main", "release/*") whose pushes need confirmation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_branches: Vec<String>,
     pub auth: AuthMethod,
     pub profile_name: String,
+    #[serde(default, skip_serializing_if = "SshOptions::is_default")]
+    pub ssh: SshOptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
+    /// Trailers appended to intercepted commits (e.g. "Signed-off-by")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trailers: Vec<String>,
+    /// Frequent co-authors ("Name <email>") for pairing sessions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coauthors: Vec<String>,
+    #[serde(default, skip_serializing_if = "RepoDefaults::is_default")]
+    pub repo_defaults: RepoDefaults,
+    /// Any other git config applied with the profile (e.g. pull.ff, commit.verbose)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub git_config: BTreeMap<String, String>,
+    /// Flags added to intercepted clones (e.g. "--filter=blob:none")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clone_args: Vec<String>,
+    /// Extra environment for intercepted commands and `gix exec` (e.g. NPM_TOKEN);
+    /// `env:NAME` values are read from the environment at runtime
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// SMTP settings for `git send-email`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sendemail: Option<SendEmailConfig>,
+    /// Remote host patterns (e.g. "github.com:work-org/*", "gitlab.company.com")
+    /// selecting the profile for clones and for repositories by their origin URL
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+    /// HTTP settings for intercepted HTTPS operations
+    #[serde(default, skip_serializing_if = "HttpOptions::is_default")]
+    pub http: HttpOptions,
+}
+
+/// Days a deleted profile stays restorable
+pub const TRASH_DAYS: i64 = 30;
+
+/// A deleted profile kept for 'gix profile restore'
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashedProfile {
+    pub profile: Profile,
+    /// RFC 3339 time of the deletion
+    pub deleted_at: String,
+}
+
+impl TrashedProfile {
+    pub fn new(profile: Profile) -> Self {
+        TrashedProfile { profile, deleted_at: chrono::Local::now().to_rfc3339() }
+    }
+
+    /// Days left before the profile is purged (0 once expired or unreadable)
+    pub fn days_left(&self) -> i64 {
+        chrono::DateTime::parse_from_rfc3339(&self.deleted_at)
+            .map(|deleted| TRASH_DAYS - (chrono::Local::now().fixed_offset() - deleted).num_days())
+            .unwrap_or(0)
+            .max(0)
+    }
+
+    pub fn expired(&self) -> bool {
+        self.days_left() == 0
+    }
+}
+
+/// Git config keys gix sets itself from the profile, which `git_config` must not override
+const RESERVED_GIT_CONFIG: &[&str] = &[
+    "user.name",
+    "user.email",
+    "user.signingkey",
+    "core.sshcommand",
+    "commit.gpgsign",
+    "tag.gpgsign",
+    "gpg.format",
+];
+
+/// Git config that runs commands, loads other config or redirects where git connects
+/// (`*` stands for any name). Profiles are shared ('gix profile import', 'unbundle'),
+/// so a profile's `git_config` never sets these.
+const UNSAFE_GIT_CONFIG: &[&str] = &[
+    "alias.*",
+    "browser.*",
+    "core.alternaterefscommand",
+    "core.askpass",
+    "core.editor",
+    "core.fsmonitor",
+    "core.gitproxy",
+    "core.hookspath",
+    "core.pager",
+    "core.worktree",
+    "credential.*",
+    "diff.external",
+    "diff.*.command",
+    "diff.*.textconv",
+    "difftool.*",
+    "filter.*",
+    "gpg.program",
+    "gpg.*.program",
+    "gpg.ssh.defaultkeycommand",
+    "include.*",
+    "includeif.*",
+    "init.templatedir",
+    "man.*",
+    "merge.*.driver",
+    "mergetool.*",
+    "pager.*",
+    "protocol.*",
+    "remote.*.receivepack",
+    "remote.*.uploadpack",
+    "safe.*",
+    "sendemail.*",
+    "sequence.editor",
+    "submodule.*.update",
+    "uploadpack.packobjectshook",
+    "url.*",
+    "web.browser",
+];
+
+/// Whether a git config key can run commands or change where git connects
+pub fn is_unsafe_git_config(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    UNSAFE_GIT_CONFIG.iter().any(|pattern| crate::push::glob_match(pattern, &key))
+}
+
+/// Variables gix sets itself from the profile, which `env` must not override
+const RESERVED_ENV_VARS: &[&str] = &[
+    "GIT_SSH_COMMAND",
+    "GIT_AUTHOR_NAME",
+    "GIT_AUTHOR_EMAIL",
+    "GIT_COMMITTER_NAME",
+    "GIT_COMMITTER_EMAIL",
+    "GIX_PROFILE",
+];
+
+/// Repository conventions applied with a profile
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoDefaults {
+    /// init.defaultBranch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_branch: Option<String>,
+    /// pull.rebase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_rebase: Option<bool>,
+    /// fetch.prune
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_prune: Option<bool>,
+    /// push.autoSetupRemote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub push_auto_setup_remote: Option<bool>,
+    /// Rewrite submodule URLs to the profile's protocol (SSH or HTTPS)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rewrite_submodules: bool,
+}
+
+impl RepoDefaults {
+    pub fn is_default(&self) -> bool {
+        self == &RepoDefaults::default()
+    }
+
+    /// Git config values for the configured defaults
+    pub fn git_config(&self) -> Vec<(String, String)> {
+        let mut values = Vec::new();
+        if let Some(branch) = &self.default_branch {
+            values.push(("init.defaultBranch".to_string(), branch.clone()));
+        }
+        let flags = [
+            ("pull.rebase", self.pull_rebase),
+            ("fetch.prune", self.fetch_prune),
+            ("push.autoSetupRemote", self.push_auto_setup_remote),
+        ];
+        for (key, value) in flags {
+            if let Some(v) = value {
+                values.push((key.to_string(), v.to_string()));
+            }
+        }
+        values
+    }
+}
+
+/// HTTP settings a network requires (corporate proxies, header-based tokens)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpOptions {
+    /// http.userAgent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// http.extraHeader lines ("Name: value"); an `env:NAME` value is read from the environment at runtime
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_headers: Vec<String>,
+}
+
+impl HttpOptions {
+    pub fn is_default(&self) -> bool {
+        self == &HttpOptions::default()
+    }
+
+    /// Header lines to send, with `env:NAME` values resolved
+    pub fn resolved_headers(&self) -> Result<Vec<String>> {
+        self.extra_headers
+            .iter()
+            .map(|header| {
+                let (name, value) = header.split_once(':').unwrap_or((header, ""));
+                match token_env_var(value.trim()) {
+                    Some(var) => std::env::var(var)
+                        .ok()
+                        .filter(|v| !v.is_empty())
+                        .map(|v| format!("{}: {}", name.trim(), v))
+                        .with_context(|| format!("Environment variable {} (for the {} header) is not set", var, name.trim())),
+                    None => Ok(header.clone()),
+                }
+            })
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(agent) = self.user_agent.as_ref().filter(|a| a.trim().is_empty() || a.contains('\n')) {
+            anyhow::bail!("Invalid HTTP user agent: '{}'", agent);
+        }
+        for header in &self.extra_headers {
+            let name = header.split_once(':').map(|(n, _)| n.trim()).unwrap_or("");
+            if name.is_empty() || name.contains(char::is_whitespace) || header.contains(['\r', '\n']) {
+                anyhow::bail!("Invalid HTTP header '{}': use 'Name: value'", header);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Profile names are compared without regard to case or surrounding whitespace
+pub fn same_profile_name(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+/// Whether a `host_keys` entry is a plain host name
+///
+/// The hosts become ssh_config patterns, so nothing ssh would read as syntax is allowed.
+pub fn is_key_host(host: &str) -> bool {
+    !host.is_empty() && !host.contains(['/', '@', '*', '?', '!', ',', '"', '#']) && !host.contains(char::is_whitespace)
 }
 
 impl Profile {
+    /// Whether this profile is the one called `name` ("work" matches "Work")
+    pub fn is_named(&self, name: &str) -> bool {
+        same_profile_name(&self.profile_name, name)
+    }
+
+    /// Create a profile with default optional settings
+    pub fn new(profile_name: String, name: String, email: String, auth: AuthMethod) -> Self {
+        Profile {
+            name,
+            email,
+            alternate_emails: vec![],
+            private_email: false,
+            protected_branches: vec![],
+            auth,
+            profile_name,
+            ssh: SshOptions::default(),
+            signing: None,
+            trailers: vec![],
+            coauthors: vec![],
+            repo_defaults: RepoDefaults::default(),
+            git_config: BTreeMap::new(),
+            clone_args: vec![],
+            env: BTreeMap::new(),
+            sendemail: None,
+            hosts: vec![],
+            http: HttpOptions::default(),
+        }
+    }
+
+    /// Git config applied with the profile: repository defaults, then the extra keys
+    pub fn git_config_values(&self) -> Vec<(String, String)> {
+        let mut values = self.repo_defaults.git_config();
+        // Also for configs edited by hand, which skip validation
+        let safe = self.git_config.iter().filter(|(k, _)| !is_unsafe_git_config(k));
+        values.extend(safe.map(|(k, v)| (k.clone(), v.clone())));
+        values
+    }
+
+    /// The profile's extra environment with `env:NAME` references resolved
+    pub fn exported_env(&self) -> Result<Vec<(String, String)>> {
+        self.env
+            .iter()
+            .map(|(name, value)| match token_env_var(value) {
+                Some(var) => std::env::var(var)
+                    .ok()
+                    .map(|v| (name.clone(), v))
+                    .with_context(|| format!("Environment variable {} (for {} of profile '{}') is not set", var, name, self.profile_name)),
+                None => Ok((name.clone(), value.clone())),
+            })
+            .collect()
+    }
+
+    /// Whether a remote URL matches one of the profile's host patterns
+    pub fn matches_url(&self, url: &str) -> bool {
+        self.hosts.iter().any(|pattern| crate::rules::url_matches_host_pattern(url, pattern))
+    }
+
+    /// Whether an address is the primary or one of the alternate emails
+    pub fn owns_email(&self, email: &str) -> bool {
+        self.email.eq_ignore_ascii_case(email)
+            || self.alternate_emails.iter().any(|e| e.eq_ignore_ascii_case(email))
+    }
+
+    /// Fully formed trailer lines for commits made with this profile
+    ///
+    /// A bare key such as `Signed-off-by` gets the profile identity as value.
+    pub fn trailer_lines(&self) -> Vec<String> {
+        self.trailers
+            .iter()
+            .map(|t| {
+                if t.contains(':') {
+                    t.clone()
+                } else {
+                    format!("{}: {} <{}>", t, self.name, self.email)
+                }
+            })
+            .collect()
+    }
+
     /// Validate the profile configuration
     pub fn validate(&self) -> Result<()> {
         // Validate email format (basic check)
@@ -41,18 +455,104 @@ impl Profile {
             anyhow::bail!("Profile name cannot contain path separators");
         }
 
-        // Validate SSH key if applicable
-        if let AuthMethod::SSH { key_path } = &self.auth {
+        if let Some(alt) = self.alternate_emails.iter().find(|e| !e.contains('@')) {
+            anyhow::bail!("Invalid alternate email: {}", alt);
+        }
+
+        if let Some(pattern) = self.hosts.iter().find(|h| h.trim().is_empty() || h.contains("://")) {
+            anyhow::bail!("Invalid host pattern: '{}' (use e.g. github.com:acme/* or gitlab.acme.com)", pattern);
+        }
+
+        for entry in &self.ssh.known_hosts {
+            crate::known_hosts::validate_entry(entry)?;
+        }
+
+        for key in self.git_config.keys() {
+            if !is_git_config_key(key) {
+                anyhow::bail!("Invalid git config key '{}': use section.name (e.g. commit.verbose)", key);
+            }
+            if RESERVED_GIT_CONFIG.contains(&key.to_ascii_lowercase().as_str()) {
+                anyhow::bail!("git config '{}' is set by gix from the profile and cannot be overridden", key);
+            }
+            if is_unsafe_git_config(key) {
+                anyhow::bail!(
+                    "git config '{}' can run commands or redirect git, so profiles cannot set it; put it in your own git config",
+                    key
+                );
+            }
+        }
+
+        // Clone flags are spliced before the URL, so each must be a single --option[=value]
+        if let Some(arg) = self.clone_args.iter().find(|a| !a.starts_with("--")) {
+            anyhow::bail!("Invalid clone flag '{}': use the --option=value form", arg);
+        }
+
+        // Validate SSH keys if applicable
+        if let AuthMethod::SSH { host_keys, .. } = &self.auth {
+            for host in host_keys.keys() {
+                if !is_key_host(host) {
+                    anyhow::bail!("Invalid host for an SSH key: '{}' (expected e.g. gitlab.example.com)", host);
+                }
+            }
+        }
+        for key_path in self.auth.ssh_keys() {
             self.validate_ssh_key(key_path)?;
         }
 
+        if let Some(program) = &self.ssh.program {
+            crate::ssh::check_ssh_program(program).map_err(|e| anyhow::anyhow!("ssh program: {}", e))?;
+        }
+
+        if let Some(signing) = self.signing.as_ref().filter(|s| s.format == SigningFormat::Openpgp) {
+            let id = signing.key_path.trim_start_matches("0x");
+            if id.len() < 8 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+                anyhow::bail!("Invalid GPG key ID: '{}' (expected a hex key ID or fingerprint)", signing.key_path);
+            }
+        }
+
+        if let Some(sendemail) = &self.sendemail {
+            sendemail.validate()?;
+        }
+
+        self.http.validate()?;
+
+        for (name, value) in &self.env {
+            if !is_env_var_name(name) {
+                anyhow::bail!("Invalid environment variable name: '{}'", name);
+            }
+            if RESERVED_ENV_VARS.contains(&name.as_str()) {
+                anyhow::bail!("{} is set by gix from the profile and cannot be overridden", name);
+            }
+            if token_env_var(value).is_some_and(|var| !is_env_var_name(var)) {
+                anyhow::bail!("Invalid environment variable name in {}: '{}'", name, value);
+            }
+        }
+
+        if let AuthMethod::Token { host_tokens, .. } = &self.auth {
+            for (host, token) in host_tokens {
+                if host.is_empty() || host.contains(['/', '@', ' ']) {
+                    anyhow::bail!("Invalid host for a token: '{}' (expected e.g. gitlab.example.com)", host);
+                }
+                if token.is_empty() {
+                    anyhow::bail!("The token for {} is empty", host);
+                }
+            }
+        }
+        for token in self.auth.tokens() {
+            if let Some(var) = token_env_var(token) {
+                if var.is_empty() || !var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    anyhow::bail!("Invalid environment variable name in token: '{}'", token);
+                }
+            }
+        }
+
         Ok(())
     }
 
-    /// Validate SSH key exists and has proper permissions
+    /// Validate SSH key exists
     fn validate_ssh_key(&self, key_path: &str) -> Result<()> {
-        let path = PathBuf::from(key_path);
-        
+        let path = crate::ssh::resolve_key_path(key_path);
+
         if !path.exists() {
             anyhow::bail!("SSH key not found at: {}", key_path);
         }
@@ -61,20 +561,16 @@ impl Profile {
             anyhow::bail!("SSH key path is not a file: {}", key_path);
         }
 
-        // Check file permissions on Unix
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let metadata = fs::metadata(&path)?;
-            let mode = metadata.permissions().mode();
-            let perms = mode & 0o777;
-            
-            // SSH keys should have permissions 600 or 400
-            if perms > 0o600 {
-                println!("\x1b[1;33m⚠ Warning: SSH key has insecure permissions ({:o}). Consider running: chmod 600 {}\x1b[0m", perms, key_path);
-            }
-        }
+        Ok(())
+    }
 
+    /// Report SSH keys with permissions ssh would refuse and offer to fix them
+    ///
+    /// Kept out of `validate`, which also runs where nobody can answer a prompt.
+    pub fn check_key_permissions(&self) -> Result<()> {
+        for key_path in self.auth.ssh_keys() {
+            crate::ssh::check_key_permissions(&crate::ssh::resolve_key_path(key_path), false)?;
+        }
         Ok(())
     }
 
@@ -82,7 +578,7 @@ impl Profile {
     #[allow(dead_code)]
     pub fn get_ssh_key_path(&self) -> Option<&str> {
         match &self.auth {
-            AuthMethod::SSH { key_path } => Some(key_path),
+            AuthMethod::SSH { key_path, .. } => Some(key_path),
             AuthMethod::Token { .. } => None,
         }
     }
@@ -117,7 +613,7 @@ pub fn list_ssh_keys() -> Vec<String> {
 /// Interactive profile selection
 pub fn select_profile(config: &Config) -> Option<&Profile> {
     if config.profiles.is_empty() {
-        println!("\x1b[1;33m⚠ No profiles configured. Run 'gix profile add' to create one.\x1b[0m");
+        eprintln!("\x1b[1;33m⚠ No profiles configured. Run 'gix profile add' to create one.\x1b[0m");
         return None;
     }
 
@@ -135,46 +631,205 @@ pub fn select_profile(config: &Config) -> Option<&Profile> {
     Some(&config.profiles[selection])
 }
 
+/// A profile as shown by 'gix profile list --json': secrets are left out, names of
+/// environment variables and headers kept
+fn profile_json(config: &Config, profile: &Profile) -> serde_json::Value {
+    use serde_json::json;
+
+    let auth = match &profile.auth {
+        AuthMethod::SSH { key_path, host_keys } => json!({
+            "method": "ssh",
+            "key_path": key_path,
+            "key_exists": crate::ssh::resolve_key_path(key_path).exists(),
+            "host_keys": host_keys,
+        }),
+        AuthMethod::Token { token, host_tokens } => json!({
+            "method": "token",
+            "token_env": token_env_var(token),
+            "token_available": resolve_token(token).is_ok(),
+            "host_tokens": host_tokens
+                .iter()
+                .map(|(host, t)| (host.clone(), json!({ "token_env": token_env_var(t), "token_available": resolve_token(t).is_ok() })))
+                .collect::<serde_json::Map<_, _>>(),
+        }),
+    };
+    let headers: Vec<&str> = profile.http.extra_headers.iter().map(|h| h.split(':').next().unwrap_or(h).trim()).collect();
+    json!({
+        "name": profile.profile_name,
+        "user_name": profile.name,
+        "email": profile.email,
+        "default": config.default_profile.as_deref().is_some_and(|d| profile.is_named(d)),
+        "alternate_emails": profile.alternate_emails,
+        "hosts": profile.hosts,
+        "private_email": profile.private_email,
+        "auth": auth,
+        "signing": profile.signing.as_ref().map(|s| json!({
+            "format": s.format,
+            "key": s.key_path,
+            "smartcard": s.smartcard,
+        })),
+        "trailers": profile.trailers,
+        "protected_branches": profile.protected_branches,
+        "git_config": profile.git_config,
+        "env": profile.env.keys().collect::<Vec<_>>(),
+        "http_headers": headers,
+    })
+}
+
 /// Handle profile-related commands
 pub fn handle_profile_command(action: crate::cli::ProfileAction) -> Result<()> {
     let mut config = load_config()?;
 
     match action {
+        crate::cli::ProfileAction::Bundle { name, output } => {
+            return crate::bundle::handle_bundle_command(name, output);
+        }
+        crate::cli::ProfileAction::Unbundle { file } => {
+            return crate::bundle::handle_unbundle_command(file);
+        }
+        crate::cli::ProfileAction::Export { names, output, no_secrets } => {
+            return crate::export::handle_export_command(names, output, no_secrets);
+        }
+        crate::cli::ProfileAction::Import { file } => {
+            return crate::export::handle_import_command(file);
+        }
+        crate::cli::ProfileAction::Test { name } => {
+            return crate::provider::handle_test_command(name);
+        }
+        crate::cli::ProfileAction::SetDefault { name, clear } => {
+            return handle_set_command(name, clear);
+        }
+        crate::cli::ProfileAction::List if crate::settings::json_output() => {
+            let profiles: Vec<serde_json::Value> = config.profiles.iter().map(|p| profile_json(&config, p)).collect();
+            let trash: Vec<&str> = config.trash.iter().map(|t| t.profile.profile_name.as_str()).collect();
+            crate::settings::print_json(&serde_json::json!({
+                "profiles": profiles,
+                "default_profile": config.default_profile,
+                "default_profile_missing": config.missing_default_profile().is_some(),
+                "trash": trash,
+            }))?;
+        }
         crate::cli::ProfileAction::List => {
             if config.profiles.is_empty() {
                 println!("\x1b[1;33m📋 No profiles configured.\x1b[0m");
                 println!("   Run '\x1b[1mgix profile add\x1b[0m' to create your first profile.");
             } else {
                 println!("\x1b[1;36m📋 Configured profiles:\x1b[0m\n");
+                print_default_profile(&config);
+                println!();
                 for (i, profile) in config.profiles.iter().enumerate() {
                     let auth_info = match &profile.auth {
-                        AuthMethod::SSH { key_path } => {
-                            let key_exists = PathBuf::from(key_path).exists();
+                        AuthMethod::SSH { key_path, host_keys } => {
+                            let key_exists = crate::ssh::resolve_key_path(key_path).exists();
                             let status = if key_exists { "✓" } else { "✗" };
-                            format!("SSH: {} {}", key_path, status)
+                            let main = format!("SSH: {} {}", key_path, status);
+                            if host_keys.is_empty() {
+                                main
+                            } else {
+                                let hosts: Vec<&str> = host_keys.keys().map(|h| h.as_str()).collect();
+                                format!("{} (own keys for {})", main, hosts.join(", "))
+                            }
+                        }
+                        AuthMethod::Token { token, host_tokens } => {
+                            let main = match token_env_var(token) {
+                                Some(var) => {
+                                    let status = if resolve_token(token).is_ok() { "✓" } else { "✗ not set" };
+                                    format!("Token: ${} {}", var, status)
+                                }
+                                None => "Token: ••••••••".to_string(),
+                            };
+                            if host_tokens.is_empty() {
+                                main
+                            } else {
+                                let hosts: Vec<&str> = host_tokens.keys().map(|h| h.as_str()).collect();
+                                format!("{} (own tokens for {})", main, hosts.join(", "))
+                            }
                         }
-                        AuthMethod::Token { .. } => "Token: ••••••••".to_string(),
                     };
+                    let is_default = config.default_profile.as_deref().is_some_and(|d| profile.is_named(d));
                     println!(
-                        "  \x1b[1;32m{}\x1b[0m. \x1b[1m{}\x1b[0m",
+                        "  \x1b[1;32m{}\x1b[0m. \x1b[1m{}\x1b[0m{}",
                         i + 1,
-                        profile.profile_name
+                        profile.profile_name,
+                        if is_default { "  \x1b[1;36m⭐ default\x1b[0m" } else { "" }
                     );
                     println!("     👤 {} <{}>", profile.name, profile.email);
+                    if !profile.alternate_emails.is_empty() {
+                        println!("     📧 Also: {}", profile.alternate_emails.join(", "));
+                    }
+                    if !profile.hosts.is_empty() {
+                        println!("     🌍 Hosts: {}", profile.hosts.join(", "));
+                    }
+                    if profile.private_email {
+                        println!("     🔒 Private email (not pushed to public hosts)");
+                    }
+                    if let Some(signing) = &profile.signing {
+                        match (signing.format, signing.smartcard) {
+                            (SigningFormat::Ssh, _) => println!("     ✍️  Signing: {}", signing.key_path),
+                            (SigningFormat::Openpgp, false) => println!("     ✍️  Signing: GPG {}", signing.key_path),
+                            (SigningFormat::Openpgp, true) => println!("     ✍️  Signing: GPG {} (smartcard)", signing.key_path),
+                        }
+                    }
+                    if profile.ssh.multiplex {
+                        println!("     🔌 Shared SSH connections");
+                    }
+                    if let Some(checking) = profile.ssh.host_key_checking {
+                        println!("     🛂 Host key checking: {}", checking.as_ssh_value());
+                    }
+                    if profile.ssh.pin_host_keys {
+                        println!("     🔏 Pinned host keys ({} custom)", profile.ssh.known_hosts.len());
+                    }
+                    let defaults = profile.git_config_values();
+                    if !defaults.is_empty() || profile.repo_defaults.rewrite_submodules {
+                        let mut shown: Vec<String> = defaults.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                        if profile.repo_defaults.rewrite_submodules {
+                            shown.push("submodule URLs rewritten".to_string());
+                        }
+                        println!("     ⚙️  Defaults: {}", shown.join(", "));
+                    }
+                    if !profile.trailers.is_empty() {
+                        println!("     📝 Trailers: {}", profile.trailers.join(", "));
+                    }
+                    if !profile.protected_branches.is_empty() {
+                        println!("     🛡️  Protected: {}", profile.protected_branches.join(", "));
+                    }
+                    if !profile.clone_args.is_empty() {
+                        println!("     📥 Clone: {}", profile.clone_args.join(" "));
+                    }
+                    if let Some(sendemail) = &profile.sendemail {
+                        println!("     📮 Send-email: {}", sendemail.describe());
+                    }
+                    if !profile.env.is_empty() {
+                        // Names only: values are often credentials
+                        let names: Vec<&str> = profile.env.keys().map(|k| k.as_str()).collect();
+                        println!("     🌐 Env: {}", names.join(", "));
+                    }
+                    if !profile.http.is_default() {
+                        // Header names only: values are often credentials
+                        let mut shown: Vec<String> = profile.http.user_agent.iter().map(|a| format!("User-Agent {}", a)).collect();
+                        shown.extend(profile.http.extra_headers.iter().map(|h| h.split(':').next().unwrap_or(h).trim().to_string()));
+                        println!("     📡 HTTP: {}", shown.join(", "));
+                    }
                     println!("     🔑 {}\n", auth_info);
                 }
             }
+            if !config.trash.is_empty() {
+                let names: Vec<&str> = config.trash.iter().map(|t| t.profile.profile_name.as_str()).collect();
+                println!("   🗑️  In the trash: {} ('gix profile restore' to bring back)", names.join(", "));
+            }
         }
         crate::cli::ProfileAction::Add => {
             println!("\x1b[1;36m➕ Add New Profile\x1b[0m\n");
 
-            let profile_name: String = Input::with_theme(&ColorfulTheme::default())
+            let profile_name: String = Input::<String>::with_theme(&ColorfulTheme::default())
                 .with_prompt("Profile Name (e.g. Work, Personal)")
-                .interact_text()?;
+                .interact_text()?
+                .trim()
+                .to_string();
 
-            // Check for duplicate names
-            if config.profiles.iter().any(|p| p.profile_name == profile_name) {
-                anyhow::bail!("A profile with name '{}' already exists", profile_name);
+            // Check for duplicate names ("work" and "Work" are the same profile)
+            if let Some(existing) = config.find_profile(&profile_name) {
+                anyhow::bail!("A profile with name '{}' already exists", existing.profile_name);
             }
 
             let user_name: String = Input::with_theme(&ColorfulTheme::default())
@@ -201,27 +856,56 @@ pub fn handle_profile_command(action: crate::cli::ProfileAction) -> Result<()> {
                 .interact()
                 .unwrap_or(0);
 
-            let auth = if auth_selection == 0 {
+            let (auth, signing) = if auth_selection == 0 {
                 create_ssh_auth(&email)?
             } else {
-                create_token_auth()?
+                (create_token_auth()?, None)
             };
 
-            let new_profile = Profile {
-                profile_name,
-                name: user_name,
-                email,
-                auth,
-            };
+            let mut new_profile = Profile::new(profile_name, user_name, email, auth);
+            new_profile.signing = signing;
+            new_profile.alternate_emails = ask_alternate_emails(&[])?;
+            new_profile.hosts = ask_hosts(&[])?;
+            new_profile.private_email = ask_private_email(false)?;
+            if auth_selection == 0 {
+                new_profile.ssh.use_keychain = ask_use_keychain(false)?;
+                new_profile.ssh.multiplex = ask_multiplex(false)?;
+                (new_profile.ssh.pin_host_keys, new_profile.ssh.known_hosts) = ask_pin_host_keys(false, &[])?;
+                new_profile.ssh.host_key_checking = ask_host_key_checking(None)?;
+            }
+            new_profile.trailers = ask_trailers(&new_profile.trailers)?;
+            new_profile.repo_defaults = ask_repo_defaults(&new_profile.repo_defaults)?;
+            new_profile.git_config = ask_git_config(&new_profile.git_config)?;
+            new_profile.clone_args = ask_clone_args(&new_profile.clone_args)?;
+            new_profile.env = ask_env(&new_profile.env)?;
+            if Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Set an HTTP user agent or extra headers (corporate networks)?")
+                .default(false)
+                .interact()?
+            {
+                new_profile.http = ask_http(&new_profile.http)?;
+            }
+            if Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Configure git send-email (SMTP) for this profile?")
+                .default(false)
+                .interact()?
+            {
+                new_profile.sendemail =
+                    crate::sendemail::ask_sendemail(None, &new_profile.name, &new_profile.email)?;
+            }
+            new_profile.protected_branches = ask_protected_branches(&[])?;
 
             // Validate before saving
             new_profile.validate()?;
+            new_profile.check_key_permissions()?;
 
             config.profiles.push(new_profile);
             save_config(&config)?;
+            crate::signing::sync_allowed_signers(&config)?;
+            crate::ssh::sync_profile_configs(&config)?;
             println!("\n\x1b[1;32m✓ Profile added successfully!\x1b[0m");
         }
-        crate::cli::ProfileAction::Delete { name } => {
+        crate::cli::ProfileAction::Delete { name, permanent } => {
             let profile_name = if let Some(n) = name {
                 n
             } else {
@@ -237,28 +921,77 @@ pub fn handle_profile_command(action: crate::cli::ProfileAction) -> Result<()> {
                 selections[selection].clone()
             };
 
-            // Double confirmation for safety
-            println!("\n\x1b[1;31m⚠ WARNING: This action cannot be undone!\x1b[0m");
-            if Confirm::with_theme(&ColorfulTheme::default())
-                .with_prompt(format!("Are you sure you want to delete '{}'?", profile_name))
-                .default(false)
-                .interact()?
-            {
-                if Confirm::with_theme(&ColorfulTheme::default())
-                    .with_prompt("Type 'yes' to confirm deletion")
-                    .default(false)
-                    .interact()?
-                {
-                    config.profiles.retain(|p| p.profile_name != profile_name);
-                    save_config(&config)?;
+            let profile_name = config
+                .find_profile(&profile_name)
+                .map(|p| p.profile_name.clone())
+                .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", profile_name))?;
+
+            // Typing the name, not just pressing Enter, confirms the deletion
+            if permanent {
+                println!("\n\x1b[1;31m⚠ Deleting '{}' permanently\x1b[0m (it cannot be restored)", profile_name);
+            } else {
+                println!("\n\x1b[1;31m⚠ Deleting '{}'\x1b[0m (restorable for {} days with 'gix profile restore')", profile_name, TRASH_DAYS);
+            }
+            if crate::settings::confirm_typed(&profile_name)? {
+                if let Some(p) = config.find_profile(&profile_name) {
+                    for key_path in p.auth.ssh_keys() {
+                        crate::ssh::close_control_sockets(key_path, &p.ssh);
+                    }
+                }
+                let (deleted, kept): (Vec<Profile>, Vec<Profile>) =
+                    config.profiles.drain(..).partition(|p| p.is_named(&profile_name));
+                config.profiles = kept;
+                // A newer deletion of the same name replaces the older one
+                config.trash.retain(|t| !t.profile.is_named(&profile_name));
+                if !permanent {
+                    config.trash.extend(deleted.into_iter().map(TrashedProfile::new));
+                }
+                save_config(&config)?;
+                crate::signing::sync_allowed_signers(&config)?;
+                crate::ssh::sync_profile_configs(&config)?;
+                if permanent {
                     println!("\x1b[1;32m✓ Profile deleted.\x1b[0m");
                 } else {
-                    println!("Deletion cancelled.");
+                    println!("\x1b[1;32m✓ Profile moved to the trash.\x1b[0m");
                 }
             } else {
                 println!("Deletion cancelled.");
             }
         }
+        crate::cli::ProfileAction::Restore { name } => {
+            if config.trash.is_empty() {
+                println!("\x1b[1;33m🗑️  The trash is empty.\x1b[0m");
+                return Ok(());
+            }
+            let idx = match name {
+                Some(n) => config
+                    .trash
+                    .iter()
+                    .position(|t| t.profile.is_named(&n))
+                    .ok_or_else(|| anyhow::anyhow!("No deleted profile named '{}'", n))?,
+                None => {
+                    let items: Vec<String> = config
+                        .trash
+                        .iter()
+                        .map(|t| format!("{} <{}> ({} days left)", t.profile.profile_name, t.profile.email, t.days_left()))
+                        .collect();
+                    Select::with_theme(&ColorfulTheme::default())
+                        .with_prompt("♻️  Select profile to restore")
+                        .items(&items)
+                        .interact()?
+                }
+            };
+            let profile_name = config.trash[idx].profile.profile_name.clone();
+            if config.find_profile(&profile_name).is_some() {
+                anyhow::bail!("A profile named '{}' exists again; rename or delete it first", profile_name);
+            }
+            let restored = config.trash.remove(idx).profile;
+            config.profiles.push(restored);
+            save_config(&config)?;
+            crate::signing::sync_allowed_signers(&config)?;
+            crate::ssh::sync_profile_configs(&config)?;
+            println!("\x1b[1;32m✓ Profile '{}' restored.\x1b[0m", profile_name);
+        }
         crate::cli::ProfileAction::Edit { name } => {
             let profile_name = if let Some(n) = name {
                 n
@@ -275,15 +1008,17 @@ pub fn handle_profile_command(action: crate::cli::ProfileAction) -> Result<()> {
                 selections[selection].clone()
             };
 
-            if let Some(idx) = config.profiles.iter().position(|p| p.profile_name == profile_name) {
+            if let Some(idx) = config.profiles.iter().position(|p| p.is_named(&profile_name)) {
                 let p = &mut config.profiles[idx];
 
                 println!("\x1b[1;36m✏️  Editing profile: {}\x1b[0m\n", p.profile_name);
 
-                p.profile_name = Input::with_theme(&ColorfulTheme::default())
+                p.profile_name = Input::<String>::with_theme(&ColorfulTheme::default())
                     .with_prompt("Profile Name")
                     .default(p.profile_name.clone())
-                    .interact_text()?;
+                    .interact_text()?
+                    .trim()
+                    .to_string();
 
                 p.name = Input::with_theme(&ColorfulTheme::default())
                     .with_prompt("Git User Name")
@@ -301,6 +1036,9 @@ pub fn handle_profile_command(action: crate::cli::ProfileAction) -> Result<()> {
                         }
                     })
                     .interact_text()?;
+                p.alternate_emails = ask_alternate_emails(&p.alternate_emails)?;
+                p.hosts = ask_hosts(&p.hosts)?;
+                p.private_email = ask_private_email(p.private_email)?;
 
                 if Confirm::with_theme(&ColorfulTheme::default())
                     .with_prompt("Update authentication settings?")
@@ -316,16 +1054,61 @@ pub fn handle_profile_command(action: crate::cli::ProfileAction) -> Result<()> {
                         .unwrap_or(0);
 
                     if auth_selection == 0 {
-                        p.auth = create_ssh_auth(&p.email)?;
+                        let (auth, signing) = create_ssh_auth(&p.email)?;
+                        p.auth = auth;
+                        if signing.is_some() {
+                            p.signing = signing;
+                        }
+                        p.ssh.use_keychain = ask_use_keychain(p.ssh.use_keychain)?;
+                        p.ssh.multiplex = ask_multiplex(p.ssh.multiplex)?;
+                        (p.ssh.pin_host_keys, p.ssh.known_hosts) = ask_pin_host_keys(p.ssh.pin_host_keys, &p.ssh.known_hosts)?;
+                        p.ssh.host_key_checking = ask_host_key_checking(p.ssh.host_key_checking)?;
+                        p.ssh.program = ask_ssh_program(p.ssh.program.as_deref())?;
                     } else {
                         p.auth = create_token_auth()?;
                     }
                 }
 
+                if Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Update commit signing settings?")
+                    .default(false)
+                    .interact()?
+                {
+                    p.signing = ask_signing(p.signing.as_ref())?;
+                }
+
+                p.trailers = ask_trailers(&p.trailers)?;
+                p.repo_defaults = ask_repo_defaults(&p.repo_defaults)?;
+                p.git_config = ask_git_config(&p.git_config)?;
+                p.clone_args = ask_clone_args(&p.clone_args)?;
+                p.env = ask_env(&p.env)?;
+                if Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Update HTTP user agent and extra headers?")
+                    .default(false)
+                    .interact()?
+                {
+                    p.http = ask_http(&p.http)?;
+                }
+                if Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Update send-email (SMTP) settings?")
+                    .default(false)
+                    .interact()?
+                {
+                    p.sendemail = crate::sendemail::ask_sendemail(p.sendemail.as_ref(), &p.name, &p.email)?;
+                }
+                p.protected_branches = ask_protected_branches(&p.protected_branches)?;
+
                 // Validate before saving
                 p.validate()?;
+                p.check_key_permissions()?;
+                let p = &config.profiles[idx];
+                if config.profiles.iter().enumerate().any(|(i, other)| i != idx && other.is_named(&p.profile_name)) {
+                    anyhow::bail!("A profile with name '{}' already exists", p.profile_name);
+                }
 
                 save_config(&config)?;
+                crate::signing::sync_allowed_signers(&config)?;
+                crate::ssh::sync_profile_configs(&config)?;
                 println!("\n\x1b[1;32m✓ Profile updated.\x1b[0m");
             } else {
                 println!("\x1b[1;31m✗ Profile not found.\x1b[0m");
@@ -336,9 +1119,20 @@ pub fn handle_profile_command(action: crate::cli::ProfileAction) -> Result<()> {
 }
 
 /// Handle the 'gix set' command to configure global default profile
-pub fn handle_set_command(name: Option<String>) -> Result<()> {
+pub fn handle_set_command(name: Option<String>, clear: bool) -> Result<()> {
     let mut config = load_config()?;
 
+    if clear {
+        match config.default_profile.take() {
+            Some(previous) => {
+                save_config(&config)?;
+                println!("\x1b[1;32m✓ Global default profile cleared (was: {}).\x1b[0m", previous);
+            }
+            None => println!("No global default profile is set."),
+        }
+        return Ok(());
+    }
+
     if config.profiles.is_empty() {
         println!("\x1b[1;33m⚠ No profiles configured. Run 'gix profile add' first.\x1b[0m");
         return Ok(());
@@ -346,14 +1140,19 @@ pub fn handle_set_command(name: Option<String>) -> Result<()> {
 
     if let Some(n) = name {
         // Find profile by name
-        if !config.profiles.iter().any(|p| p.profile_name == n) {
-            anyhow::bail!("Profile '{}' not found", n);
-        }
-        
+        let n = config
+            .find_profile(&n)
+            .map(|p| p.profile_name.clone())
+            .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", n))?;
+
         config.default_profile = Some(n.clone());
         save_config(&config)?;
         println!("\x1b[1;32m✓ Global default profile set to: {}\x1b[0m", n);
     } else {
+        if !crate::settings::interactive() {
+            anyhow::bail!("Give the profile to use by default, or --clear to remove the default");
+        }
+
         // Interactive selection
         let mut selections: Vec<String> = config.profiles.iter()
             .map(|p| format!("{} ({} <{}>)", p.profile_name, p.name, p.email))
@@ -364,13 +1163,15 @@ pub fn handle_set_command(name: Option<String>) -> Result<()> {
         
         // Determine current default index
         let default_idx = if let Some(def) = &config.default_profile {
-            config.profiles.iter().position(|p| &p.profile_name == def).unwrap_or(0)
+            config.profiles.iter().position(|p| p.is_named(def)).unwrap_or(0)
         } else {
             0
         };
 
         println!("\x1b[1;36m🌍 Select Global Default Profile\x1b[0m\n");
         println!("This profile will be used for repositories that don't have a specific gix profile configured.\n");
+        print_default_profile(&config);
+        println!();
 
         let selection = Select::with_theme(&ColorfulTheme::default())
             .with_prompt("Select default profile")
@@ -394,8 +1195,23 @@ pub fn handle_set_command(name: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Print the global default profile line, warning when it names a missing profile
+pub fn print_default_profile(config: &Config) {
+    match (&config.default_profile, config.missing_default_profile()) {
+        (_, Some(missing)) => {
+            println!("   \x1b[1;33m⚠ Default profile '{}' no longer exists.\x1b[0m", missing);
+            println!("     Pick another with 'gix profile set-default <name>' or clear it with 'gix profile set-default --clear'.");
+        }
+        (Some(name), None) => println!("   🌍 Default profile: \x1b[1;36m{}\x1b[0m", name),
+        (None, None) => println!("   🌍 Default profile: none ('gix profile set-default' to choose one)"),
+    }
+}
+
 /// Create SSH authentication configuration
-fn create_ssh_auth(email: &str) -> Result<AuthMethod> {
+///
+/// Returns the auth method and, when the user opted to generate one alongside
+/// a new auth key, the signing configuration.
+fn create_ssh_auth(email: &str) -> Result<(AuthMethod, Option<SigningConfig>)> {
     let mut keys = list_ssh_keys();
     keys.push("🆕 Create new SSH key".to_string());
     keys.push("📁 Custom path...".to_string());
@@ -407,6 +1223,8 @@ fn create_ssh_auth(email: &str) -> Result<AuthMethod> {
         .interact()
         .unwrap_or(0);
 
+    let mut signing = None;
+
     let ssh_key = if selection == keys.len() - 2 {
         // Create New SSH Key
         let key_name: String = Input::with_theme(&ColorfulTheme::default())
@@ -418,32 +1236,44 @@ fn create_ssh_auth(email: &str) -> Result<AuthMethod> {
             .allow_empty_password(true)
             .interact()?;
 
-        let home = BaseDirs::new()
-            .context("Could not determine home directory")?
-            .home_dir()
-            .to_path_buf();
-        let key_path = home.join(".ssh").join(&key_name);
-        let key_path_str = key_path.to_string_lossy().to_string();
+        let key_path_str = generate_ssh_key(&key_name, email, &passphrase)?;
 
-        // Ensure .ssh directory exists
-        fs::create_dir_all(home.join(".ssh"))?;
+        println!("\n\x1b[1;32m✓ SSH key generated at: {}\x1b[0m", key_path_str);
+        let target = crate::provider::ask_provider_host()?;
+        crate::provider::print_key_instructions(
+            &format!("{}.pub", key_path_str),
+            email,
+            KeyUse::Authentication,
+            target.as_deref(),
+        )?;
 
-        let mut cmd = Command::new("ssh-keygen");
-        cmd.args(["-t", "ed25519", "-f", &key_path_str, "-C", email]);
-        if passphrase.is_empty() {
-            cmd.args(["-N", ""]);
-        } else {
-            cmd.args(["-N", &passphrase]);
-        }
+        if Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Also generate a separate SSH signing key for this identity?")
+            .default(false)
+            .interact()?
+        {
+            let signing_name: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Signing Key Name")
+                .default(format!("{}_signing", key_name))
+                .interact_text()?;
 
-        let status = cmd.status().context("Failed to generate SSH key")?;
-        if !status.success() {
-            anyhow::bail!("ssh-keygen failed");
-        }
+            let signing_path = generate_ssh_key(&signing_name, &format!("{} (signing)", email), &passphrase)?;
 
-        println!("\n\x1b[1;32m✓ SSH key generated at: {}\x1b[0m", key_path_str);
-        println!("\n\x1b[1;36m📋 Add this public key to your Git provider:\x1b[0m\n");
-        println!("{}", fs::read_to_string(format!("{}.pub", key_path_str))?);
+            println!("\n\x1b[1;32m✓ Signing key generated at: {}\x1b[0m", signing_path);
+            crate::provider::print_key_instructions(
+                &format!("{}.pub", signing_path),
+                email,
+                KeyUse::Signing,
+                target.as_deref(),
+            )?;
+
+            signing = Some(SigningConfig {
+                key_path: signing_path,
+                sign_commits: true,
+                format: SigningFormat::Ssh,
+                smartcard: false,
+            });
+        }
 
         key_path_str
     } else if selection == keys.len() - 1 {
@@ -454,18 +1284,479 @@ fn create_ssh_auth(email: &str) -> Result<AuthMethod> {
         keys[selection].clone()
     };
 
-    Ok(AuthMethod::SSH { key_path: ssh_key })
+    let mut host_keys = BTreeMap::new();
+    let per_host = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Use different SSH keys for some hosts (e.g. GitHub and a university GitLab)?")
+        .default(false)
+        .interact()?;
+    if per_host {
+        loop {
+            let host: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Host (empty to finish)")
+                .allow_empty(true)
+                .interact_text()?;
+            let host = host.trim().to_lowercase();
+            if host.is_empty() {
+                break;
+            }
+            let host_key: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("SSH key path for {}", host))
+                .interact_text()?;
+            if !host_key.trim().is_empty() {
+                host_keys.insert(host, host_key.trim().to_string());
+            }
+        }
+    }
+
+    Ok((AuthMethod::SSH { key_path: ssh_key, host_keys }, signing))
+}
+
+/// Generate an ed25519 key pair in ~/.ssh and return the private key path
+fn generate_ssh_key(key_name: &str, comment: &str, passphrase: &str) -> Result<String> {
+    let home = BaseDirs::new()
+        .context("Could not determine home directory")?
+        .home_dir()
+        .to_path_buf();
+    let key_path = home.join(".ssh").join(key_name);
+    let key_path_str = key_path.to_string_lossy().to_string();
+
+    // Ensure .ssh directory exists
+    fs::create_dir_all(home.join(".ssh"))?;
+
+    let mut cmd = Command::new("ssh-keygen");
+    cmd.args(["-t", "ed25519", "-f", &key_path_str, "-C", comment]);
+    if passphrase.is_empty() {
+        cmd.args(["-N", ""]);
+    } else {
+        cmd.args(["-N", passphrase]);
+    }
+
+    let status = cmd.status().context("Failed to generate SSH key")?;
+    if !status.success() {
+        anyhow::bail!("ssh-keygen failed");
+    }
+
+    Ok(key_path_str)
+}
+
+/// Ask whether ssh should cache the key passphrase in the macOS Keychain
+fn ask_use_keychain(current: bool) -> Result<bool> {
+    if !cfg!(target_os = "macos") {
+        return Ok(current);
+    }
+
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Add key to ssh-agent and macOS Keychain (no passphrase prompt on every push)?")
+        .default(true)
+        .interact()?)
+}
+
+/// Ask whether ssh connections of the profile should be shared between git commands
+fn ask_multiplex(current: bool) -> Result<bool> {
+    if !cfg!(unix) {
+        return Ok(current);
+    }
+
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Keep SSH connections open between commands (faster repeated fetch/push)?")
+        .default(current)
+        .interact()?)
+}
+
+/// Ask whether to pre-trust provider host keys, plus custom host entries
+fn ask_pin_host_keys(current: bool, entries: &[String]) -> Result<(bool, Vec<String>)> {
+    let pin = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Trust the published host keys of GitHub/GitLab/Bitbucket/Codeberg (no host-key prompts in scripts)?")
+        .default(current)
+        .interact()?;
+    if !pin {
+        return Ok((false, vec![]));
+    }
+
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Extra known_hosts entries for custom hosts, ';' separated (\"host key-type key\", empty for none)")
+        .default(entries.join("; "))
+        .allow_empty(true)
+        .interact_text()?;
+    let entries: Vec<String> = input
+        .split(';')
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect();
+    for entry in &entries {
+        crate::known_hosts::validate_entry(entry)?;
+    }
+    Ok((true, entries))
+}
+
+/// Ask how strictly ssh should check host keys for this identity
+fn ask_host_key_checking(current: Option<HostKeyChecking>) -> Result<Option<HostKeyChecking>> {
+    let options = [
+        None,
+        Some(HostKeyChecking::Yes),
+        Some(HostKeyChecking::AcceptNew),
+        Some(HostKeyChecking::No),
+    ];
+    let items = [
+        "Default (from ~/.ssh/config)",
+        "Strict: refuse unknown hosts",
+        "Accept new hosts, refuse changed keys",
+        "Off: accept any host key (insecure)",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("SSH host key checking")
+        .items(&items)
+        .default(options.iter().position(|o| *o == current).unwrap_or(0))
+        .interact()?;
+    Ok(options[selection])
+}
+
+/// Ask for a custom ssh executable (wrapper script, ssh.exe...) for the profile
+fn ask_ssh_program(current: Option<&str>) -> Result<Option<String>> {
+    let program: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("ssh executable for this profile (empty for the global setting)")
+        .default(current.unwrap_or_default().to_string())
+        .allow_empty(true)
+        .interact_text()?;
+    let program = program.trim();
+    Ok((!program.is_empty()).then(|| program.to_string()))
+}
+
+/// Ask whether commits made with the profile should carry a DCO sign-off
+fn ask_trailers(current: &[String]) -> Result<Vec<String>> {
+    let has_signoff = current.iter().any(|t| t == "Signed-off-by");
+
+    let signoff = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Add 'Signed-off-by' (DCO) to commits made with this profile?")
+        .default(has_signoff)
+        .interact()?;
+
+    let mut trailers: Vec<String> = current.iter().filter(|t| *t != "Signed-off-by").cloned().collect();
+    if signoff {
+        trailers.insert(0, "Signed-off-by".to_string());
+    }
+    Ok(trailers)
+}
+
+/// Ask for repository defaults applied with the profile
+fn ask_repo_defaults(current: &RepoDefaults) -> Result<RepoDefaults> {
+    if !Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Configure repository defaults (default branch, pull.rebase, fetch.prune, push.autoSetupRemote, submodules)?")
+        .default(false)
+        .interact()?
+    {
+        return Ok(current.clone());
+    }
+
+    let branch: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Default branch name (empty to leave unset)")
+        .default(current.default_branch.clone().unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()?;
+
+    let ask_flag = |prompt: &str, value: Option<bool>| -> Result<Option<bool>> {
+        let options = ["Leave unset", "true", "false"];
+        let default = match value {
+            None => 0,
+            Some(true) => 1,
+            Some(false) => 2,
+        };
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .items(&options)
+            .default(default)
+            .interact()?;
+        Ok(match selection {
+            1 => Some(true),
+            2 => Some(false),
+            _ => None,
+        })
+    };
+
+    Ok(RepoDefaults {
+        default_branch: Some(branch.trim().to_string()).filter(|b| !b.is_empty()),
+        pull_rebase: ask_flag("pull.rebase", current.pull_rebase)?,
+        fetch_prune: ask_flag("fetch.prune", current.fetch_prune)?,
+        push_auto_setup_remote: ask_flag("push.autoSetupRemote", current.push_auto_setup_remote)?,
+        rewrite_submodules: Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Rewrite submodule URLs to this profile's protocol (SSH or HTTPS)?")
+            .default(current.rewrite_submodules)
+            .interact()?,
+    })
+}
+
+/// Ask for other addresses that identify the profile
+fn ask_alternate_emails(current: &[String]) -> Result<Vec<String>> {
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Alternate emails for detection (comma separated, e.g. noreply address; empty for none)")
+        .default(current.join(", "))
+        .allow_empty(true)
+        .interact_text()?;
+
+    Ok(input
+        .split(',')
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect())
+}
+
+/// Ask for the remote host patterns that select the profile
+fn ask_hosts(current: &[String]) -> Result<Vec<String>> {
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Remote hosts for detection (comma separated, e.g. github.com:acme/*, gitlab.acme.com; empty for none)")
+        .default(current.join(", "))
+        .allow_empty(true)
+        .interact_text()?;
+
+    Ok(input
+        .split(',')
+        .map(|h| h.trim().to_ascii_lowercase())
+        .filter(|h| !h.is_empty())
+        .collect())
+}
+
+/// Ask whether the primary email must stay off public hosts
+fn ask_private_email(current: bool) -> Result<bool> {
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Keep this email private (block pushes to GitHub/GitLab/... that would publish it)?")
+        .default(current)
+        .interact()?)
+}
+
+/// Ask for flags added to clones made with the profile
+fn ask_clone_args(current: &[String]) -> Result<Vec<String>> {
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Default clone flags (e.g. --filter=blob:none --recurse-submodules, empty for none)")
+        .default(current.join(" "))
+        .allow_empty(true)
+        .interact_text()?;
+
+    Ok(input.split_whitespace().map(|a| a.to_string()).collect())
+}
+
+/// Split `key=value, key=value` input into pairs
+///
+/// A comma only starts a new pair when a key and `=` follow it, so values may contain
+/// commas (`diff.wordRegex=[^,]+`).
+fn split_pairs(input: &str, is_key: impl Fn(&str) -> bool) -> Result<Vec<(String, String)>, String> {
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    for piece in input.split(',') {
+        match piece.split_once('=').filter(|(k, _)| is_key(k.trim())) {
+            Some((key, value)) => pairs.push((key.trim().to_string(), value.trim().to_string())),
+            None => match pairs.last_mut() {
+                Some((_, value)) => {
+                    value.push(',');
+                    value.push_str(piece.trim_end());
+                }
+                None if piece.trim().is_empty() => {}
+                None => return Err(format!("'{}' is not key=value", piece.trim())),
+            },
+        }
+    }
+    Ok(pairs)
+}
+
+fn is_git_config_key(key: &str) -> bool {
+    key.contains('.') && !key.starts_with('.') && !key.ends_with('.') && !key.contains(char::is_whitespace)
+}
+
+/// Ask for extra git config applied with the profile
+fn ask_git_config(current: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>> {
+    let shown: Vec<String> = current.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Extra git config (e.g. commit.verbose=true, pull.ff=only, comma separated, empty for none)")
+        .default(shown.join(", "))
+        .allow_empty(true)
+        .validate_with(|input: &String| -> Result<(), String> {
+            let pairs = split_pairs(input, is_git_config_key)?;
+            match pairs.iter().find(|(k, _)| is_unsafe_git_config(k)) {
+                Some((key, _)) => Err(format!("'{}' can run commands or redirect git; set it in your own git config", key)),
+                None => Ok(()),
+            }
+        })
+        .interact_text()?;
+
+    Ok(split_pairs(&input, is_git_config_key).map_err(|e| anyhow::anyhow!(e))?.into_iter().collect())
+}
+
+/// Parse the environment prompt: `NAME=value` sets a variable, a bare `NAME` keeps its
+/// current value, and names left out are removed
+///
+/// Values may contain commas: a piece that is neither continues the last value.
+fn parse_env_input(input: &str, current: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>, String> {
+    let mut env: BTreeMap<String, String> = BTreeMap::new();
+    let mut last: Option<String> = None;
+    for piece in input.split(',') {
+        let trimmed = piece.trim();
+        if let Some(value) = current.get(trimmed) {
+            env.insert(trimmed.to_string(), value.clone());
+            last = None;
+        } else if let Some((name, value)) = piece.split_once('=').filter(|(n, _)| is_env_var_name(n.trim())) {
+            env.insert(name.trim().to_string(), value.trim().to_string());
+            last = Some(name.trim().to_string());
+        } else if let Some(value) = last.as_ref().and_then(|name| env.get_mut(name)) {
+            value.push(',');
+            value.push_str(piece.trim_end());
+        } else if is_env_var_name(trimmed) {
+            return Err(format!("'{}' needs a value: {}=...", trimmed, trimmed));
+        } else if !trimmed.is_empty() {
+            return Err(format!("'{}' is not NAME=value", trimmed));
+        }
+    }
+    Ok(env)
+}
+
+/// Ask for environment variables exported with the profile
+///
+/// Values can be secrets, so only the names are shown: they keep their values unless
+/// given a new one.
+fn ask_env(current: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>> {
+    let names: Vec<&str> = current.keys().map(|k| k.as_str()).collect();
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Environment variables (NAME=value to set, NAME alone keeps its value, e.g. NPM_TOKEN=env:WORK_NPM_TOKEN; comma separated, empty for none)")
+        .default(names.join(", "))
+        .allow_empty(true)
+        .validate_with(|input: &String| parse_env_input(input, current).map(|_| ()))
+        .interact_text()?;
+
+    parse_env_input(&input, current).map_err(|e| anyhow::anyhow!(e))
+}
+
+/// Ask for the HTTP user agent and extra headers of HTTPS operations
+fn ask_http(current: &HttpOptions) -> Result<HttpOptions> {
+    let agent: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("HTTP user agent (empty for git's own)")
+        .default(current.user_agent.clone().unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()?;
+
+    let headers: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Extra HTTP headers (e.g. X-Corp-Auth: env:CORP_TOKEN, separated by ';', empty for none)")
+        .default(current.extra_headers.join("; "))
+        .allow_empty(true)
+        .validate_with(|input: &String| split_headers(input).map(|_| ()))
+        .interact_text()?;
+
+    Ok(HttpOptions {
+        user_agent: Some(agent.trim().to_string()).filter(|a| !a.is_empty()),
+        extra_headers: split_headers(&headers).map_err(|e| anyhow::anyhow!(e))?,
+    })
+}
+
+/// Split `Name: value; Name: value` input into header lines
+///
+/// A ';' only starts a new header when a name and ':' follow it, so values may contain
+/// semicolons (`Cookie: a=1; b=2`).
+fn split_headers(input: &str) -> Result<Vec<String>, String> {
+    let mut headers: Vec<String> = Vec::new();
+    for piece in input.split(';') {
+        let is_header = piece
+            .split_once(':')
+            .map(|(name, _)| name.trim())
+            .is_some_and(|name| !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || c == '='));
+        match headers.last_mut() {
+            _ if is_header => headers.push(piece.trim().to_string()),
+            Some(header) => {
+                header.push(';');
+                header.push_str(piece.trim_end());
+            }
+            None if piece.trim().is_empty() => {}
+            None => return Err(format!("'{}' is not 'Name: value'", piece.trim())),
+        }
+    }
+    Ok(headers)
+}
+
+/// Ask for branch patterns whose pushes need confirmation
+fn ask_protected_branches(current: &[String]) -> Result<Vec<String>> {
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Branches that need confirmation before push (e.g. main, release/*; empty for none)")
+        .default(current.join(", "))
+        .allow_empty(true)
+        .interact_text()?;
+
+    Ok(input
+        .split(',')
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty())
+        .collect())
+}
+
+/// Ask how the profile signs commits: SSH key, OpenPGP key (optionally on a smartcard), or not at all
+fn ask_signing(current: Option<&SigningConfig>) -> Result<Option<SigningConfig>> {
+    let options = vec!["🔐 SSH key", "🔏 GPG key", "💳 GPG key on a smartcard (YubiKey, Nitrokey...)", "🚫 Don't sign"];
+    let default = match current {
+        Some(s) if s.format == SigningFormat::Ssh => 0,
+        Some(s) if s.smartcard => 2,
+        Some(_) => 1,
+        None => 3,
+    };
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Commit signing")
+        .items(&options)
+        .default(default)
+        .interact()?;
+    if selection == 3 {
+        return Ok(None);
+    }
+
+    let format = if selection == 0 { SigningFormat::Ssh } else { SigningFormat::Openpgp };
+    let prompt = if format == SigningFormat::Ssh { "SSH signing key path" } else { "GPG key ID or fingerprint" };
+    let theme = ColorfulTheme::default();
+    let mut input = Input::<String>::with_theme(&theme).with_prompt(prompt);
+    if let Some(s) = current.filter(|s| s.format == format) {
+        input = input.default(s.key_path.clone());
+    }
+    let key_path = input.interact_text()?.trim().to_string();
+
+    let smartcard = selection == 2;
+    if smartcard {
+        if let crate::signing::CardStatus::Absent = crate::signing::card_status() {
+            println!("\x1b[1;33m⚠ No smartcard detected right now; insert it before committing.\x1b[0m");
+        }
+    }
+
+    Ok(Some(SigningConfig {
+        key_path,
+        sign_commits: current.map(|s| s.sign_commits).unwrap_or(true),
+        format,
+        smartcard,
+    }))
 }
 
 /// Create token authentication configuration
 fn create_token_auth() -> Result<AuthMethod> {
     let token: String = Password::with_theme(&ColorfulTheme::default())
-        .with_prompt("Personal Access Token")
+        .with_prompt("Personal Access Token (or env:VAR_NAME to read it from the environment)")
         .interact()?;
 
     if token.is_empty() {
         anyhow::bail!("Token cannot be empty");
     }
 
-    Ok(AuthMethod::Token { token })
+    let mut host_tokens = BTreeMap::new();
+    let per_host = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Use different tokens for some hosts (e.g. GitHub and a self-hosted GitLab)?")
+        .default(false)
+        .interact()?;
+    if per_host {
+        loop {
+            let host: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Host (empty to finish)")
+                .allow_empty(true)
+                .interact_text()?;
+            let host = host.trim().to_lowercase();
+            if host.is_empty() {
+                break;
+            }
+            let host_token: String = Password::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Token for {} (or env:VAR_NAME)", host))
+                .interact()?;
+            if !host_token.is_empty() {
+                host_tokens.insert(host, host_token);
+            }
+        }
+    }
+
+    Ok(AuthMethod::Token { token, host_tokens })
 }
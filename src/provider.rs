@@ -0,0 +1,756 @@
+use anyhow::{Context, Result};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Password, Select};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::load_config;
+use crate::profile::{resolve_token, AuthMethod, Profile};
+use crate::secrets::SecretString;
+
+/// What a public key is uploaded for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyUse {
+    Authentication,
+    Signing,
+}
+
+/// The account behind a token or key
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub login: String,
+    /// Numeric account id, where the provider has one
+    pub id: Option<u64>,
+}
+
+/// A repository created through a provider's API
+#[derive(Debug, Clone)]
+pub struct NewRepo {
+    pub https_url: String,
+    pub ssh_url: String,
+    pub web_url: String,
+}
+
+/// A git hosting service: its web UI, API and the conventions of its git endpoints
+pub trait Provider {
+    fn name(&self) -> &'static str;
+
+    fn host(&self) -> &str;
+
+    /// Settings page where a new SSH key is added
+    fn key_settings_url(&self) -> String;
+
+    /// Which option of the form selects the key's purpose
+    fn key_type_hint(&self, key_use: KeyUse) -> &'static str;
+
+    /// User name stored with a token in git's credentials (`fallback`: the profile's name)
+    fn token_username(&self, fallback: &str) -> String {
+        fallback.to_string()
+    }
+
+    /// Account named in the host's reply to `ssh -T`, empty when the host accepts the
+    /// key without naming it; `None` when the reply is not a successful login
+    fn ssh_account(&self, reply: &str) -> Option<String>;
+
+    /// Whether API calls need the account's user name next to the token
+    fn api_needs_user(&self) -> bool {
+        false
+    }
+
+    /// Check a token (used as `user` where the API needs one) and return its account
+    fn validate_token(&self, user: &str, token: &str) -> Result<Account>;
+
+    fn upload_key(&self, user: &str, token: &str, title: &str, public_key: &str, key_use: KeyUse) -> Result<()>;
+
+    /// Address the host uses in place of the account's real email, if it has one
+    fn noreply_email(&self, account: &Account) -> Option<String>;
+
+    /// Create a repository owned by the token's account
+    fn create_repo(&self, user: &str, token: &str, name: &str, private: bool) -> Result<NewRepo>;
+}
+
+/// Guess the provider from a host name, or from the host an ssh_config alias points to
+pub fn for_host(host: &str) -> Option<Box<dyn Provider>> {
+    guess_provider(host).or_else(|| crate::ssh::resolve_host_alias(host).and_then(|real| guess_provider(&real)))
+}
+
+fn guess_provider(host: &str) -> Option<Box<dyn Provider>> {
+    let host = host.to_ascii_lowercase();
+    let owned = host.clone();
+    match host.as_str() {
+        "github.com" => Some(Box::new(GitHub { host: owned })),
+        "gitlab.com" => Some(Box::new(GitLab { host: owned })),
+        "bitbucket.org" => Some(Box::new(Bitbucket)),
+        "codeberg.org" => Some(Box::new(Gitea { host: owned })),
+        _ if host.contains("gitlab") => Some(Box::new(GitLab { host: owned })),
+        _ if host.contains("gitea") || host.contains("forgejo") => Some(Box::new(Gitea { host: owned })),
+        _ if host.contains("github") => Some(Box::new(GitHub { host: owned })),
+        _ => None,
+    }
+}
+
+/// Call a provider API with curl
+///
+/// The request goes to curl as a config file on stdin, so the token never shows up in
+/// the process list.
+fn api_request(method: &str, url: &str, auth_header: &str, body: Option<&Value>) -> Result<Value> {
+    curl_request(method, url, &format!("header = {}", quote(auth_header)), body)
+}
+
+/// `api_request` with HTTP Basic authentication (Bitbucket app passwords)
+fn basic_api_request(method: &str, url: &str, user: &str, password: &str, body: Option<&Value>) -> Result<Value> {
+    curl_request(method, url, &format!("user = {}", quote(&format!("{}:{}", user, password))), body)
+}
+
+/// Quote a value for a curl config file
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn curl_request(method: &str, url: &str, auth: &str, body: Option<&Value>) -> Result<Value> {
+    let mut config = format!(
+        "url = {}\nrequest = {}\n{}\nheader = \"Accept: application/json\"\nuser-agent = \"gix-cli\"\nsilent\nshow-error\nwrite-out = \"\\n%{{http_code}}\"\n",
+        quote(url),
+        quote(method),
+        auth
+    );
+    if let Some(body) = body {
+        config.push_str("header = \"Content-Type: application/json\"\n");
+        config.push_str(&format!("data = {}\n", quote(&body.to_string())));
+    }
+
+    // Holds the token: zeroed once curl has read it
+    let config = SecretString::new(config);
+    let mut child = Command::new("curl")
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl. Make sure curl is installed.")?;
+    child.stdin.take().context("Failed to talk to curl")?.write_all(config.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim().trim_start_matches("curl: "));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (body, code) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+    let code: u16 = code.trim().parse().unwrap_or(0);
+    let value: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+    if !(200..300).contains(&code) {
+        let message = value["message"]
+            .as_str()
+            .or_else(|| value["error"]["message"].as_str())
+            .or_else(|| value["error"].as_str())
+            .map(String::from)
+            .unwrap_or_else(|| format!("HTTP {}", code));
+        anyhow::bail!("{} API: {}", url.split('/').nth(2).unwrap_or(url), message);
+    }
+    Ok(value)
+}
+
+fn string_field(value: &Value, field: &str) -> Result<String> {
+    value[field]
+        .as_str()
+        .map(String::from)
+        .with_context(|| format!("Unexpected API response (no '{}')", field))
+}
+
+/// github.com and GitHub Enterprise Server
+pub struct GitHub {
+    host: String,
+}
+
+impl GitHub {
+    fn api(&self, path: &str) -> String {
+        if self.host == "github.com" {
+            format!("https://api.github.com{}", path)
+        } else {
+            format!("https://{}/api/v3{}", self.host, path)
+        }
+    }
+}
+
+impl Provider for GitHub {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn key_settings_url(&self) -> String {
+        format!("https://{}/settings/ssh/new", self.host)
+    }
+
+    fn key_type_hint(&self, key_use: KeyUse) -> &'static str {
+        match key_use {
+            KeyUse::Authentication => "Key type: Authentication Key",
+            KeyUse::Signing => "Key type: Signing Key",
+        }
+    }
+
+    /// GitHub ignores the user name of token logins; this one names what the password is
+    fn token_username(&self, _fallback: &str) -> String {
+        "x-access-token".to_string()
+    }
+
+    fn ssh_account(&self, reply: &str) -> Option<String> {
+        // "Hi octocat! You've successfully authenticated, but GitHub does not provide shell access."
+        let rest = &reply[reply.find("Hi ")? + 3..];
+        rest.contains("successfully authenticated").then(|| rest[..rest.find('!').unwrap_or(0)].to_string())
+    }
+
+    fn validate_token(&self, _user: &str, token: &str) -> Result<Account> {
+        let user = api_request("GET", &self.api("/user"), &format!("Authorization: Bearer {}", token), None)?;
+        Ok(Account { login: string_field(&user, "login")?, id: user["id"].as_u64() })
+    }
+
+    fn upload_key(&self, _user: &str, token: &str, title: &str, public_key: &str, key_use: KeyUse) -> Result<()> {
+        let path = match key_use {
+            KeyUse::Authentication => "/user/keys",
+            KeyUse::Signing => "/user/ssh_signing_keys",
+        };
+        let body = json!({ "title": title, "key": public_key.trim() });
+        api_request("POST", &self.api(path), &format!("Authorization: Bearer {}", token), Some(&body))?;
+        Ok(())
+    }
+
+    fn noreply_email(&self, account: &Account) -> Option<String> {
+        let id = account.id?;
+        Some(format!("{}+{}@users.noreply.{}", id, account.login, self.host))
+    }
+
+    fn create_repo(&self, _user: &str, token: &str, name: &str, private: bool) -> Result<NewRepo> {
+        let body = json!({ "name": name, "private": private });
+        let repo = api_request("POST", &self.api("/user/repos"), &format!("Authorization: Bearer {}", token), Some(&body))?;
+        Ok(NewRepo {
+            https_url: string_field(&repo, "clone_url")?,
+            ssh_url: string_field(&repo, "ssh_url")?,
+            web_url: string_field(&repo, "html_url")?,
+        })
+    }
+}
+
+/// gitlab.com and self-hosted GitLab
+pub struct GitLab {
+    host: String,
+}
+
+impl GitLab {
+    fn api(&self, path: &str) -> String {
+        format!("https://{}/api/v4{}", self.host, path)
+    }
+}
+
+impl Provider for GitLab {
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn key_settings_url(&self) -> String {
+        format!("https://{}/-/user_settings/ssh_keys", self.host)
+    }
+
+    fn key_type_hint(&self, key_use: KeyUse) -> &'static str {
+        match key_use {
+            KeyUse::Authentication => "Usage type: Authentication",
+            KeyUse::Signing => "Usage type: Signing",
+        }
+    }
+
+    /// GitLab accepts personal access tokens under the "oauth2" user name
+    fn token_username(&self, _fallback: &str) -> String {
+        "oauth2".to_string()
+    }
+
+    fn ssh_account(&self, reply: &str) -> Option<String> {
+        // "Welcome to GitLab, @octocat!"
+        let rest = &reply[reply.find("Welcome to GitLab, @")? + 20..];
+        Some(rest[..rest.find('!').unwrap_or(rest.len())].to_string())
+    }
+
+    fn validate_token(&self, _user: &str, token: &str) -> Result<Account> {
+        let user = api_request("GET", &self.api("/user"), &format!("PRIVATE-TOKEN: {}", token), None)?;
+        Ok(Account { login: string_field(&user, "username")?, id: user["id"].as_u64() })
+    }
+
+    fn upload_key(&self, _user: &str, token: &str, title: &str, public_key: &str, key_use: KeyUse) -> Result<()> {
+        let usage = match key_use {
+            KeyUse::Authentication => "auth",
+            KeyUse::Signing => "signing",
+        };
+        let body = json!({ "title": title, "key": public_key.trim(), "usage_type": usage });
+        api_request("POST", &self.api("/user/keys"), &format!("PRIVATE-TOKEN: {}", token), Some(&body))?;
+        Ok(())
+    }
+
+    fn noreply_email(&self, account: &Account) -> Option<String> {
+        let id = account.id?;
+        Some(format!("{}-{}@users.noreply.{}", id, account.login, self.host))
+    }
+
+    fn create_repo(&self, _user: &str, token: &str, name: &str, private: bool) -> Result<NewRepo> {
+        let visibility = if private { "private" } else { "public" };
+        let body = json!({ "name": name, "visibility": visibility });
+        let repo = api_request("POST", &self.api("/projects"), &format!("PRIVATE-TOKEN: {}", token), Some(&body))?;
+        Ok(NewRepo {
+            https_url: string_field(&repo, "http_url_to_repo")?,
+            ssh_url: string_field(&repo, "ssh_url_to_repo")?,
+            web_url: string_field(&repo, "web_url")?,
+        })
+    }
+}
+
+/// bitbucket.org (Bitbucket Data Center has a different API)
+pub struct Bitbucket;
+
+impl Bitbucket {
+    const API: &'static str = "https://api.bitbucket.org/2.0";
+
+    /// App passwords authenticate with HTTP Basic and the account's user name
+    fn request(&self, method: &str, path: &str, user: &str, token: &str, body: Option<&Value>) -> Result<Value> {
+        if user.is_empty() {
+            anyhow::bail!("Bitbucket needs the account's user name next to the app password");
+        }
+        basic_api_request(method, &format!("{}{}", Self::API, path), user, token, body)
+    }
+}
+
+impl Provider for Bitbucket {
+    fn name(&self) -> &'static str {
+        "Bitbucket"
+    }
+
+    fn host(&self) -> &str {
+        "bitbucket.org"
+    }
+
+    fn key_settings_url(&self) -> String {
+        "https://bitbucket.org/account/settings/ssh-keys/".to_string()
+    }
+
+    fn key_type_hint(&self, key_use: KeyUse) -> &'static str {
+        match key_use {
+            KeyUse::Authentication => "Add key",
+            KeyUse::Signing => "Add key (Bitbucket keeps one list for both uses)",
+        }
+    }
+
+    // App passwords need the account name, so the default user name stays
+
+    fn api_needs_user(&self) -> bool {
+        true
+    }
+
+    fn ssh_account(&self, reply: &str) -> Option<String> {
+        // "authenticated via ssh key." (older servers: "logged in as octocat.")
+        if let Some(start) = reply.find("logged in as ") {
+            let rest = &reply[start + 13..];
+            return Some(rest[..rest.find('.').unwrap_or(rest.len())].to_string());
+        }
+        reply.contains("authenticated via").then(String::new)
+    }
+
+    fn validate_token(&self, user: &str, token: &str) -> Result<Account> {
+        let account = self.request("GET", "/user", user, token, None)?;
+        Ok(Account { login: string_field(&account, "username")?, id: None })
+    }
+
+    fn upload_key(&self, user: &str, token: &str, title: &str, public_key: &str, _key_use: KeyUse) -> Result<()> {
+        let uuid = string_field(&self.request("GET", "/user", user, token, None)?, "uuid")?;
+        let body = json!({ "label": title, "key": public_key.trim() });
+        self.request("POST", &format!("/users/{}/ssh-keys", uuid), user, token, Some(&body))?;
+        Ok(())
+    }
+
+    fn noreply_email(&self, _account: &Account) -> Option<String> {
+        None
+    }
+
+    fn create_repo(&self, user: &str, token: &str, name: &str, private: bool) -> Result<NewRepo> {
+        let workspace = self.validate_token(user, token)?.login;
+        let slug = name.to_ascii_lowercase();
+        let body = json!({ "scm": "git", "is_private": private });
+        let repo = self.request("POST", &format!("/repositories/{}/{}", workspace, slug), user, token, Some(&body))?;
+        let clone = |kind: &str| {
+            repo["links"]["clone"]
+                .as_array()
+                .and_then(|links| links.iter().find(|l| l["name"] == kind))
+                .and_then(|l| l["href"].as_str())
+                .map(String::from)
+                .with_context(|| format!("Unexpected API response (no {} clone URL)", kind))
+        };
+        Ok(NewRepo {
+            https_url: clone("https")?,
+            ssh_url: clone("ssh")?,
+            web_url: repo["links"]["html"]["href"].as_str().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+/// Gitea and its fork Forgejo (e.g. codeberg.org)
+pub struct Gitea {
+    host: String,
+}
+
+impl Gitea {
+    fn api(&self, path: &str) -> String {
+        format!("https://{}/api/v1{}", self.host, path)
+    }
+}
+
+impl Provider for Gitea {
+    fn name(&self) -> &'static str {
+        "Gitea/Forgejo"
+    }
+
+    fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn key_settings_url(&self) -> String {
+        format!("https://{}/user/settings/keys", self.host)
+    }
+
+    fn key_type_hint(&self, key_use: KeyUse) -> &'static str {
+        match key_use {
+            KeyUse::Authentication => "Manage SSH Keys → Add Key",
+            KeyUse::Signing => "Manage SSH Keys → Add Key, then Verify it to mark commits as verified",
+        }
+    }
+
+    fn ssh_account(&self, reply: &str) -> Option<String> {
+        // "Hi there, octocat! You've successfully authenticated with the key named ..."
+        let rest = &reply[reply.find("Hi there, ")? + 10..];
+        Some(rest[..rest.find('!').unwrap_or(rest.len())].to_string())
+    }
+
+    fn validate_token(&self, _user: &str, token: &str) -> Result<Account> {
+        let user = api_request("GET", &self.api("/user"), &format!("Authorization: token {}", token), None)?;
+        Ok(Account { login: string_field(&user, "login")?, id: user["id"].as_u64() })
+    }
+
+    fn upload_key(&self, _user: &str, token: &str, title: &str, public_key: &str, _key_use: KeyUse) -> Result<()> {
+        // One list for both uses; a signing key is verified in the web UI afterwards
+        let body = json!({ "title": title, "key": public_key.trim() });
+        api_request("POST", &self.api("/user/keys"), &format!("Authorization: token {}", token), Some(&body))?;
+        Ok(())
+    }
+
+    fn noreply_email(&self, account: &Account) -> Option<String> {
+        Some(format!("{}@noreply.{}", account.login, self.host))
+    }
+
+    fn create_repo(&self, _user: &str, token: &str, name: &str, private: bool) -> Result<NewRepo> {
+        let body = json!({ "name": name, "private": private });
+        let repo = api_request("POST", &self.api("/user/repos"), &format!("Authorization: token {}", token), Some(&body))?;
+        Ok(NewRepo {
+            https_url: string_field(&repo, "clone_url")?,
+            ssh_url: string_field(&repo, "ssh_url")?,
+            web_url: string_field(&repo, "html_url")?,
+        })
+    }
+}
+
+/// Ask where a new key will be used
+///
+/// Returns `None` when the user skips, in which case only the key itself is shown.
+pub fn ask_provider_host() -> Result<Option<Box<dyn Provider>>> {
+    let options = [
+        "GitHub (github.com)",
+        "GitLab (gitlab.com)",
+        "Bitbucket (bitbucket.org)",
+        "Codeberg (codeberg.org)",
+        "Self-hosted GitLab / Gitea / Forgejo / GitHub Enterprise...",
+        "Skip",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Where will you use this key?")
+        .items(&options)
+        .default(0)
+        .interact()?;
+
+    let known = match selection {
+        0 => Some("github.com"),
+        1 => Some("gitlab.com"),
+        2 => Some("bitbucket.org"),
+        3 => Some("codeberg.org"),
+        _ => None,
+    };
+    if let Some(host) = known {
+        return Ok(for_host(host));
+    }
+    if selection == options.len() - 1 {
+        return Ok(None);
+    }
+
+    let host: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Host (e.g. gitlab.company.com)")
+        .interact_text()?;
+    let host = host.trim().trim_start_matches("https://").trim_end_matches('/').to_string();
+    if let Some(provider) = for_host(&host) {
+        return Ok(Some(provider));
+    }
+    let kinds = ["GitLab", "Gitea/Forgejo", "GitHub"];
+    let idx = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Which software runs on this host?")
+        .items(&kinds)
+        .default(0)
+        .interact()?;
+    Ok(Some(match idx {
+        0 => Box::new(GitLab { host }),
+        1 => Box::new(Gitea { host }),
+        _ => Box::new(GitHub { host }),
+    }))
+}
+
+/// Name of this machine, for key titles
+fn machine_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| {
+            std::process::Command::new("hostname")
+                .output()
+                .ok()
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        })
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "this machine".to_string())
+}
+
+fn key_title(email: &str, key_use: KeyUse) -> String {
+    let purpose = match key_use {
+        KeyUse::Authentication => "",
+        KeyUse::Signing => " (signing)",
+    };
+    format!("{} @ {}{}", email, machine_name(), purpose)
+}
+
+/// Print the steps to upload a freshly generated public key, or upload it through the
+/// provider's API when the user has a token at hand
+pub fn print_key_instructions(
+    public_key_path: &str,
+    email: &str,
+    key_use: KeyUse,
+    target: Option<&dyn Provider>,
+) -> Result<()> {
+    let public_key = fs::read_to_string(public_key_path)?;
+    let title = key_title(email, key_use);
+
+    match target {
+        Some(provider) => {
+            if crate::settings::interactive()
+                && Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("Upload it to {} now with an API token?", provider.host()))
+                    .default(false)
+                    .interact()?
+            {
+                let (user, token) = ask_api_token(provider)?;
+                match provider.upload_key(&user, &token, &title, &public_key, key_use) {
+                    Ok(()) => {
+                        println!("\x1b[1;32m✓ Key added to {} as '{}'\x1b[0m", provider.host(), title);
+                        return Ok(());
+                    }
+                    Err(e) => println!("\x1b[1;33m⚠ Upload failed: {}\x1b[0m", e),
+                }
+            }
+            println!("\n\x1b[1;36m📋 Add this key to {} ({}):\x1b[0m\n", provider.name(), provider.host());
+            println!("   1. Open \x1b[4m{}\x1b[0m", provider.key_settings_url());
+            println!("   2. Title: \x1b[1m{}\x1b[0m", title);
+            println!("   3. {}", provider.key_type_hint(key_use));
+            println!("   4. Paste the key below\n");
+        }
+        None => {
+            let kind = match key_use {
+                KeyUse::Authentication => "",
+                KeyUse::Signing => " as a *signing* key",
+            };
+            println!("\n\x1b[1;36m📋 Add this public key to your Git provider{} (title: {}):\x1b[0m\n", kind, title);
+        }
+    }
+    println!("{}", public_key);
+    Ok(())
+}
+
+/// User name and token for one API call; they are not stored
+fn ask_api_token(provider: &dyn Provider) -> Result<(String, SecretString)> {
+    if !crate::settings::interactive() {
+        anyhow::bail!("An API token for {} is needed; use a token profile or run interactively", provider.host());
+    }
+    let user = if provider.api_needs_user() {
+        Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("{} user name", provider.name()))
+            .interact_text()?
+    } else {
+        String::new()
+    };
+    let token = Password::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("API token for {} (used once, not stored)", provider.host()))
+        .interact()?;
+    crate::secrets::register_secret(&token);
+    Ok((user, SecretString::new(token)))
+}
+
+/// User name and token of a token profile (as git sends them), else ones asked for
+fn profile_api_token(profile: &Profile, provider: &dyn Provider) -> Result<(String, SecretString)> {
+    match profile.auth.token_for(provider.host()) {
+        Some(token) => Ok((provider.token_username(&profile.name), resolve_token(token)?)),
+        None => ask_api_token(provider),
+    }
+}
+
+/// Hosts a profile is used with: those of its host patterns, else the origin remote's
+fn profile_hosts(profile: &Profile) -> Vec<String> {
+    let mut hosts: Vec<String> = profile
+        .hosts
+        .iter()
+        .filter_map(|pattern| pattern.split([':', '/']).next())
+        .filter(|host| !host.is_empty() && !host.contains('*'))
+        .map(|host| host.to_ascii_lowercase())
+        .collect();
+    hosts.dedup();
+    if hosts.is_empty() {
+        if let Some((host, _)) = crate::detection::origin_url().and_then(|url| crate::submodule::split_remote_url(&url)) {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+/// Providers to talk to for a profile, asking when nothing says which
+fn profile_providers(profile: &Profile) -> Result<Vec<Box<dyn Provider>>> {
+    let hosts = profile_hosts(profile);
+    let mut providers = Vec::new();
+    for host in &hosts {
+        match for_host(host) {
+            Some(provider) => providers.push(provider),
+            None => println!("\x1b[1;33m⚠ Unknown provider at {}; skipped.\x1b[0m", host),
+        }
+    }
+    if providers.is_empty() && hosts.is_empty() && crate::settings::interactive() {
+        providers.extend(ask_provider_host()?);
+    }
+    if providers.is_empty() {
+        anyhow::bail!(
+            "No known host for profile '{}'; add one with 'gix profile edit' (remote hosts)",
+            profile.profile_name
+        );
+    }
+    Ok(providers)
+}
+
+/// `ssh -T` with the profile's key; returns the host's reply
+fn ssh_probe(profile: &Profile, key_path: &str, host: &str) -> Result<String> {
+    let key = crate::ssh::resolve_key_path(key_path);
+    let output = Command::new(crate::ssh::ssh_program(&profile.ssh))
+        .arg("-i")
+        .arg(&key)
+        .args(["-o", "IdentitiesOnly=yes", "-o", "BatchMode=yes", "-o", "ConnectTimeout=10", "-T"])
+        .arg(format!("git@{}", host))
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to run ssh")?;
+    Ok(format!("{}{}", String::from_utf8_lossy(&output.stderr), String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Handle the 'gix profile test' command: check a profile's credentials against its hosts
+pub fn handle_test_command(name: Option<String>) -> Result<()> {
+    let config = load_config()?;
+    let profile = match &name {
+        Some(n) => config.find_profile(n).with_context(|| format!("Profile '{}' not found", n))?,
+        None => crate::profile::select_profile(&config).context("No profile selected")?,
+    };
+
+    println!("\x1b[1;36m🔌 Testing profile '{}'\x1b[0m\n", profile.profile_name);
+    let mut all_ok = true;
+    for provider in profile_providers(profile)? {
+        let host = provider.host().to_string();
+        match &profile.auth {
+            AuthMethod::SSH { .. } => {
+                let key_path = profile.auth.key_for(Some(&host)).unwrap_or_default();
+                let reply = ssh_probe(profile, key_path, &host)?;
+                match provider.ssh_account(&reply) {
+                    Some(account) if account.is_empty() => println!("   \x1b[1;32m✓\x1b[0m {}: SSH key accepted", host),
+                    Some(account) => println!("   \x1b[1;32m✓\x1b[0m {}: SSH key accepted as \x1b[1m{}\x1b[0m", host, account),
+                    None => {
+                        let reason = reply.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("no reply");
+                        println!("   \x1b[1;31m✗\x1b[0m {}: {}", host, reason.trim());
+                        all_ok = false;
+                    }
+                }
+            }
+            AuthMethod::Token { .. } => match resolve_token(profile.auth.token_for(&host).unwrap_or_default())
+                .and_then(|t| provider.validate_token(&provider.token_username(&profile.name), &t))
+            {
+                Ok(account) => {
+                    println!("   \x1b[1;32m✓\x1b[0m {}: token valid for \x1b[1m{}\x1b[0m", host, account.login);
+                    if let Some(noreply) = provider.noreply_email(&account) {
+                        let used = profile.email.eq_ignore_ascii_case(&noreply)
+                            || profile.alternate_emails.iter().any(|e| e.eq_ignore_ascii_case(&noreply));
+                        let note = if used { "" } else { " (add it as an alternate email to keep your address private)" };
+                        println!("      No-reply address: {}{}", noreply, note);
+                    }
+                }
+                Err(e) => {
+                    println!("   \x1b[1;31m✗\x1b[0m {}: {:#}", host, e);
+                    all_ok = false;
+                }
+            },
+        }
+    }
+
+    println!();
+    if !all_ok {
+        anyhow::bail!("Profile '{}' could not authenticate everywhere", profile.profile_name);
+    }
+    println!("\x1b[1;32m✓ Profile '{}' works.\x1b[0m", profile.profile_name);
+    Ok(())
+}
+
+/// Handle the 'gix repos create' command: create a repository with a profile's account
+pub fn handle_create_command(name: String, public: bool, profile_name: Option<String>, host: Option<String>) -> Result<()> {
+    let config = load_config()?;
+    let profile = match &profile_name {
+        Some(n) => config.find_profile(n).with_context(|| format!("Profile '{}' not found", n))?,
+        None => match crate::detection::detect(&config) {
+            Some(detection) => detection.profile,
+            None => crate::profile::select_profile(&config).context("No profile selected")?,
+        },
+    };
+    let provider = match host {
+        Some(host) => for_host(&host).with_context(|| format!("Unknown provider at {}", host))?,
+        None => profile_providers(profile)?.swap_remove(0),
+    };
+
+    let (user, token) = profile_api_token(profile, provider.as_ref())?;
+    let repo = provider.create_repo(&user, &token, &name, !public)?;
+    let visibility = if public { "public" } else { "private" };
+    println!("\x1b[1;32m✓ Created {} repository {} on {}\x1b[0m", visibility, name, provider.host());
+    if !repo.web_url.is_empty() {
+        println!("   🌐 {}", repo.web_url);
+    }
+
+    // SSH profiles push over SSH, token profiles over HTTPS
+    let url = match profile.auth {
+        AuthMethod::SSH { .. } => &repo.ssh_url,
+        AuthMethod::Token { .. } => &repo.https_url,
+    };
+    if crate::git::is_inside_git_repo() && crate::detection::origin_url().is_none() {
+        let status = Command::new("git").args(["remote", "add", "origin", url]).status()?;
+        if status.success() {
+            println!("   🔗 Added as origin: {}", url);
+            return Ok(());
+        }
+    }
+    println!("   🔗 {}", url);
+    Ok(())
+}
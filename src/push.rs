@@ -0,0 +1,156 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::process::Command;
+
+use crate::profile::Profile;
+
+/// Environment variable that confirms protected-branch pushes non-interactively
+const ALLOW_PROTECTED_ENV: &str = "GIX_ALLOW_PROTECTED_PUSH";
+
+/// Remote and refspecs of a `git push` command line
+#[derive(Debug, Clone)]
+pub struct PushTarget {
+    pub remote: String,
+    /// (source, destination branch) pairs; an empty source deletes the destination
+    pub refspecs: Vec<(String, String)>,
+    /// Every tag is pushed too (`--tags`, `--mirror`)
+    pub tags: bool,
+}
+
+impl PushTarget {
+    /// Parse the arguments of an intercepted `push`
+    ///
+    /// Options and their values are skipped; without a remote git pushes to `origin`
+    /// (for the common setups gix manages), without refspecs the current branch.
+    /// `--all`/`--branches`/`--mirror` push every local branch, `--delete` sends nothing.
+    pub fn parse(args: &[String]) -> Self {
+        let has = |flags: &[&str]| args.iter().skip(1).any(|a| flags.contains(&a.as_str()));
+        let mut positionals = crate::git::positional_args(args).into_iter();
+        let remote = positionals
+            .next()
+            .map(String::from)
+            .or_else(|| args.iter().find_map(|a| a.strip_prefix("--repo=")).map(String::from))
+            .unwrap_or_else(|| "origin".to_string());
+        let delete = has(&["-d", "--delete"]);
+
+        let mut refspecs: Vec<(String, String)> = positionals
+            .map(|spec| {
+                let spec = spec.trim_start_matches('+');
+                let (src, dst) = spec.split_once(':').unwrap_or((spec, spec));
+                let dst = if dst == "HEAD" { current_branch().unwrap_or_default() } else { dst.to_string() };
+                let src = if delete { "" } else { src };
+                (src.to_string(), dst.trim_start_matches("refs/heads/").to_string())
+            })
+            .collect();
+
+        let tags = has(&["--tags", "--mirror"]);
+        if has(&["--all", "--branches", "--mirror"]) {
+            refspecs = local_branches().into_iter().map(|b| (b.clone(), b)).collect();
+        } else if refspecs.is_empty() && !tags {
+            if let Some(branch) = current_branch() {
+                refspecs.push(("HEAD".to_string(), branch));
+            }
+        }
+
+        PushTarget { remote, refspecs, tags }
+    }
+
+    /// Local revisions whose commits are sent, as `git log` arguments
+    pub fn sources(&self) -> Vec<String> {
+        let mut sources: Vec<String> = self.refspecs.iter().map(|(src, _)| src.clone()).filter(|s| !s.is_empty()).collect();
+        if self.tags {
+            sources.push("--tags".to_string());
+        }
+        sources
+    }
+}
+
+fn local_branches() -> Vec<String> {
+    Command::new("git")
+        .args(["for-each-ref", "--format=%(refname:short)", "refs/heads/"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+fn current_branch() -> Option<String> {
+    Command::new("git")
+        .args(["symbolic-ref", "--short", "-q", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+/// Match a name against a pattern where `*` stands for any run of characters
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || !text[first.len()..].ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Branches of a push that the profile protects
+pub fn protected_targets(profile: &Profile, args: &[String]) -> Vec<String> {
+    if profile.protected_branches.is_empty() || args.first().map(|a| a != "push").unwrap_or(true) {
+        return vec![];
+    }
+    PushTarget::parse(args)
+        .refspecs
+        .into_iter()
+        .map(|(_, dst)| dst)
+        .filter(|dst| profile.protected_branches.iter().any(|p| glob_match(p, dst)))
+        .collect()
+}
+
+/// Ask before an intercepted push updates one of the profile's protected branches
+pub fn confirm_protected_push(profile: &Profile, args: &[String]) -> Result<()> {
+    let protected = protected_targets(profile, args);
+    if protected.is_empty() {
+        return Ok(());
+    }
+
+    let branches = protected.join(", ");
+    if std::env::var(ALLOW_PROTECTED_ENV).is_ok_and(|v| v == "1") {
+        return Ok(());
+    }
+
+    eprintln!(
+        "\x1b[1;33m🛡️  '{}' is protected for profile '{}'.\x1b[0m",
+        branches, profile.profile_name
+    );
+
+    let interactive = crate::settings::interactive();
+    if !interactive {
+        anyhow::bail!(
+            "Push to protected branch '{}' needs confirmation (set {}=1 to allow it non-interactively)",
+            branches,
+            ALLOW_PROTECTED_ENV
+        );
+    }
+
+    if !Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Push to {} on '{}' as {}?", branches, PushTarget::parse(args).remote, profile.email))
+        .default(false)
+        .interact()?
+    {
+        anyhow::bail!("Push cancelled");
+    }
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::ReposAction;
+use crate::config::get_gix_home_dir;
+use crate::git::get_local_git_config;
+use crate::profile::{same_profile_name, AuthMethod};
+
+/// A profile switch of a repository
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Switch {
+    pub timestamp: String,
+    /// OS account that made the change
+    pub user: String,
+    pub from: Option<String>,
+    pub to: String,
+}
+
+/// A repository gix has configured
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RepoRecord {
+    pub path: PathBuf,
+    pub profile: String,
+    #[serde(default)]
+    pub switches: Vec<Switch>,
+}
+
+/// Machine-wide registry of managed repositories (~/.gix/registry.json)
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Registry {
+    #[serde(default)]
+    pub repos: Vec<RepoRecord>,
+    /// Repositories whose owner declined to configure a profile (`persist_prompt = once`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub declined: Vec<PathBuf>,
+}
+
+impl Registry {
+    pub fn find(&self, path: &Path) -> Option<&RepoRecord> {
+        let path = canonical(path);
+        self.repos.iter().find(|r| r.path == path)
+    }
+}
+
+fn registry_path() -> Result<PathBuf> {
+    Ok(get_gix_home_dir()?.join("registry.json"))
+}
+
+/// Registry entries are keyed by canonical path so `.` and absolute paths match
+///
+/// Stored in plain form (no Windows `\\?\` prefix) so git accepts them.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(crate::paths::long_path(path))
+        .map(|p| crate::paths::plain_path(&p))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Current OS user, for the "who" of a switch
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .or_else(|_| std::env::var("LOGNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+pub fn load_registry() -> Registry {
+    registry_path()
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str::<Registry>(&c).ok())
+        .map(|mut registry| {
+            // Older versions stored Windows paths in `\\?\` form
+            for repo in &mut registry.repos {
+                repo.path = crate::paths::plain_path(&repo.path);
+            }
+            registry
+        })
+        .unwrap_or_default()
+}
+
+fn save_registry(registry: &Registry) -> Result<()> {
+    let path = registry_path()?;
+    // Bookkeeping only: a read-only shared install must not stop profile switches
+    if !crate::config::is_writable(&path) {
+        return Ok(());
+    }
+    crate::config::write_atomic(&path, &serde_json::to_string_pretty(registry)?, false)
+}
+
+/// Record that `dir` now uses `profile`, appending a switch when it changed
+pub fn record_switch(dir: &Path, from: Option<&str>, profile: &str) -> Result<()> {
+    let mut registry = load_registry();
+    let path = canonical(dir);
+    registry.declined.retain(|p| *p != path);
+
+    let index = match registry.repos.iter().position(|r| r.path == path) {
+        Some(i) => i,
+        None => {
+            registry.repos.push(RepoRecord {
+                path,
+                profile: profile.to_string(),
+                switches: vec![],
+            });
+            registry.repos.len() - 1
+        }
+    };
+
+    let record = &mut registry.repos[index];
+    let from = from.map(|f| f.to_string()).or_else(|| record.switches.last().map(|s| s.to.clone()));
+    if from.as_deref() != Some(profile) {
+        record.switches.push(Switch {
+            timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            user: current_user(),
+            from,
+            to: profile.to_string(),
+        });
+    }
+    record.profile = profile.to_string();
+
+    save_registry(&registry)
+}
+
+/// Whether configuring a profile was declined for the repository at `dir`
+pub fn persistence_declined(dir: &Path) -> bool {
+    let path = canonical(dir);
+    load_registry().declined.contains(&path)
+}
+
+/// Remember that the repository at `dir` should not be configured, so gix stops asking
+pub fn record_declined(dir: &Path) -> Result<()> {
+    let mut registry = load_registry();
+    let path = canonical(dir);
+    if !registry.declined.contains(&path) {
+        registry.declined.push(path);
+        save_registry(&registry)?;
+    }
+    Ok(())
+}
+
+/// Print the switch history of a repository (for 'gix status --history')
+pub fn print_history(dir: &Path) {
+    let registry = load_registry();
+    let switches = registry.find(dir).map(|r| r.switches.as_slice()).unwrap_or_default();
+
+    println!("   \x1b[1m🕘 Profile history\x1b[0m");
+    if switches.is_empty() {
+        println!("      No profile switches recorded.");
+        return;
+    }
+    for switch in switches {
+        println!(
+            "      {}  {:<10} {} → \x1b[1m{}\x1b[0m",
+            switch.timestamp,
+            switch.user,
+            switch.from.as_deref().unwrap_or("(none)"),
+            switch.to
+        );
+    }
+}
+
+/// Handle the 'gix repos' command
+pub fn handle_repos_command(action: ReposAction) -> Result<()> {
+    match action {
+        ReposAction::MigrateKey { profile } => migrate_key(&profile),
+        ReposAction::Create { name, public, profile, host } => {
+            crate::provider::handle_create_command(name, public, profile, host)
+        }
+    }
+}
+
+/// Rewrite `core.sshCommand` of every registered repository of a profile after its key changed
+///
+/// All repositories are updated or none: a failure rolls back the ones already changed.
+fn migrate_key(profile_name: &str) -> Result<()> {
+    let config = crate::config::load_config()?;
+    let profile = config
+        .find_profile(profile_name)
+        .with_context(|| format!("Profile '{}' not found", profile_name))?;
+    let AuthMethod::SSH { key_path, .. } = &profile.auth else {
+        anyhow::bail!("Profile '{}' does not use an SSH key", profile.profile_name);
+    };
+    // Per-host keys: each repository gets the key for its origin's host
+    let ssh_command = |path: &PathBuf| crate::ssh::profile_ssh_command(profile, crate::git::origin_host(path).as_deref());
+
+    let repos: Vec<PathBuf> = load_registry()
+        .repos
+        .into_iter()
+        .filter(|r| same_profile_name(&r.profile, &profile.profile_name))
+        .map(|r| r.path)
+        .collect();
+
+    println!("\x1b[1;36m🔑 Migrating '{}' to {}\x1b[0m\n", profile.profile_name, key_path);
+
+    let (present, missing): (Vec<PathBuf>, Vec<PathBuf>) = repos.into_iter().partition(|p| p.join(".git").is_dir());
+    for path in &missing {
+        println!("   \x1b[1;33m⚠\x1b[0m {} (no longer a repository, skipped)", path.display());
+    }
+    let pending: Vec<(PathBuf, Option<String>, String)> = present
+        .into_iter()
+        .map(|p| {
+            let current = get_local_git_config(&p, "core.sshCommand");
+            let wanted = ssh_command(&p);
+            (p, current, wanted)
+        })
+        .filter(|(_, current, wanted)| current.as_deref() != Some(wanted.as_str()))
+        .collect();
+
+    if pending.is_empty() {
+        println!("\x1b[1;32m✓ All repositories of this profile already use the key.\x1b[0m");
+        return Ok(());
+    }
+
+    let mut done: Vec<&(PathBuf, Option<String>, String)> = Vec::new();
+    for entry in &pending {
+        let (path, _, wanted) = entry;
+        let result = crate::history::track(path, &profile.profile_name, || {
+            crate::git::set_local_git_config(path, "core.sshCommand", wanted)
+        });
+        if let Err(e) = result {
+            println!("   \x1b[1;31m✗\x1b[0m {}: {}", path.display(), e);
+            for (path, _, _) in done.iter().rev() {
+                // Undo the config change and the history entry it left for 'gix undo'
+                if let Err(e) = crate::history::rollback_last(path) {
+                    println!("   \x1b[1;31m✗\x1b[0m Could not roll back {}: {}", path.display(), e);
+                }
+            }
+            anyhow::bail!("Migration aborted; {} repository(ies) rolled back", done.len());
+        }
+        println!("   \x1b[1;32m✓\x1b[0m {}", path.display());
+        done.push(entry);
+    }
+
+    // One connection per host is enough to prove the new key is accepted
+    println!("\n\x1b[1;36m🔌 Verifying access\x1b[0m");
+    let mut checked: Vec<String> = Vec::new();
+    let mut failures = 0;
+    for (path, _, _) in &pending {
+        let Some(url) = crate::git::git_config_in(path, "remote.origin.url") else {
+            continue;
+        };
+        let Some((host, _)) = crate::submodule::split_remote_url(&url).filter(|_| !url.starts_with("http")) else {
+            continue;
+        };
+        if checked.contains(&host) {
+            continue;
+        }
+        checked.push(host.clone());
+
+        let ok = Command::new("git")
+            .arg("-C")
+            .arg(path)
+            .args(["ls-remote", "--quiet", "origin", "HEAD"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdout(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success());
+        if ok {
+            println!("   \x1b[1;32m✓\x1b[0m {} (via {})", host, path.display());
+        } else {
+            println!("   \x1b[1;31m✗\x1b[0m {} (via {}): the new key was rejected or the host is unreachable", host, path.display());
+            failures += 1;
+        }
+    }
+    if checked.is_empty() {
+        println!("   No SSH remotes to check.");
+    }
+
+    println!("\n\x1b[1;32m✓ Updated {} repository(ies).\x1b[0m", done.len());
+    if failures > 0 {
+        anyhow::bail!("{} host(s) did not accept the new key; upload its public key and retry 'git ls-remote'", failures);
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::path::Path;
+
+use crate::cli::RemoteAction;
+use crate::config::{load_config, Config};
+use crate::git::{get_git_root, get_local_git_config, remote_url_entries, run_local_git_config};
+use crate::profile::{AuthMethod, Profile};
+
+/// A remote URL that does not match the profile's authentication
+struct UrlFix {
+    /// Config key, e.g. `remote.origin.url`
+    key: String,
+    from: String,
+    to: String,
+}
+
+/// URL a remote should use so it authenticates like the profile, when its protocol
+/// does not match (SSH remote for a token profile, HTTPS remote for an SSH profile)
+fn fixed_url(config: &Config, profile: &Profile, url: &str) -> Option<String> {
+    let is_ssh = crate::rules::is_ssh_url(url);
+    let is_http = url.starts_with("https://") || url.starts_with("http://");
+    let (host, path) = crate::submodule::split_remote_url(url)?;
+    let host = crate::ssh_config::real_host(config, &host);
+    match &profile.auth {
+        AuthMethod::SSH { .. } if is_http => {
+            let host = match crate::ssh_config::can_alias(config, profile) {
+                true => crate::ssh_config::host_alias(&host, profile),
+                false => host,
+            };
+            Some(format!("git@{}:{}", host, path))
+        }
+        AuthMethod::Token { .. } if is_ssh => Some(format!("https://{}/{}", host, path)),
+        _ => None,
+    }
+}
+
+fn planned_fixes(config: &Config, profile: &Profile, dir: &Path) -> Vec<UrlFix> {
+    remote_url_entries(dir)
+        .into_iter()
+        .filter_map(|(key, from)| fixed_url(config, profile, &from).map(|to| UrlFix { key, from, to }))
+        .collect()
+}
+
+/// Profile to fix the remotes for: the one given, else the repository's
+fn remote_profile<'a>(config: &'a Config, name: Option<&str>) -> Result<&'a Profile> {
+    if let Some(name) = name {
+        return config.find_profile(name).with_context(|| format!("Profile '{}' not found", name));
+    }
+    crate::detection::env_profile(config)?
+        .or_else(|| crate::git::detect_profile(config))
+        .context("No profile for this repository: pass --profile or run 'gix use' first")
+}
+
+/// Handle the 'gix remotes' command
+pub fn handle_remote_command(action: RemoteAction) -> Result<()> {
+    match action {
+        RemoteAction::Fix { profile, yes } => fix_remotes(profile.as_deref(), yes),
+    }
+}
+
+/// Rewrite remote URLs whose protocol does not match the profile's authentication
+fn fix_remotes(name: Option<&str>, yes: bool) -> Result<()> {
+    let dir = get_git_root().context("Not inside a git repository")?;
+    let config = load_config()?;
+    let profile = remote_profile(&config, name)?;
+    let method = match profile.auth {
+        AuthMethod::SSH { .. } => "SSH",
+        AuthMethod::Token { .. } => "HTTPS (token)",
+    };
+
+    let fixes = planned_fixes(&config, profile, &dir);
+    if fixes.is_empty() {
+        println!(
+            "\x1b[1;32m✓ All remotes already use {}, like profile '{}'.\x1b[0m",
+            method, profile.profile_name
+        );
+        return Ok(());
+    }
+
+    println!(
+        "\x1b[1;36m🔧 Profile '{}' authenticates over {}; these remotes do not:\x1b[0m\n",
+        profile.profile_name, method
+    );
+    for fix in &fixes {
+        println!("   \x1b[1m{}\x1b[0m", fix.key);
+        println!("      {}", crate::secrets::redact(&fix.from));
+        println!("    → {}", fix.to);
+    }
+    println!();
+
+    if !yes {
+        if !crate::settings::interactive() {
+            anyhow::bail!("Pass --yes to rewrite the remotes without confirmation");
+        }
+        let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Rewrite {} URL(s)?", fixes.len()))
+            .default(true)
+            .interact()?;
+        if !confirmed {
+            println!("Nothing was changed.");
+            return Ok(());
+        }
+    }
+
+    for fix in &fixes {
+        run_local_git_config(&dir, &["--fixed-value", "--replace-all", &fix.key, &fix.to, &fix.from])
+            .with_context(|| format!("Failed to rewrite {}", fix.key))?;
+    }
+    println!("\x1b[1;32m✓ Rewrote {} remote URL(s).\x1b[0m", fixes.len());
+
+    // The new URLs only authenticate once the repository is configured for the profile
+    let configured = match profile.auth {
+        AuthMethod::SSH { .. } => {
+            crate::ssh_config::can_alias(&config, profile) || get_local_git_config(&dir, "core.sshCommand").is_some()
+        }
+        AuthMethod::Token { .. } => true,
+    };
+    if !configured {
+        println!(
+            "   Run '\x1b[1mgix use {}\x1b[0m' so git uses the profile's SSH key for them.",
+            profile.profile_name
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::cli::ReportFormat;
+
+/// A commit that does not comply with the expected identity
+#[derive(Serialize, Debug, Clone)]
+pub struct OffendingCommit {
+    pub hash: String,
+    pub author_email: String,
+    pub subject: String,
+    pub reason: String,
+}
+
+/// One repository entry of a compliance report
+#[derive(Serialize, Debug, Clone)]
+pub struct ReportEntry {
+    pub repository: String,
+    pub remote: Option<String>,
+    pub expected_profile: Option<String>,
+    pub expected_email: Option<String>,
+    pub actual_email: Option<String>,
+    pub status: String,
+    pub offending_commits: Vec<OffendingCommit>,
+}
+
+/// A full report as written to disk
+#[derive(Serialize, Debug)]
+pub struct Report {
+    pub kind: String,
+    pub generated_at: String,
+    pub gix_version: String,
+    pub entries: Vec<ReportEntry>,
+}
+
+impl Report {
+    pub fn new(kind: &str, entries: Vec<ReportEntry>) -> Self {
+        Report {
+            kind: kind.to_string(),
+            generated_at: chrono::Local::now().to_rfc3339(),
+            gix_version: env!("CARGO_PKG_VERSION").to_string(),
+            entries,
+        }
+    }
+}
+
+/// Pick the report format: explicit flag first, then the file extension, then JSON
+pub fn resolve_format(path: &Path, format: Option<ReportFormat>) -> ReportFormat {
+    format.unwrap_or_else(|| {
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+            Some("csv") => ReportFormat::Csv,
+            Some("html") | Some("htm") => ReportFormat::Html,
+            _ => ReportFormat::Json,
+        }
+    })
+}
+
+/// Write a report to `path` in the requested format
+pub fn write_report(report: &Report, path: &Path, format: Option<ReportFormat>) -> Result<()> {
+    let contents = match resolve_format(path, format) {
+        ReportFormat::Json => serde_json::to_string_pretty(report)?,
+        ReportFormat::Csv => to_csv(report),
+        ReportFormat::Html => to_html(report),
+    };
+
+    fs::write(path, contents).with_context(|| format!("Failed to write report to {}", path.display()))?;
+    println!("\n\x1b[1;32m✓ Report written to {}\x1b[0m", path.display());
+    Ok(())
+}
+
+/// Quote a CSV field when it contains separators, quotes or newlines
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One row per offending commit; repositories without any get a single row
+fn to_csv(report: &Report) -> String {
+    let mut out = String::from(
+        "repository,remote,expected_profile,expected_email,actual_email,status,commit,commit_author,commit_subject,reason\n",
+    );
+
+    for entry in &report.entries {
+        let base = [
+            entry.repository.as_str(),
+            entry.remote.as_deref().unwrap_or(""),
+            entry.expected_profile.as_deref().unwrap_or(""),
+            entry.expected_email.as_deref().unwrap_or(""),
+            entry.actual_email.as_deref().unwrap_or(""),
+            entry.status.as_str(),
+        ]
+        .map(csv_field)
+        .join(",");
+
+        if entry.offending_commits.is_empty() {
+            out.push_str(&format!("{},,,,\n", base));
+        }
+        for commit in &entry.offending_commits {
+            let extra = [
+                commit.hash.as_str(),
+                commit.author_email.as_str(),
+                commit.subject.as_str(),
+                commit.reason.as_str(),
+            ]
+            .map(csv_field)
+            .join(",");
+            out.push_str(&format!("{},{}\n", base, extra));
+        }
+    }
+
+    out
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Self-contained HTML page, suitable for attaching to a review
+fn to_html(report: &Report) -> String {
+    let mut rows = String::new();
+
+    for entry in &report.entries {
+        let commits = if entry.offending_commits.is_empty() {
+            "—".to_string()
+        } else {
+            let items: Vec<String> = entry
+                .offending_commits
+                .iter()
+                .map(|c| {
+                    format!(
+                        "<li><code>{}</code> {} &lt;{}&gt; — {}</li>",
+                        html_escape(&c.hash[..c.hash.len().min(12)]),
+                        html_escape(&c.subject),
+                        html_escape(&c.author_email),
+                        html_escape(&c.reason)
+                    )
+                })
+                .collect();
+            format!("<ul>{}</ul>", items.join(""))
+        };
+
+        let status_class = if entry.status == "ok" { "ok" } else { "bad" };
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td>{}</td></tr>\n",
+            html_escape(&entry.repository),
+            html_escape(entry.remote.as_deref().unwrap_or("")),
+            html_escape(&match (&entry.expected_profile, &entry.expected_email) {
+                (Some(p), Some(e)) => format!("{} <{}>", p, e),
+                (Some(p), None) => p.clone(),
+                _ => String::new(),
+            }),
+            html_escape(entry.actual_email.as_deref().unwrap_or("")),
+            status_class,
+            html_escape(&entry.status),
+            commits
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>gix {kind} report</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ border: 1px solid #ccc; padding: 6px 8px; text-align: left; vertical-align: top; }}
+th {{ background: #f0f0f0; }}
+.ok {{ color: #1a7f37; }}
+.bad {{ color: #cf222e; font-weight: bold; }}
+ul {{ margin: 0; padding-left: 1.2em; }}
+</style>
+</head>
+<body>
+<h1>gix {kind} report</h1>
+<p>Generated {generated} by gix {version}</p>
+<table>
+<tr><th>Repository</th><th>Remote</th><th>Expected identity</th><th>Actual identity</th><th>Status</th><th>Offending commits</th></tr>
+{rows}</table>
+</body>
+</html>
+"#,
+        kind = html_escape(&report.kind),
+        generated = html_escape(&report.generated_at),
+        version = html_escape(&report.gix_version),
+        rows = rows
+    )
+}
@@ -0,0 +1,215 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::RuleAction;
+use crate::config::{load_config, save_config, Config};
+use crate::profile::Profile;
+
+/// A rule that maps repositories to a profile when nothing selects one explicitly
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Rule {
+    /// Any email at this domain (or a subdomain) belongs to the profile
+    EmailDomain { domain: String, profile: String },
+    /// Clone URLs on this host (or `host/org/*` path pattern) belong to the profile
+    Host { pattern: String, profile: String },
+}
+
+impl Rule {
+    pub fn profile(&self) -> &str {
+        match self {
+            Rule::EmailDomain { profile, .. } | Rule::Host { profile, .. } => profile,
+        }
+    }
+
+    /// Whether the rule applies to an email address
+    pub fn matches(&self, email: &str) -> bool {
+        match self {
+            Rule::EmailDomain { domain, .. } => email_in_domain(email, domain),
+            Rule::Host { .. } => false,
+        }
+    }
+
+    /// Whether the rule applies to a remote URL
+    pub fn matches_url(&self, url: &str) -> bool {
+        match self {
+            Rule::Host { pattern, .. } => url_matches_host_pattern(url, pattern),
+            Rule::EmailDomain { .. } => false,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Rule::EmailDomain { domain, profile } => format!("*@{} → {}", domain, profile),
+            Rule::Host { pattern, profile } => format!("{} → {}", pattern, profile),
+        }
+    }
+}
+
+/// Normalize a domain as typed by the user ("@Acme.com" → "acme.com")
+fn normalize_domain(domain: &str) -> String {
+    domain.trim().trim_start_matches('@').to_ascii_lowercase()
+}
+
+/// Whether an email address belongs to a domain or one of its subdomains
+pub fn email_in_domain(email: &str, domain: &str) -> bool {
+    let Some((_, host)) = email.rsplit_once('@') else {
+        return false;
+    };
+    let host = host.to_ascii_lowercase();
+    let domain = normalize_domain(domain);
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// A host pattern in `host/path` form: the scp-like `host:path` separator becomes `/`
+/// and a port (`host:8443/path`) is dropped, as remote hosts are compared without one
+pub fn normalize_host_pattern(pattern: &str) -> String {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    let Some((host, rest)) = pattern.split_once(':').filter(|(host, _)| !host.contains('/')) else {
+        return pattern;
+    };
+    let port_end = rest.find('/').unwrap_or(rest.len());
+    if port_end > 0 && rest[..port_end].bytes().all(|b| b.is_ascii_digit()) {
+        format!("{}{}", host, &rest[port_end..])
+    } else {
+        format!("{}/{}", host, rest)
+    }
+}
+
+/// Host part of a host pattern (`github.com` for `github.com:acme/*`)
+pub fn pattern_host(pattern: &str) -> String {
+    let pattern = normalize_host_pattern(pattern);
+    pattern.split('/').next().unwrap_or_default().to_string()
+}
+
+/// Whether a remote URL matches `host` or `host/path` glob patterns
+///
+/// Path patterns are compared without a trailing `.git`, so `github.com/acme/*`
+/// matches `git@github.com:acme/api.git`. The scp-like `github.com:acme/*` works too.
+/// SSH URLs through an ssh_config alias (`git@github-work:acme/api`) also match by
+/// the real host name the alias points to.
+pub fn url_matches_host_pattern(url: &str, pattern: &str) -> bool {
+    let Some((host, path)) = crate::submodule::split_remote_url(url) else {
+        return false;
+    };
+    let pattern = normalize_host_pattern(pattern);
+    let matches = |host: &str| {
+        let host = host.to_ascii_lowercase();
+        match pattern.split_once('/') {
+            Some(_) => {
+                let full = format!("{}/{}", host, path.trim_end_matches(".git").to_ascii_lowercase());
+                crate::push::glob_match(&pattern, &full)
+            }
+            None => crate::push::glob_match(&pattern, &host),
+        }
+    };
+    if matches(&host) {
+        return true;
+    }
+    is_ssh_url(url) && crate::ssh::resolve_host_alias(&host).is_some_and(|real| matches(&real))
+}
+
+/// Whether a remote URL goes over SSH (`ssh://` or scp-like `user@host:path`)
+pub fn is_ssh_url(url: &str) -> bool {
+    url.starts_with("ssh://") || (!url.contains("://") && crate::submodule::split_remote_url(url).is_some())
+}
+
+/// Profiles declaring a host pattern that matches a URL
+pub fn profiles_declaring_url<'a>(config: &'a Config, url: &str) -> Vec<&'a Profile> {
+    config.profiles.iter().filter(|p| p.matches_url(url)).collect()
+}
+
+/// Profiles of every host rule or profile host pattern matching a URL, without duplicates
+pub fn profiles_for_url<'a>(config: &'a Config, url: &str) -> Vec<&'a Profile> {
+    let mut profiles: Vec<&Profile> = profiles_declaring_url(config, url);
+    for rule in config.rules.iter().filter(|r| r.matches_url(url)) {
+        if let Some(p) = config.find_profile(rule.profile()) {
+            if !profiles.iter().any(|q| q.is_named(&p.profile_name)) {
+                profiles.push(p);
+            }
+        }
+    }
+    profiles
+}
+
+/// Profile that owns an email: exact (primary or alternate) address first, then domain rules
+pub fn profile_for_email<'a>(config: &'a Config, email: &str) -> Option<&'a Profile> {
+    if let Some(p) = config.profiles.iter().find(|p| p.owns_email(email)) {
+        return Some(p);
+    }
+    profile_for_domain(config, email)
+}
+
+/// Profile mapped to an email by the first matching domain rule
+pub fn profile_for_domain<'a>(config: &'a Config, email: &str) -> Option<&'a Profile> {
+    config.rules.iter().find_map(|rule| match rule {
+        Rule::EmailDomain { domain, profile } if email_in_domain(email, domain) => {
+            config.find_profile(profile)
+        }
+        _ => None,
+    })
+}
+
+/// Handle the 'gix rule' command
+pub fn handle_rule_command(action: RuleAction) -> Result<()> {
+    let mut config = load_config()?;
+
+    match action {
+        RuleAction::List => {
+            if config.rules.is_empty() {
+                println!("\x1b[1;33m📋 No rules configured.\x1b[0m");
+                println!("   Add one with '\x1b[1mgix rule add --domain acme.com Work\x1b[0m' or '\x1b[1mgix rule add --host github.com/acme/* Work\x1b[0m'.");
+                return Ok(());
+            }
+            println!("\x1b[1;36m📋 Profile rules:\x1b[0m\n");
+            for (i, rule) in config.rules.iter().enumerate() {
+                let missing = if config.find_profile(rule.profile()).is_some() {
+                    ""
+                } else {
+                    " \x1b[1;31m(profile not found)\x1b[0m"
+                };
+                println!("  \x1b[1;32m{}\x1b[0m. {}{}", i + 1, rule.describe(), missing);
+            }
+        }
+        RuleAction::Add { domain, host, profile } => {
+            let profile = config
+                .find_profile(&profile)
+                .map(|p| p.profile_name.clone())
+                .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found", profile))?;
+            let rule = match (domain, host) {
+                (Some(domain), _) => {
+                    let domain = normalize_domain(&domain);
+                    if domain.is_empty() || !domain.contains('.') {
+                        anyhow::bail!("Invalid email domain: {}", domain);
+                    }
+                    Rule::EmailDomain { domain, profile }
+                }
+                (None, Some(pattern)) => {
+                    let pattern = pattern.trim().to_ascii_lowercase();
+                    if pattern.is_empty() || pattern.contains("://") {
+                        anyhow::bail!("Invalid host pattern: {} (use e.g. github.com/acme/*)", pattern);
+                    }
+                    Rule::Host { pattern, profile }
+                }
+                (None, None) => anyhow::bail!("Specify --domain or --host"),
+            };
+            if config.rules.contains(&rule) {
+                println!("\x1b[1;33m⚠ Rule already exists.\x1b[0m");
+                return Ok(());
+            }
+            println!("\x1b[1;32m✓ Added rule {}\x1b[0m", rule.describe());
+            config.rules.push(rule);
+            save_config(&config)?;
+        }
+        RuleAction::Remove { index } => {
+            if index == 0 || index > config.rules.len() {
+                anyhow::bail!("No rule #{} (see 'gix rule list')", index);
+            }
+            let rule = config.rules.remove(index - 1);
+            save_config(&config)?;
+            println!("\x1b[1;32m✓ Removed rule {}\x1b[0m", rule.describe());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,382 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::ReportFormat;
+use crate::config::{load_config, load_local_config_from_dir, save_local_profile_selection_to_dir, Config};
+use crate::git::{apply_local_config_to_dir, git_config_in};
+use crate::history::track;
+use crate::profile::Profile;
+use crate::report::{write_report, OffendingCommit, Report, ReportEntry};
+
+/// Directories never worth descending into while looking for repositories
+const SKIPPED_DIRS: [&str; 4] = ["node_modules", "target", ".cache", "vendor"];
+
+/// Identity state of one repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoState {
+    /// Bound to a profile and git identity matches it
+    Ok,
+    /// Bound to a profile but git identity differs
+    Misconfigured,
+    /// Bound to a profile name that no longer exists
+    Dangling,
+    /// Not bound, but its email belongs to a known profile
+    UnmanagedKnown,
+    /// Not bound and its identity is unknown to gix
+    Unmanaged,
+    /// Not bound, and using a profile other than the one a host rule selects for its remote
+    RuleMismatch,
+}
+
+impl RepoState {
+    /// Stable identifier used in exported reports
+    pub fn label(&self) -> &'static str {
+        match self {
+            RepoState::Ok => "ok",
+            RepoState::Misconfigured => "misconfigured",
+            RepoState::Dangling => "dangling",
+            RepoState::UnmanagedKnown => "unmanaged_known",
+            RepoState::Unmanaged => "unmanaged",
+            RepoState::RuleMismatch => "rule_mismatch",
+        }
+    }
+}
+
+/// Scan result for one repository
+#[derive(Debug, Clone)]
+pub struct RepoReport {
+    pub path: PathBuf,
+    pub remote: Option<String>,
+    /// Profile the repository should use (host rule, local selection or email match)
+    pub expected: Option<String>,
+    pub expected_email: Option<String>,
+    /// Effective `user.email` in the repository
+    pub actual_email: Option<String>,
+    pub state: RepoState,
+    /// Host rule or profile host pattern that matched the remote, if any
+    pub rule: Option<String>,
+    /// The repository's own choice in .gix differs from the profile `rule` selects
+    pub overrides_rule: bool,
+    /// The profile is locked by team policy: `--fix` leaves the repository alone
+    pub locked: bool,
+}
+
+/// Recursively collect git repositories under a directory
+pub fn find_repositories(root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut repos = Vec::new();
+    walk(root, 0, max_depth, &mut repos);
+    repos.sort();
+    repos
+}
+
+fn walk(dir: &Path, depth: usize, max_depth: usize, repos: &mut Vec<PathBuf>) {
+    // `.git` is a directory for normal repos and a file for worktrees/submodules
+    if dir.join(".git").exists() {
+        repos.push(dir.to_path_buf());
+        return;
+    }
+
+    if depth >= max_depth {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if !file_type.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_str()) {
+            continue;
+        }
+        walk(&entry.path(), depth + 1, max_depth, repos);
+    }
+}
+
+/// Profile selected for a remote URL by host rules, with the matching rule
+///
+/// Returns None when nothing matches or when several profiles claim the URL,
+/// since an ambiguous match cannot say which identity is expected.
+fn profile_for_remote<'a>(config: &'a Config, url: &str) -> Option<(&'a Profile, String)> {
+    let [profile] = crate::rules::profiles_for_url(config, url)[..] else {
+        return None;
+    };
+    let rule = config
+        .rules
+        .iter()
+        .find(|r| r.matches_url(url) && profile.is_named(r.profile()))
+        .map(|r| r.describe())
+        .unwrap_or_else(|| format!("host pattern of profile {}", profile.profile_name));
+    Some((profile, rule))
+}
+
+/// Resolve expected and actual identity for a repository
+pub fn inspect_repository(config: &Config, path: &Path) -> RepoReport {
+    let actual_email = git_config_in(path, "user.email");
+    let remote = git_config_in(path, "remote.origin.url");
+    let local = load_local_config_from_dir(path);
+    let locked = local.as_ref().is_some_and(|l| l.locked);
+    let selected = local.and_then(|l| l.selected_profile);
+    let by_rule = remote.as_deref().and_then(|url| profile_for_remote(config, url));
+
+    let find = |name: &str| config.find_profile(name);
+    let by_email = |email: &str| crate::rules::profile_for_email(config, email);
+    let uses = |p: &Profile| actual_email.as_deref().is_some_and(|e| p.owns_email(e));
+
+    // A profile chosen in .gix wins over host rules: it is expected, not a mismatch
+    let (expected, state): (Option<&Profile>, RepoState) = match (&selected, &by_rule) {
+        (Some(name), _) => match find(name) {
+            Some(p) if uses(p) => (Some(p), RepoState::Ok),
+            Some(p) => (Some(p), RepoState::Misconfigured),
+            None => (None, RepoState::Dangling),
+        },
+        (None, Some((r, _))) => match actual_email.as_deref().and_then(by_email) {
+            Some(p) if !p.is_named(&r.profile_name) => (Some(*r), RepoState::RuleMismatch),
+            _ => (Some(*r), RepoState::UnmanagedKnown),
+        },
+        (None, None) => match actual_email.as_deref().and_then(by_email) {
+            Some(p) => (Some(p), RepoState::UnmanagedKnown),
+            None => (None, RepoState::Unmanaged),
+        },
+    };
+
+    let overrides_rule = match (selected.as_deref().and_then(find), &by_rule) {
+        (Some(p), Some((r, _))) => !r.is_named(&p.profile_name),
+        _ => false,
+    };
+
+    RepoReport {
+        path: path.to_path_buf(),
+        remote,
+        expected: expected.map(|p| p.profile_name.clone()).or(selected),
+        expected_email: expected.map(|p| p.email.clone()),
+        actual_email,
+        state,
+        rule: by_rule.map(|(_, rule)| rule),
+        overrides_rule,
+        locked,
+    }
+}
+
+/// Recent commits authored under another gix profile instead of the expected one
+///
+/// Commits by people outside the configured profiles are not counted: only
+/// identity slips of the user themselves are reported.
+pub fn offending_commits(config: &Config, report: &RepoReport, count: usize) -> Vec<OffendingCommit> {
+    let Some(expected) = report
+        .expected
+        .as_ref()
+        .and_then(|name| config.find_profile(name))
+    else {
+        return vec![];
+    };
+
+    let out = Command::new("git")
+        .current_dir(&report.path)
+        .args(["log", &format!("-n{}", count), "--format=%H%x1f%ae%x1f%s"])
+        .output();
+    let Ok(out) = out else {
+        return vec![];
+    };
+    if !out.status.success() {
+        return vec![];
+    }
+
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\x1f').collect();
+            let [hash, email, subject] = fields[..] else {
+                return None;
+            };
+            if expected.owns_email(email) {
+                return None;
+            }
+            let other = config.profiles.iter().find(|p| p.owns_email(email))?;
+            Some(OffendingCommit {
+                hash: hash.to_string(),
+                author_email: email.to_string(),
+                subject: subject.to_string(),
+                reason: format!("authored as profile '{}'", other.profile_name),
+            })
+        })
+        .collect()
+}
+
+impl RepoReport {
+    /// Convert into an exportable report entry
+    pub fn to_entry(&self, offending_commits: Vec<OffendingCommit>) -> ReportEntry {
+        ReportEntry {
+            repository: self.path.display().to_string(),
+            remote: self.remote.clone(),
+            expected_profile: self.expected.clone(),
+            expected_email: self.expected_email.clone(),
+            actual_email: self.actual_email.clone(),
+            status: self.state.label().to_string(),
+            offending_commits,
+        }
+    }
+}
+
+/// Bring a repository in line with its expected profile; returns true if changed
+fn fix_repository(config: &Config, report: &RepoReport) -> Result<bool> {
+    if report.locked {
+        return Ok(false);
+    }
+    let Some(profile) = report
+        .expected
+        .as_ref()
+        .and_then(|name| config.find_profile(name))
+    else {
+        return Ok(false);
+    };
+
+    match report.state {
+        RepoState::Misconfigured => {
+            track(&report.path, &profile.profile_name, || apply_local_config_to_dir(profile, &report.path))?;
+            Ok(true)
+        }
+        RepoState::UnmanagedKnown | RepoState::RuleMismatch => {
+            track(&report.path, &profile.profile_name, || {
+                save_local_profile_selection_to_dir(&profile.profile_name, report.path.clone())?;
+                apply_local_config_to_dir(profile, &report.path)
+            })?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Handle the 'gix scan' command
+pub fn handle_scan_command(
+    dir: PathBuf,
+    max_depth: usize,
+    fix: bool,
+    report_path: Option<PathBuf>,
+    format: Option<ReportFormat>,
+    commits: usize,
+) -> Result<()> {
+    let config = load_config()?;
+
+    println!("\x1b[1;36m🔎 Scanning {} for git repositories...\x1b[0m\n", dir.display());
+
+    let repos = find_repositories(&dir, max_depth);
+    if repos.is_empty() {
+        println!("   No repositories found.");
+        return Ok(());
+    }
+
+    let mut counts = [0usize; 6];
+    let mut fixed = 0;
+    let mut entries = Vec::new();
+
+    for path in &repos {
+        let report = inspect_repository(&config, path);
+        let actual = report.actual_email.as_deref().unwrap_or("(no user.email)");
+
+        let (idx, line) = match report.state {
+            RepoState::Ok => (
+                0,
+                format!("\x1b[1;32m✓\x1b[0m {} → {}", report.expected.as_deref().unwrap_or(""), actual),
+            ),
+            RepoState::Misconfigured => (
+                1,
+                format!(
+                    "\x1b[1;31m✗\x1b[0m expects {} <{}> but uses {}",
+                    report.expected.as_deref().unwrap_or(""),
+                    report.expected_email.as_deref().unwrap_or(""),
+                    actual
+                ),
+            ),
+            RepoState::Dangling => (
+                2,
+                format!(
+                    "\x1b[1;31m✗\x1b[0m bound to missing profile '{}'",
+                    report.expected.as_deref().unwrap_or("")
+                ),
+            ),
+            RepoState::UnmanagedKnown => (
+                3,
+                match &report.rule {
+                    Some(rule) => format!(
+                        "\x1b[1;33m⚠\x1b[0m unmanaged, rule '{}' selects profile {}",
+                        rule,
+                        report.expected.as_deref().unwrap_or("")
+                    ),
+                    None => format!(
+                        "\x1b[1;33m⚠\x1b[0m unmanaged, email matches profile {}",
+                        report.expected.as_deref().unwrap_or("")
+                    ),
+                },
+            ),
+            RepoState::Unmanaged => (4, format!("\x1b[1;33m⚠\x1b[0m unmanaged, uses {}", actual)),
+            RepoState::RuleMismatch => (
+                5,
+                format!(
+                    "\x1b[1;31m✗\x1b[0m rule '{}' expects {} <{}> but uses {}",
+                    report.rule.as_deref().unwrap_or(""),
+                    report.expected.as_deref().unwrap_or(""),
+                    report.expected_email.as_deref().unwrap_or(""),
+                    actual
+                ),
+            ),
+        };
+        counts[idx] += 1;
+
+        println!("   {}", path.display());
+        println!("      {}", line);
+        if report.overrides_rule {
+            println!("      \x1b[2mchosen in .gix over rule '{}'\x1b[0m", report.rule.as_deref().unwrap_or(""));
+        }
+
+        if report_path.is_some() {
+            let commits = offending_commits(&config, &report, commits);
+            if !commits.is_empty() {
+                println!("      \x1b[1;31m✗\x1b[0m {} commit(s) authored under another profile", commits.len());
+            }
+            entries.push(report.to_entry(commits));
+        }
+
+        let fixable = matches!(
+            report.state,
+            RepoState::Misconfigured | RepoState::UnmanagedKnown | RepoState::RuleMismatch
+        );
+        if fix && fixable && report.locked {
+            println!("      \x1b[1;33m⚠ Locked by team policy: not changed\x1b[0m");
+        } else if fix && fix_repository(&config, &report)? {
+            fixed += 1;
+            println!("      \x1b[1;32m✓ Fixed\x1b[0m");
+        }
+    }
+
+    println!(
+        "\n   {} repositories: {} ok, {} misconfigured, {} rule mismatch, {} dangling, {} unmanaged (known identity), {} unmanaged",
+        repos.len(),
+        counts[0],
+        counts[1],
+        counts[5],
+        counts[2],
+        counts[3],
+        counts[4]
+    );
+
+    if let Some(path) = &report_path {
+        write_report(&Report::new("scan", entries), path, format)?;
+    }
+
+    if fix {
+        println!("\n\x1b[1;32m✓ Fixed {} repositories.\x1b[0m", fixed);
+    } else if counts[1] + counts[3] + counts[5] > 0 {
+        println!("\n   Run '\x1b[1mgix scan {} --fix\x1b[0m' to apply the expected profiles.", dir.display());
+    }
+
+    Ok(())
+}
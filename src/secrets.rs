@@ -0,0 +1,289 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::config::get_gix_home_dir;
+use crate::credential::{split_url_credentials, UrlCredentials};
+
+/// Prefixes of provider tokens (GitHub, GitLab, Bitbucket)
+const TOKEN_PREFIXES: &[&str] = &[
+    "github_pat_", "ghp_", "gho_", "ghu_", "ghs_", "ghr_", "glpat-", "gloas-", "ATBB",
+];
+
+/// A plaintext secret found on disk
+pub struct Finding {
+    pub file: PathBuf,
+    pub line: usize,
+    pub masked: String,
+    /// Credentials embedded in a URL stored under this git config key
+    pub git_key: Option<(String, UrlCredentials)>,
+}
+
+/// A secret held in memory only as long as needed: its bytes are zeroed on drop
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: String) -> Self {
+        SecretString(value)
+    }
+}
+
+impl std::ops::Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("****")
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        let mut bytes = std::mem::take(&mut self.0).into_bytes();
+        bytes.fill(0);
+        // Keep the zeroing from being optimized away as a dead store
+        std::hint::black_box(&bytes);
+    }
+}
+
+/// Secret values resolved by this process, masked by `redact` whatever they look like
+static KNOWN_SECRETS: Mutex<Vec<SecretString>> = Mutex::new(Vec::new());
+
+/// Remember a secret so logs and error messages never show it
+///
+/// Very short values are skipped: masking them would mangle ordinary words.
+pub fn register_secret(value: &str) {
+    if value.len() < 6 {
+        return;
+    }
+    let mut known = KNOWN_SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    if !known.iter().any(|k| &**k == value) {
+        known.push(SecretString::new(value.to_string()));
+    }
+}
+
+/// Does this word look like a provider token?
+fn is_token(word: &str) -> bool {
+    TOKEN_PREFIXES
+        .iter()
+        .any(|p| word.starts_with(p) && word.len() >= p.len() + 16)
+}
+
+/// Words of a line, split on anything that cannot be part of a URL or token
+fn words(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ',' | '(' | ')' | '[' | ']' | '<' | '>'))
+        .filter(|w| !w.is_empty())
+}
+
+/// The line with every token, URL password and known secret replaced by `****`
+pub fn redact(line: &str) -> String {
+    let mut result = line.to_string();
+    for secret in KNOWN_SECRETS.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+        result = result.replace(&**secret, "****");
+    }
+    let line = result.clone();
+    for word in words(&line) {
+        if let Some(creds) = split_url_credentials(word) {
+            result = result.replace(word, &creds.masked_url());
+        } else if is_token(word) {
+            result = result.replace(word, "****");
+        } else if let Some(token) = word.split(['=', ':', '/', '@']).find(|p| is_token(p)) {
+            result = result.replace(token, "****");
+        }
+    }
+    result
+}
+
+pub fn contains_secret(line: &str) -> bool {
+    redact(line) != line
+}
+
+/// Files under ~/.gix that should never hold a secret
+///
+/// config.json is where token profiles live on purpose, so it is left out.
+fn gix_state_files() -> Vec<PathBuf> {
+    let Ok(home) = get_gix_home_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(&home) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.file_name().map(|n| n != "config.json").unwrap_or(false))
+        .collect();
+    files.sort();
+    files
+}
+
+fn scan_file(path: &Path) -> Vec<Finding> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| contains_secret(line))
+        .map(|(i, line)| Finding {
+            file: path.to_path_buf(),
+            line: i + 1,
+            masked: redact(line.trim()),
+            git_key: None,
+        })
+        .collect()
+}
+
+/// Look for credentials in the local git config of a repository
+fn scan_git_config(repo: &Path) -> Vec<Finding> {
+    let file = repo.join(".git").join("config");
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["config", "--local", "--list"])
+        .output();
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            if !contains_secret(value) {
+                return None;
+            }
+            Some(Finding {
+                file: file.clone(),
+                line: 0,
+                masked: format!("{} = {}", key, redact(value)),
+                git_key: split_url_credentials(value).map(|c| (key.to_string(), c)),
+            })
+        })
+        .collect()
+}
+
+/// Scan gix state, logs and registered repositories for plaintext secrets
+pub fn scan() -> Vec<Finding> {
+    let mut findings: Vec<Finding> = gix_state_files().iter().flat_map(|f| scan_file(f)).collect();
+    for repo in crate::registry::load_registry().repos {
+        if repo.path.join(".git").is_dir() {
+            findings.extend(scan_git_config(&repo.path));
+        }
+    }
+    findings
+}
+
+/// Hand URL credentials to the git credential helper and point the key at the clean URL
+///
+/// The URL keeps its credentials unless a helper took them: otherwise they would be lost.
+fn migrate_git_key(repo: &Path, key: &str, creds: &UrlCredentials) -> Result<()> {
+    if crate::credential::configured_helpers_in(repo).is_empty() {
+        anyhow::bail!(
+            "no credential helper would keep the credentials; configure one (e.g. 'git config --global credential.helper store') and retry"
+        );
+    }
+    let mut child = Command::new("git")
+        .current_dir(repo)
+        .args(["credential", "approve"])
+        .stdin(std::process::Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        use std::io::Write;
+        let protocol = creds.url.split("://").next().unwrap_or("https");
+        write!(
+            stdin,
+            "protocol={}\nhost={}\nusername={}\npassword={}\n",
+            protocol, creds.host, creds.username, creds.secret
+        )?;
+    }
+    if !child.wait()?.success() {
+        anyhow::bail!("git credential approve failed; the URL was left unchanged");
+    }
+    crate::git::set_local_git_config(repo, key, &creds.clean_url)
+}
+
+/// Rewrite a gix state file with every secret redacted
+fn scrub_file(path: &Path) -> Result<()> {
+    let contents = fs::read_to_string(path)?;
+    let mut scrubbed: Vec<String> = contents.lines().map(redact).collect();
+    if contents.ends_with('\n') {
+        scrubbed.push(String::new());
+    }
+    crate::config::write_atomic(path, &scrubbed.join("\n"), true)
+}
+
+/// `gix doctor --secrets`
+pub fn handle_secrets_check(fix: bool) -> Result<()> {
+    println!("\x1b[1;36m🔎 Scanning for plaintext secrets...\x1b[0m\n");
+    let findings = scan();
+
+    if findings.is_empty() {
+        println!("\x1b[1;32m✓ No plaintext tokens or embedded credentials found.\x1b[0m");
+        return Ok(());
+    }
+
+    for finding in &findings {
+        if finding.line > 0 {
+            println!("   \x1b[1;31m✗\x1b[0m {}:{}", finding.file.display(), finding.line);
+        } else {
+            println!("   \x1b[1;31m✗\x1b[0m {}", finding.file.display());
+        }
+        println!("      {}", finding.masked);
+    }
+    println!("\n\x1b[1;33m⚠ {} secret(s) found in plain text.\x1b[0m", findings.len());
+
+    let proceed = fix
+        || (crate::settings::interactive()
+            && Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Move URL credentials to the git credential helper and scrub these files?")
+                .default(true)
+                .interact()?);
+    if !proceed {
+        println!("Run 'gix doctor --secrets --fix' to scrub them.");
+        return Ok(());
+    }
+
+    let mut scrubbed_files: Vec<&Path> = Vec::new();
+    let mut failed = 0;
+    for finding in &findings {
+        let result = match &finding.git_key {
+            // .git/config sits two levels below the repository
+            Some((key, creds)) => match finding.file.parent().and_then(|p| p.parent()) {
+                Some(repo) => migrate_git_key(repo, key, creds),
+                None => Ok(()),
+            },
+            None if finding.line > 0 && !scrubbed_files.contains(&finding.file.as_path()) => {
+                scrubbed_files.push(&finding.file);
+                scrub_file(&finding.file)
+            }
+            None if finding.line == 0 => {
+                println!(
+                    "   \x1b[1;33m⚠\x1b[0m {}: not a URL, remove it by hand ({})",
+                    finding.file.display(),
+                    finding.masked
+                );
+                Ok(())
+            }
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            failed += 1;
+            println!("   \x1b[1;31m✗\x1b[0m {}: {}", finding.file.display(), e);
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} secret(s) could not be moved; they were left in place", failed);
+    }
+    println!("\x1b[1;32m✓ Secrets scrubbed.\x1b[0m Re-run 'gix doctor --secrets' to confirm.");
+
+    Ok(())
+}
@@ -0,0 +1,350 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// A named end-to-end check run inside the sandbox
+type Scenario = (&'static str, fn(&Sandbox) -> Result<()>);
+
+const PROFILE_NAME: &str = "selftest";
+const PROFILE_EMAIL: &str = "selftest@gix.invalid";
+
+/// Scratch environment: temporary HOME plus working area, removed on drop
+struct Sandbox {
+    root: PathBuf,
+    home: PathBuf,
+    work: PathBuf,
+    exe: PathBuf,
+    /// Leave the files behind on drop (a directory the caller gave, or a `gix test-env`
+    /// the caller cleans up)
+    keep: bool,
+}
+
+impl Sandbox {
+    fn new() -> Result<Self> {
+        Self::temporary(&format!("gix-selftest-{}", std::process::id()))
+    }
+
+    /// A sandbox in a fresh directory under the system temp dir, removed on drop
+    fn temporary(name: &str) -> Result<Self> {
+        let root = std::env::temp_dir().join(name);
+        // Left behind by an earlier run that had the same process id
+        if root.exists() {
+            fs::remove_dir_all(&root)?;
+        }
+        Self::at(root, true)
+    }
+
+    /// A sandbox in `root`, which must be new or empty; only an `owned` root is removed on drop
+    fn at(root: PathBuf, owned: bool) -> Result<Self> {
+        if fs::read_dir(&root).is_ok_and(|mut entries| entries.next().is_some()) {
+            anyhow::bail!("{} is not empty; give a new or empty directory", root.display());
+        }
+        let home = root.join("home");
+        let work = root.join("work");
+        fs::create_dir_all(&home)?;
+        fs::create_dir_all(&work)?;
+
+        Ok(Sandbox {
+            root,
+            home,
+            work,
+            exe: std::env::current_exe().context("Could not locate the gix executable")?,
+            keep: !owned,
+        })
+    }
+
+    /// Variables that keep git and gix away from the real HOME and git config
+    fn env_vars(&self) -> Vec<(&'static str, PathBuf)> {
+        vec![
+            ("HOME", self.home.clone()),
+            ("USERPROFILE", self.home.clone()),
+            ("XDG_CONFIG_HOME", self.home.join(".config")),
+            ("GIT_CONFIG_GLOBAL", self.home.join(".gitconfig")),
+            ("GIT_CONFIG_NOSYSTEM", PathBuf::from("1")),
+            ("GIT_TERMINAL_PROMPT", PathBuf::from("0")),
+        ]
+    }
+
+    /// Apply the isolation environment to a command
+    fn isolate<'a>(&self, cmd: &'a mut Command, dir: &Path) -> &'a mut Command {
+        cmd.current_dir(dir)
+            .envs(self.env_vars())
+            .env_remove(crate::settings::NESTED_ENV)
+            .stdin(Stdio::null())
+    }
+
+    fn git(&self, dir: &Path, args: &[&str]) -> Result<Output> {
+        let mut cmd = Command::new("git");
+        self.isolate(&mut cmd, dir)
+            .args(args)
+            .output()
+            .with_context(|| format!("Failed to run git {}", args.join(" ")))
+    }
+
+    fn gix(&self, dir: &Path, args: &[&str]) -> Result<Output> {
+        let mut cmd = Command::new(&self.exe);
+        self.isolate(&mut cmd, dir)
+            .args(args)
+            .output()
+            .with_context(|| format!("Failed to run gix {}", args.join(" ")))
+    }
+
+    /// Run git and return trimmed stdout, failing on a non-zero exit
+    fn git_stdout(&self, dir: &Path, args: &[&str]) -> Result<String> {
+        let out = self.git(dir, args)?;
+        ensure_success(&out, &format!("git {}", args.join(" ")))?;
+        Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        if !self.keep {
+            fs::remove_dir_all(&self.root).ok();
+        }
+    }
+}
+
+/// Turn a failed process into an error carrying its stderr
+fn ensure_success(out: &Output, what: &str) -> Result<()> {
+    if !out.status.success() {
+        anyhow::bail!(
+            "{} exited with {}: {}",
+            what,
+            out.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Write the fixture profile and a dummy key into the scratch HOME
+fn setup_profile(sb: &Sandbox) -> Result<()> {
+    let ssh_dir = sb.home.join(".ssh");
+    fs::create_dir_all(&ssh_dir)?;
+    let key = ssh_dir.join("id_selftest");
+    fs::write(&key, "not a real key\n")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&ssh_dir, fs::Permissions::from_mode(0o700))?;
+        fs::set_permissions(&key, fs::Permissions::from_mode(0o600))?;
+    }
+
+    let config = serde_json::json!({
+        "profiles": [{
+            "profile_name": PROFILE_NAME,
+            "name": "gix selftest",
+            "email": PROFILE_EMAIL,
+            "auth": { "SSH": { "key_path": key.to_string_lossy() } }
+        }],
+        "intercepted_commands": ["pull", "push", "fetch", "clone", "commit"],
+        "default_profile": PROFILE_NAME
+    });
+
+    let gix_dir = sb.home.join(".gix");
+    fs::create_dir_all(&gix_dir)?;
+    fs::write(gix_dir.join("config.json"), serde_json::to_string_pretty(&config)?)?;
+    Ok(())
+}
+
+/// `gix use` writes identity, auth and the local selection
+fn scenario_profile_apply(sb: &Sandbox) -> Result<()> {
+    let repo = sb.work.join("repo");
+    fs::create_dir_all(&repo)?;
+    sb.git_stdout(&repo, &["init", "-q"])?;
+
+    let out = sb.gix(&repo, &["use", PROFILE_NAME])?;
+    ensure_success(&out, "gix use")?;
+
+    let email = sb.git_stdout(&repo, &["config", "--local", "user.email"])?;
+    if email != PROFILE_EMAIL {
+        anyhow::bail!("user.email is '{}', expected '{}'", email, PROFILE_EMAIL);
+    }
+
+    let ssh_command = sb.git_stdout(&repo, &["config", "--local", "core.sshCommand"])?;
+    if !ssh_command.contains("id_selftest") {
+        anyhow::bail!("core.sshCommand does not use the profile key: {}", ssh_command);
+    }
+
+    if !repo.join(".gix").join("config.json").exists() {
+        anyhow::bail!(".gix/config.json was not written");
+    }
+    Ok(())
+}
+
+/// Intercepted commits carry the profile identity even without local git config
+fn scenario_interception(sb: &Sandbox) -> Result<()> {
+    let repo = sb.work.join("repo");
+    sb.git(&repo, &["config", "--local", "--unset", "user.email"])?;
+    sb.git(&repo, &["config", "--local", "--unset", "user.name"])?;
+
+    let out = sb.gix(&repo, &["commit", "--allow-empty", "-q", "-m", "selftest"])?;
+    ensure_success(&out, "gix commit")?;
+
+    let author = sb.git_stdout(&repo, &["log", "-1", "--format=%ae"])?;
+    if author != PROFILE_EMAIL {
+        anyhow::bail!("commit author is '{}', expected '{}'", author, PROFILE_EMAIL);
+    }
+    Ok(())
+}
+
+/// Intercepted clones are configured with the profile afterwards
+fn scenario_clone_post_config(sb: &Sandbox) -> Result<()> {
+    let remote = sb.work.join("remote.git");
+    sb.git_stdout(
+        &sb.work,
+        &["clone", "-q", "--bare", "repo", &remote.to_string_lossy()],
+    )?;
+
+    let out = sb.gix(&sb.work, &["clone", "-q", &remote.to_string_lossy(), "cloned"])?;
+    ensure_success(&out, "gix clone")?;
+
+    let cloned = sb.work.join("cloned");
+    let local = fs::read_to_string(cloned.join(".gix").join("config.json"))
+        .context("cloned repository has no .gix/config.json")?;
+    if !local.contains(PROFILE_NAME) {
+        anyhow::bail!("cloned repository is not bound to the profile");
+    }
+
+    let email = sb.git_stdout(&cloned, &["config", "--local", "user.email"])?;
+    if email != PROFILE_EMAIL {
+        anyhow::bail!("cloned user.email is '{}', expected '{}'", email, PROFILE_EMAIL);
+    }
+    Ok(())
+}
+
+/// The shell hook script is emitted and its check re-applies a drifted identity
+fn scenario_shell_hook(sb: &Sandbox) -> Result<()> {
+    for shell in ["bash", "zsh", "fish"] {
+        let out = sb.gix(&sb.work, &["hook", "shell", shell])?;
+        ensure_success(&out, &format!("gix hook shell {}", shell))?;
+        if !String::from_utf8_lossy(&out.stdout).contains("gix hook check") {
+            anyhow::bail!("{} hook does not call 'gix hook check'", shell);
+        }
+    }
+
+    let repo = sb.work.join("repo");
+    sb.git_stdout(&repo, &["config", "--local", "user.email", "drifted@example.com"])?;
+
+    let out = sb.gix(&repo, &["hook", "check", "--apply"])?;
+    ensure_success(&out, "gix hook check --apply")?;
+
+    let email = sb.git_stdout(&repo, &["config", "--local", "user.email"])?;
+    if email != PROFILE_EMAIL {
+        anyhow::bail!("hook check left user.email as '{}'", email);
+    }
+    Ok(())
+}
+
+/// Handle the 'gix selftest' command
+pub fn handle_selftest() -> Result<()> {
+    println!("\x1b[1;36m🧪 gix Selftest - End-to-End Checks\x1b[0m\n");
+
+    let sb = Sandbox::new()?;
+    println!("   Sandbox: {}\n", sb.root.display());
+    setup_profile(&sb)?;
+
+    let scenarios: Vec<Scenario> = vec![
+        ("Profile apply (gix use)", scenario_profile_apply),
+        ("Command interception (gix commit)", scenario_interception),
+        ("Clone post-configuration (gix clone)", scenario_clone_post_config),
+        ("Shell hook installation (gix hook)", scenario_shell_hook),
+    ];
+
+    let mut failed = 0;
+    for (name, scenario) in &scenarios {
+        print!("   {}... ", name);
+        match scenario(&sb) {
+            Ok(()) => println!("\x1b[1;32m✓\x1b[0m"),
+            Err(e) => {
+                failed += 1;
+                println!("\x1b[1;31m✗\x1b[0m {}", e);
+            }
+        }
+    }
+
+    println!();
+    if failed > 0 {
+        anyhow::bail!("{} of {} scenario(s) failed", failed, scenarios.len());
+    }
+
+    println!("\x1b[1;32m✓ All {} scenarios passed!\x1b[0m", scenarios.len());
+    Ok(())
+}
+
+/// Profiles, repositories and remotes of a `gix test-env`
+#[cfg(feature = "test-env")]
+fn setup_test_env(sb: &Sandbox) -> Result<PathBuf> {
+    setup_profile(sb)?;
+
+    // A second fixture profile, authenticating with a token from the environment
+    let config_path = sb.home.join(".gix").join("config.json");
+    let mut config: serde_json::Value = serde_json::from_str(&fs::read_to_string(&config_path)?)?;
+    if let Some(profiles) = config["profiles"].as_array_mut() {
+        profiles.push(serde_json::json!({
+            "profile_name": "selftest-token",
+            "name": "gix selftest token",
+            "email": "token@gix.invalid",
+            "auth": { "Token": { "token": "env:GIX_TEST_TOKEN" } }
+        }));
+    }
+    fs::write(&config_path, serde_json::to_string_pretty(&config)?)?;
+
+    // A bare remote with one commit, so clone/fetch/push have something to talk to
+    let seed = sb.work.join("seed");
+    fs::create_dir_all(&seed)?;
+    sb.git_stdout(&seed, &["init", "-q", "-b", "main"])?;
+    sb.git_stdout(
+        &seed,
+        &["-c", "user.name=seed", "-c", "user.email=seed@gix.invalid", "commit", "-q", "--allow-empty", "-m", "initial"],
+    )?;
+    let remote = sb.work.join("remote.git");
+    sb.git_stdout(&sb.work, &["clone", "-q", "--bare", "seed", &remote.to_string_lossy()])?;
+    fs::remove_dir_all(&seed)?;
+    Ok(remote)
+}
+
+/// Handle the hidden 'gix test-env' command (built with the `test-env` feature)
+///
+/// With a command, runs it inside a fresh sandbox and removes a temporary sandbox
+/// afterwards. Without one, creates the sandbox, keeps it, and prints shell exports for it.
+/// A `dir` must be new or empty and is never removed.
+#[cfg(feature = "test-env")]
+pub fn handle_test_env(dir: Option<PathBuf>, command: Vec<String>) -> Result<()> {
+    let mut sb = match dir {
+        Some(dir) => Sandbox::at(dir, false)?,
+        None => Sandbox::temporary(&format!("gix-test-env-{}", std::process::id()))?,
+    };
+    let remote = setup_test_env(&sb)?;
+
+    let Some((program, args)) = command.split_first() else {
+        sb.keep = true;
+        for (name, value) in sb.env_vars() {
+            println!("export {}='{}'", name, value.display());
+        }
+        println!("export GIX_TEST_ROOT='{}'", sb.root.display());
+        println!("export GIX_TEST_REMOTE='{}'", remote.display());
+        println!("export GIX_BIN='{}'", sb.exe.display());
+        println!("cd '{}'", sb.work.display());
+        eprintln!("Sandbox kept at {}; remove it when done.", sb.root.display());
+        return Ok(());
+    };
+
+    let status = Command::new(program)
+        .args(args)
+        .current_dir(&sb.work)
+        .envs(sb.env_vars())
+        .env_remove(crate::settings::NESTED_ENV)
+        .env("GIX_TEST_ROOT", &sb.root)
+        .env("GIX_TEST_REMOTE", &remote)
+        .env("GIX_BIN", &sb.exe)
+        .status()
+        .with_context(|| format!("Failed to run {}", program))?;
+    drop(sb);
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
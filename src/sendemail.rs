@@ -0,0 +1,171 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::profile::Profile;
+
+/// Transport security for the SMTP connection, as `sendemail.smtpEncryption` names it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpEncryption {
+    /// STARTTLS, usually on port 587
+    Tls,
+    /// Implicit TLS, usually on port 465
+    Ssl,
+}
+
+impl SmtpEncryption {
+    pub fn as_git_value(&self) -> &'static str {
+        match self {
+            SmtpEncryption::Tls => "tls",
+            SmtpEncryption::Ssl => "ssl",
+        }
+    }
+}
+
+/// Outgoing mail settings for `git send-email`
+///
+/// The SMTP password is left to git's credential helper, like HTTPS tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SendEmailConfig {
+    pub smtp_server: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smtp_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smtp_user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<SmtpEncryption>,
+    /// Sender address; defaults to the profile identity ("Name <email>")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+/// Git config keys gix manages for send-email
+pub const SENDEMAIL_KEYS: [&str; 5] = [
+    "sendemail.smtpServer",
+    "sendemail.smtpServerPort",
+    "sendemail.smtpUser",
+    "sendemail.smtpEncryption",
+    "sendemail.from",
+];
+
+impl SendEmailConfig {
+    /// Git config values for sending mail as `profile`
+    pub fn git_config(&self, profile: &Profile) -> Vec<(String, String)> {
+        let mut values = vec![("sendemail.smtpServer".to_string(), self.smtp_server.clone())];
+        if let Some(port) = self.smtp_port {
+            values.push(("sendemail.smtpServerPort".to_string(), port.to_string()));
+        }
+        if let Some(user) = &self.smtp_user {
+            values.push(("sendemail.smtpUser".to_string(), user.clone()));
+        }
+        if let Some(encryption) = self.encryption {
+            values.push(("sendemail.smtpEncryption".to_string(), encryption.as_git_value().to_string()));
+        }
+        let from = self
+            .from
+            .clone()
+            .unwrap_or_else(|| format!("{} <{}>", profile.name, profile.email));
+        values.push(("sendemail.from".to_string(), from));
+        values
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.smtp_server.is_empty() || self.smtp_server.contains(char::is_whitespace) {
+            anyhow::bail!("Invalid SMTP server: '{}'", self.smtp_server);
+        }
+        if let Some(from) = self.from.as_ref().filter(|f| !f.contains('@')) {
+            anyhow::bail!("Invalid send-email sender: '{}'", from);
+        }
+        Ok(())
+    }
+
+    /// Short description for listings, e.g. "smtp.example.com:587 (tls) as me@example.com"
+    pub fn describe(&self) -> String {
+        let mut text = self.smtp_server.clone();
+        if let Some(port) = self.smtp_port {
+            text.push_str(&format!(":{}", port));
+        }
+        if let Some(encryption) = self.encryption {
+            text.push_str(&format!(" ({})", encryption.as_git_value()));
+        }
+        if let Some(user) = &self.smtp_user {
+            text.push_str(&format!(" as {}", user));
+        }
+        text
+    }
+}
+
+/// Whether a `sendemail.smtpServer` value was written by one of the gix profiles
+pub fn is_managed_smtp_server(config: &Config, value: &str) -> bool {
+    config
+        .profiles
+        .iter()
+        .filter_map(|p| p.sendemail.as_ref())
+        .any(|s| s.smtp_server == value)
+}
+
+/// Ask for the profile's send-email settings; an empty server turns them off
+pub fn ask_sendemail(current: Option<&SendEmailConfig>, name: &str, email: &str) -> Result<Option<SendEmailConfig>> {
+    let theme = ColorfulTheme::default();
+    let server: String = Input::with_theme(&theme)
+        .with_prompt("SMTP server (e.g. smtp.gmail.com, empty to disable send-email)")
+        .default(current.map(|c| c.smtp_server.clone()).unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()?;
+    let server = server.trim().to_string();
+    if server.is_empty() {
+        return Ok(None);
+    }
+
+    let options = ["STARTTLS (tls, port 587)", "SSL/TLS (ssl, port 465)", "None"];
+    let default = match current.and_then(|c| c.encryption) {
+        Some(SmtpEncryption::Tls) | None => 0,
+        Some(SmtpEncryption::Ssl) => 1,
+    };
+    let encryption = match Select::with_theme(&theme)
+        .with_prompt("Encryption")
+        .items(&options)
+        .default(default)
+        .interact()?
+    {
+        0 => Some(SmtpEncryption::Tls),
+        1 => Some(SmtpEncryption::Ssl),
+        _ => None,
+    };
+
+    let port: String = Input::with_theme(&theme)
+        .with_prompt("SMTP port (empty for the encryption's default)")
+        .default(current.and_then(|c| c.smtp_port).map(|p| p.to_string()).unwrap_or_default())
+        .allow_empty(true)
+        .validate_with(|input: &String| -> Result<(), &str> {
+            if input.trim().is_empty() || input.trim().parse::<u16>().is_ok() {
+                Ok(())
+            } else {
+                Err("Enter a port number")
+            }
+        })
+        .interact_text()?;
+
+    let user: String = Input::with_theme(&theme)
+        .with_prompt("SMTP user (empty for none)")
+        .default(current.and_then(|c| c.smtp_user.clone()).unwrap_or_else(|| email.to_string()))
+        .allow_empty(true)
+        .interact_text()?;
+
+    let identity = format!("{} <{}>", name, email);
+    let from: String = Input::with_theme(&theme)
+        .with_prompt("Send as")
+        .default(current.and_then(|c| c.from.clone()).unwrap_or_else(|| identity.clone()))
+        .interact_text()?;
+
+    Ok(Some(SendEmailConfig {
+        smtp_server: server,
+        smtp_port: port.trim().parse().ok(),
+        smtp_user: Some(user.trim().to_string()).filter(|u| !u.is_empty()),
+        encryption,
+        // Following the profile identity keeps the sender right after a rename
+        from: Some(from.trim().to_string()).filter(|f| *f != identity),
+    }))
+}
@@ -0,0 +1,495 @@
+use anyhow::{Context, Result};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::OnceLock;
+
+use crate::cli::ConfigAction;
+use crate::config::{get_global_config_path, load_config, save_config, write_atomic, Config};
+
+/// Release stream followed by 'gix update'
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Include pre-releases
+    Prerelease,
+}
+
+/// Where the messages gix adds around an intercepted git command go
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BannerMode {
+    Stdout,
+    /// Keeps stdout byte-identical to plain git, so gix works as a `git` alias
+    #[default]
+    Stderr,
+    /// Only warnings, on stderr
+    Off,
+}
+
+/// What happens after choosing a profile for a repository without one
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PersistPrompt {
+    /// Ask every time
+    #[default]
+    Ask,
+    /// Configure the repository without asking
+    Always,
+    /// Never ask and never configure
+    Never,
+    /// Ask once per repository and remember the answer
+    Once,
+}
+
+/// Whether gix's messages keep their ANSI colors
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    /// Only on a terminal, and never with NO_COLOR set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl BannerMode {
+    fn parse(value: &str) -> Option<BannerMode> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "stdout" => Some(BannerMode::Stdout),
+            "stderr" => Some(BannerMode::Stderr),
+            "off" | "none" | "quiet" => Some(BannerMode::Off),
+            _ => None,
+        }
+    }
+}
+
+/// Global settings exposed through 'gix settings get/set'
+const SETTINGS: [(&str, &str); 13] = [
+    ("default_profile", "profile used when a repository has none (\"none\" to clear)"),
+    ("intercepted_commands", "git commands gix wraps (comma separated)"),
+    ("credential_mode", "how tokens reach git: auto, approve, gcm or helper ('gix credential-helper install')"),
+    ("logging", "write intercepted commands to ~/.gix/usage.log (true/false)"),
+    ("update_channel", "releases offered by 'gix update': stable or prerelease"),
+    ("detection_order", "profile detection precedence: local, remote, default, email, rule"),
+    ("ssh_program", "ssh executable for generated ssh commands (\"none\" for plain ssh)"),
+    ("banner", "where messages around intercepted commands go: stderr (default), stdout or off"),
+    ("telemetry", "send anonymous weekly command counts (false by default; see 'gix telemetry show')"),
+    ("persist_prompt", "after picking a profile in an unconfigured repository: ask, always, never or once (per repository)"),
+    ("ssh_aliases", "SSH profiles use host aliases kept in ~/.ssh/config (e.g. github.com-work) instead of core.sshCommand"),
+    ("suggestions", "hint after intercepted commands when 'gix suggest' has rules to propose (true/false)"),
+    ("color", "colors in gix's messages: auto (terminals without NO_COLOR), always or never"),
+];
+
+fn check_key(key: &str) -> Result<()> {
+    if SETTINGS.iter().any(|(k, _)| *k == key) {
+        return Ok(());
+    }
+    let known: Vec<&str> = SETTINGS.iter().map(|(k, _)| *k).collect();
+    anyhow::bail!("Unknown setting '{}'. Available: {}", key, known.join(", "))
+}
+
+/// Human/script friendly rendering of a setting value
+fn render(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(render).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+/// Parse a command-line value into the JSON shape of the current setting
+fn parse_value(current: &Value, raw: &str) -> Result<Value> {
+    Ok(match current {
+        Value::Bool(_) => Value::Bool(
+            raw.parse()
+                .with_context(|| format!("Expected true or false, got '{}'", raw))?,
+        ),
+        Value::Array(_) => Value::Array(
+            raw.split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| Value::String(s.to_string()))
+                .collect(),
+        ),
+        _ if raw.is_empty() || raw == "none" => Value::Null,
+        _ => Value::String(raw.to_string()),
+    })
+}
+
+/// Check settings whose validity depends on the rest of the config
+fn check_setting(config: &Config, key: &str) -> Result<()> {
+    match key {
+        "default_profile" => {
+            if let Some(name) = &config.default_profile {
+                if config.find_profile(name).is_none() {
+                    anyhow::bail!("Profile '{}' not found", name);
+                }
+            }
+        }
+        "intercepted_commands" if config.intercepted_commands.is_empty() => {
+            anyhow::bail!("At least one command must be intercepted");
+        }
+        "detection_order" if config.detection_order.is_empty() => {
+            anyhow::bail!("At least one detection source is required");
+        }
+        "ssh_program" => {
+            if let Some(program) = &config.ssh_program {
+                crate::ssh::check_ssh_program(program).map_err(|e| anyhow::anyhow!(e))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Every problem in a config that would be persisted, as one message each
+pub fn validate_config(config: &Config) -> Vec<String> {
+    let mut errors = vec![];
+
+    for (i, profile) in config.profiles.iter().enumerate() {
+        if let Err(e) = profile.validate() {
+            errors.push(format!("profiles[{}] ({}): {}", i, profile.profile_name, e));
+        }
+        if config.profiles[..i].iter().any(|p| p.is_named(&profile.profile_name)) {
+            errors.push(format!("profiles[{}]: duplicate profile name '{}'", i, profile.profile_name));
+        }
+    }
+
+    if let Some(name) = &config.default_profile {
+        if config.find_profile(name).is_none() {
+            errors.push(format!("default_profile: profile '{}' not found", name));
+        }
+    }
+
+    for (i, rule) in config.rules.iter().enumerate() {
+        if config.find_profile(rule.profile()).is_none() {
+            errors.push(format!("rules[{}]: profile '{}' not found", i, rule.profile()));
+        }
+    }
+
+    if config.intercepted_commands.is_empty() {
+        errors.push("intercepted_commands: at least one command must be intercepted".to_string());
+    }
+
+    errors
+}
+
+/// Editor from $VISUAL / $EDITOR, which may carry arguments (e.g. "code --wait")
+fn editor_command() -> Vec<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    editor.split_whitespace().map(|s| s.to_string()).collect()
+}
+
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = editor_command();
+    let status = Command::new(&editor[0])
+        .args(&editor[1..])
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to launch editor '{}'", editor.join(" ")))?;
+    if !status.success() {
+        anyhow::bail!("Editor exited with {}", status);
+    }
+    Ok(())
+}
+
+/// Open the global config in an editor and save it only once it is valid
+fn edit_config() -> Result<()> {
+    let path = get_global_config_path()?;
+    let original = serde_json::to_string_pretty(&load_config()?)?;
+
+    // Edit a private copy so the real config is never left half-written or broken; the
+    // copy holds tokens, so it is removed however the edit ends
+    let draft = path.with_extension("edit.json");
+    let result = write_atomic(&draft, &original, true).and_then(|_| edit_draft(&path, &draft, &original));
+    let _ = fs::remove_file(&draft);
+    result
+}
+
+/// Edit loop of `edit_config`: save the draft once it is valid, or give up
+fn edit_draft(path: &Path, draft: &Path, original: &str) -> Result<()> {
+    loop {
+        run_editor(draft)?;
+        let content = fs::read_to_string(draft)?;
+        if content.trim() == original.trim() {
+            println!("\x1b[1;33m⚠ No changes made.\x1b[0m");
+            return Ok(());
+        }
+
+        let errors = match serde_json::from_str::<Config>(&content) {
+            Ok(config) => {
+                let errors = validate_config(&config);
+                if errors.is_empty() {
+                    save_config(&config)?;
+                    println!("\x1b[1;32m✓ Configuration saved.\x1b[0m");
+                    return Ok(());
+                }
+                errors
+            }
+            // serde_json reports the line and column of the problem
+            Err(e) => vec![e.to_string()],
+        };
+
+        eprintln!("\x1b[1;31m✗ The edited configuration is invalid:\x1b[0m");
+        for error in &errors {
+            eprintln!("   • {}", error);
+        }
+
+        let retry = interactive()
+            && Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Edit again?")
+                .default(true)
+                .interact()?;
+        if !retry {
+            anyhow::bail!("Configuration not saved; {} is unchanged", path.display());
+        }
+    }
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static BANNER: OnceLock<BannerMode> = OnceLock::new();
+
+/// Drop gix's informational messages for the rest of this process
+/// (`--quiet`, or `-q`/`--quiet` given to the git command)
+pub fn set_quiet() {
+    QUIET.store(true, Ordering::Relaxed);
+}
+
+/// Banner destination: quiet flags, then GIX_BANNER, then the `banner` setting
+pub fn banner_mode() -> BannerMode {
+    if QUIET.load(Ordering::Relaxed) {
+        return BannerMode::Off;
+    }
+    *BANNER.get_or_init(|| {
+        std::env::var("GIX_BANNER")
+            .ok()
+            .and_then(|v| BannerMode::parse(&v))
+            .unwrap_or_else(|| load_config().map(|c| c.banner).unwrap_or_default())
+    })
+}
+
+/// Print an informational message around an intercepted command
+pub fn banner(message: impl std::fmt::Display) {
+    match banner_mode() {
+        BannerMode::Stdout => println!("{}", message),
+        BannerMode::Stderr => eprintln!("{}", message),
+        BannerMode::Off => {}
+    }
+}
+
+/// Print a warning around an intercepted command: it may leave stdout, but is never dropped
+pub fn banner_warning(message: impl std::fmt::Display) {
+    match banner_mode() {
+        BannerMode::Stdout => println!("{}", message),
+        BannerMode::Stderr | BannerMode::Off => eprintln!("{}", message),
+    }
+}
+
+static COLOR: OnceLock<ColorMode> = OnceLock::new();
+static COLOR_LOADING: AtomicBool = AtomicBool::new(false);
+
+/// The `color` setting, or None for a message printed while it is being loaded
+fn color_mode() -> Option<ColorMode> {
+    if let Some(mode) = COLOR.get() {
+        return Some(*mode);
+    }
+    if COLOR_LOADING.swap(true, Ordering::Relaxed) {
+        return None;
+    }
+    Some(*COLOR.get_or_init(|| load_config().map(|c| c.color).unwrap_or_default()))
+}
+
+/// A message as it should reach stdout (or stderr): without its ANSI colors when the
+/// `color` setting, NO_COLOR or a redirected stream turn them off
+pub fn paint(message: String, stderr: bool) -> String {
+    if !message.contains('\x1b') {
+        return message;
+    }
+    let colored = match color_mode() {
+        Some(ColorMode::Always) => true,
+        Some(ColorMode::Never) => false,
+        Some(ColorMode::Auto) | None => {
+            std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                && if stderr {
+                    std::io::IsTerminal::is_terminal(&std::io::stderr())
+                } else {
+                    std::io::IsTerminal::is_terminal(&std::io::stdout())
+                }
+        }
+    };
+    if colored {
+        message
+    } else {
+        strip_ansi(&message)
+    }
+}
+
+/// Drop ANSI escape sequences (`ESC [ ... final byte`)
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        if chars.clone().next() == Some('[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Print machine-readable JSON instead of colored text (`--json`)
+pub fn set_json() {
+    JSON.store(true, Ordering::Relaxed);
+}
+
+/// Whether `--json` was given; commands without JSON output ignore it
+pub fn json_output() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Print a value as pretty JSON on stdout
+pub fn print_json(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+static NO_INPUT: AtomicBool = AtomicBool::new(false);
+
+/// Forbid prompts for the rest of this process (`--no-input`)
+pub fn set_no_input() {
+    NO_INPUT.store(true, Ordering::Relaxed);
+}
+
+/// Whether gix must not prompt: `--no-input`, GIX_NO_INPUT or a CI environment
+pub fn no_input() -> bool {
+    let set = |name: &str| {
+        std::env::var(name).is_ok_and(|v| !v.is_empty() && v != "0" && !v.eq_ignore_ascii_case("false"))
+    };
+    NO_INPUT.load(Ordering::Relaxed) || set("GIX_NO_INPUT") || set("CI")
+}
+
+/// Whether gix may prompt: stdin is a terminal and prompts are not forbidden
+pub fn interactive() -> bool {
+    !no_input() && std::io::IsTerminal::is_terminal(&std::io::stdin())
+}
+
+/// Make the user type `expected` before a destructive action; a slip of the Enter key cancels
+pub fn confirm_typed(expected: &str) -> Result<bool> {
+    if !interactive() {
+        anyhow::bail!("This action needs a typed confirmation; run it in a terminal");
+    }
+    let typed: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Type '{}' to confirm", expected))
+        .allow_empty(true)
+        .interact_text()?;
+    Ok(typed.trim() == expected)
+}
+
+/// Whether GIX_DISABLE asks for gix to stay out of the way
+pub fn disabled_by_env() -> bool {
+    std::env::var("GIX_DISABLE").is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Set for every process gix starts; the value counts the gix invocations above it
+pub const NESTED_ENV: &str = "GIX_ACTIVE";
+
+/// A deeper chain is gix calling itself in a loop rather than a hook calling git
+const MAX_NESTING: u32 = 4;
+
+static NESTING: AtomicU32 = AtomicU32::new(0);
+
+/// Note how deeply this invocation is nested and mark everything it starts
+///
+/// Hooks and scripts run by an intercepted command inherit the marker, so a `git` that
+/// resolves back to gix (an alias or a shim on PATH) is recognised as re-entry.
+pub fn enter_nesting() -> Result<()> {
+    let depth = std::env::var(NESTED_ENV).ok().and_then(|v| v.parse::<u32>().ok()).unwrap_or(0);
+    if depth >= MAX_NESTING {
+        anyhow::bail!(
+            "gix was started {} times inside itself; 'git' probably resolves back to gix. Unset {} or fix PATH.",
+            depth,
+            NESTED_ENV
+        );
+    }
+    NESTING.store(depth, Ordering::Relaxed);
+    std::env::set_var(NESTED_ENV, (depth + 1).to_string());
+    Ok(())
+}
+
+/// Whether this gix was started, directly or not, by another gix
+pub fn nested() -> bool {
+    NESTING.load(Ordering::Relaxed) > 0
+}
+
+/// Handle 'gix on' / 'gix off'
+pub fn handle_interception_toggle(enabled: bool) -> Result<()> {
+    let mut config = load_config()?;
+    config.interception_disabled = !enabled;
+    save_config(&config)?;
+
+    if enabled {
+        println!("\x1b[1;32m▶ Interception on: git commands use your profiles again.\x1b[0m");
+        if disabled_by_env() {
+            println!("\x1b[1;33m⚠ GIX_DISABLE is set in this shell and still bypasses gix.\x1b[0m");
+        }
+    } else {
+        println!("\x1b[1;33m⏸  Interception off: git commands pass straight through.\x1b[0m");
+        println!("   Run 'gix on' to resume.");
+    }
+    Ok(())
+}
+
+/// Handle the 'gix settings' command
+pub fn handle_config_command(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::List => {
+            let config = serde_json::to_value(load_config()?)?;
+            for (key, description) in SETTINGS {
+                println!("{} = {}", key, render(&config[key]));
+                println!("    \x1b[2m{}\x1b[0m", description);
+            }
+        }
+        ConfigAction::Edit => edit_config()?,
+        ConfigAction::Get { key } => {
+            check_key(&key)?;
+            let config = serde_json::to_value(load_config()?)?;
+            println!("{}", render(&config[key.as_str()]));
+        }
+        ConfigAction::Set { key, value } => {
+            check_key(&key)?;
+            let mut json = serde_json::to_value(load_config()?)?;
+            json[key.as_str()] = parse_value(&json[key.as_str()], &value)?;
+
+            // Round-trip through the typed config to reject invalid enum values
+            let config: Config = serde_json::from_value(json)
+                .map_err(|e| anyhow::anyhow!("Invalid value for {}: {}", key, e))?;
+            check_setting(&config, &key)?;
+
+            save_config(&config)?;
+            println!("\x1b[1;32m✓ {} = {}\x1b[0m", key, render(&serde_json::to_value(&config)?[key.as_str()]));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use anyhow::Result;
+use std::process::Command;
+
+use crate::cli::Shell;
+use crate::config::load_config;
+use crate::git::{apply_local_config_to_dir, detect_profile, get_git_root, is_inside_git_repo};
+
+/// Shell snippet that runs `gix hook check` whenever the working directory changes
+pub fn hook_script(shell: Shell, apply: bool) -> String {
+    let check = if apply { "gix hook check --apply" } else { "gix hook check" };
+
+    match shell {
+        Shell::Bash => format!(
+            r#"# gix shell hook (bash) - add to ~/.bashrc:
+#   eval "$(gix hook shell bash)"
+_gix_hook() {{
+  if [[ "$PWD" != "$_GIX_LAST_PWD" ]]; then
+    _GIX_LAST_PWD="$PWD"
+    command {check} 2>&1 >/dev/null
+  fi
+}}
+if [[ ";${{PROMPT_COMMAND[*]:-}};" != *";_gix_hook;"* ]]; then
+  PROMPT_COMMAND="_gix_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}"
+fi
+"#
+        ),
+        Shell::Zsh => format!(
+            r#"# gix shell hook (zsh) - add to ~/.zshrc:
+#   eval "$(gix hook shell zsh)"
+_gix_hook() {{
+  command {check} 2>&1 >/dev/null
+}}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _gix_hook
+_gix_hook
+"#
+        ),
+        Shell::Fish => format!(
+            r#"# gix shell hook (fish) - add to ~/.config/fish/config.fish:
+#   gix hook shell fish | source
+function __gix_hook --on-variable PWD
+    command {check} 2>&1 >/dev/null
+end
+__gix_hook
+"#
+        ),
+    }
+}
+
+/// Handle the 'gix hook shell' command
+pub fn handle_hook_shell(shell: Shell, apply: bool) -> Result<()> {
+    print!("{}", hook_script(shell, apply));
+    Ok(())
+}
+
+/// Handle the 'gix hook check' command run by the shell hook
+///
+/// Never prompts: compares the resolved profile with the effective git identity
+/// and either applies the profile silently or warns on stderr.
+pub fn handle_hook_check(apply: bool) -> Result<()> {
+    if !is_inside_git_repo() {
+        return Ok(());
+    }
+
+    // A broken config should not spam every prompt
+    let Ok(config) = load_config() else {
+        return Ok(());
+    };
+    let Some(profile) = detect_profile(&config) else {
+        return Ok(());
+    };
+
+    let current = Command::new("git")
+        .args(["config", "user.email"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+
+    if profile.owns_email(&current) {
+        return Ok(());
+    }
+
+    if apply {
+        if let Some(root) = get_git_root() {
+            crate::history::track(&root, &profile.profile_name, || apply_local_config_to_dir(profile, &root))?;
+        }
+    } else if current.is_empty() {
+        eprintln!(
+            "\x1b[1;33mgix: no git identity set here; profile '{}' expects {}. Run 'gix use {}'.\x1b[0m",
+            profile.profile_name, profile.email, profile.profile_name
+        );
+    } else {
+        eprintln!(
+            "\x1b[1;33mgix: git identity is {} but profile '{}' expects {}. Run 'gix use {}'.\x1b[0m",
+            current, profile.profile_name, profile.email, profile.profile_name
+        );
+    }
+
+    Ok(())
+}
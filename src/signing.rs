@@ -0,0 +1,433 @@
+use anyhow::{Context, Result};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::config::Config;
+use crate::managed_block::{self, Position};
+use crate::profile::Profile;
+
+/// Kind of key a profile signs with
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningFormat {
+    #[default]
+    Ssh,
+    /// GnuPG key, possibly on a smartcard
+    Openpgp,
+}
+
+impl SigningFormat {
+    fn is_default(&self) -> bool {
+        *self == SigningFormat::Ssh
+    }
+}
+
+/// Commit signing configuration for a profile
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SigningConfig {
+    /// Path to the SSH signing key (private key; the `.pub` is used for git),
+    /// or the key ID / fingerprint for OpenPGP
+    pub key_path: String,
+    /// Sign every commit and tag automatically
+    #[serde(default = "default_true")]
+    pub sign_commits: bool,
+    #[serde(default, skip_serializing_if = "SigningFormat::is_default")]
+    pub format: SigningFormat,
+    /// The OpenPGP key lives on a smartcard (YubiKey, Nitrokey...) served by scdaemon
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub smartcard: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl SigningConfig {
+    /// Value handed to git as `user.signingkey`
+    pub fn git_signing_key(&self) -> String {
+        match self.format {
+            SigningFormat::Ssh => self.public_key_path(),
+            SigningFormat::Openpgp => self.key_path.clone(),
+        }
+    }
+
+    /// Public key path of an SSH signing key
+    pub fn public_key_path(&self) -> String {
+        let key = crate::ssh::translate_key_path(&self.key_path);
+        if key.ends_with(".pub") {
+            key
+        } else {
+            format!("{}.pub", key)
+        }
+    }
+}
+
+/// Signing behaviour a repository can pin in .gix/config.json
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningPin {
+    /// Sign every commit and tag (needs a profile with a signing key)
+    Always,
+    /// Never sign, even with a profile that signs by default
+    Never,
+}
+
+/// Git config keys gix manages for commit signing
+pub const SIGNING_KEYS: [&str; 5] = [
+    "gpg.format",
+    "user.signingkey",
+    "commit.gpgsign",
+    "tag.gpgsign",
+    "gpg.ssh.allowedSignersFile",
+];
+
+/// Markers delimiting the gix-managed block in allowed_signers
+const BLOCK_START: &str = "# >>> gix managed signers >>>";
+const BLOCK_END: &str = "# <<< gix managed signers <<<";
+
+/// Git config values that enable signing for a profile
+pub fn signing_git_config(profile: &Profile) -> Vec<(String, String)> {
+    let Some(signing) = &profile.signing else {
+        return vec![];
+    };
+
+    let sign = if signing.sign_commits { "true" } else { "false" };
+    let format = match signing.format {
+        SigningFormat::Ssh => "ssh",
+        SigningFormat::Openpgp => "openpgp",
+    };
+    let mut values = vec![
+        ("gpg.format".to_string(), format.to_string()),
+        ("user.signingkey".to_string(), signing.git_signing_key()),
+        ("commit.gpgsign".to_string(), sign.to_string()),
+        ("tag.gpgsign".to_string(), sign.to_string()),
+    ];
+    if signing.format != SigningFormat::Ssh {
+        return values;
+    }
+    if let Ok(path) = allowed_signers_path() {
+        values.push((
+            "gpg.ssh.allowedSignersFile".to_string(),
+            path.to_string_lossy().into_owned(),
+        ));
+    }
+    values
+}
+
+/// Signing values for a profile, adjusted by the repository's pinned mode
+pub fn pinned_signing_git_config(profile: &Profile, pin: Option<SigningPin>) -> Vec<(String, String)> {
+    let mut values = signing_git_config(profile);
+    match pin {
+        None => {}
+        Some(SigningPin::Always) => {
+            for (key, value) in values.iter_mut() {
+                if key == "commit.gpgsign" || key == "tag.gpgsign" {
+                    *value = "true".to_string();
+                }
+            }
+        }
+        Some(SigningPin::Never) => {
+            values = vec![
+                ("commit.gpgsign".to_string(), "false".to_string()),
+                ("tag.gpgsign".to_string(), "false".to_string()),
+            ];
+        }
+    }
+    values
+}
+
+/// Check whether a `user.signingkey` value belongs to one of the gix profiles
+pub fn is_managed_signing_key(config: &Config, value: &str) -> bool {
+    config
+        .profiles
+        .iter()
+        .filter_map(|p| p.signing.as_ref())
+        .any(|s| s.git_signing_key() == value)
+}
+
+/// Location of the allowed signers file (`$XDG_CONFIG_HOME/git/allowed_signers`)
+pub fn allowed_signers_path() -> Result<PathBuf> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => BaseDirs::new()
+            .map(|dirs| dirs.home_dir().join(".config"))
+            .context("Could not determine home directory")?,
+    };
+    Ok(config_dir.join("git").join("allowed_signers"))
+}
+
+/// Read the `<type> <key>` part of a public key file
+fn read_public_key(path: &str) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    let mut fields = contents.split_whitespace();
+    let key_type = fields.next()?;
+    let key = fields.next()?;
+    Some(format!("{} {}", key_type, key))
+}
+
+/// allowed_signers lines for every profile that signs with SSH
+pub fn allowed_signers_entries(config: &Config) -> Vec<String> {
+    config
+        .profiles
+        .iter()
+        .filter_map(|p| {
+            let signing = p.signing.as_ref().filter(|s| s.format == SigningFormat::Ssh)?;
+            let key = read_public_key(&signing.public_key_path())?;
+            // Principals are comma separated: alternate addresses sign with the same key
+            let principals: Vec<&str> = std::iter::once(p.email.as_str())
+                .chain(p.alternate_emails.iter().map(|e| e.as_str()))
+                .collect();
+            Some(format!(
+                "{} namespaces=\"git\" {} gix:{}",
+                principals.join(","),
+                key,
+                p.profile_name
+            ))
+        })
+        .collect()
+}
+
+/// Rewrite the gix-managed block of the allowed signers file
+///
+/// Entries outside the managed block are left untouched.
+pub fn sync_allowed_signers(config: &Config) -> Result<()> {
+    let path = allowed_signers_path()?;
+    let entries = allowed_signers_entries(config);
+
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    if entries.is_empty() && !managed_block::contains(&existing, BLOCK_START) {
+        return Ok(());
+    }
+
+    let block = (!entries.is_empty()).then(|| entries.join("\n"));
+    let contents = managed_block::replace(&existing, BLOCK_START, BLOCK_END, block.as_deref(), Position::Last)
+        .with_context(|| format!("Cannot update {}", path.display()))?;
+    if contents != existing {
+        crate::config::write_atomic(&path, &contents, false)?;
+    }
+    Ok(())
+}
+
+/// State of the OpenPGP smartcard reader
+pub enum CardStatus {
+    /// A card is inserted (serial number, if reported)
+    Present(Option<String>),
+    Absent,
+    /// gpg is not installed or scdaemon could not be reached
+    Unavailable(String),
+}
+
+/// Ask gpg whether an OpenPGP card is inserted
+pub fn card_status() -> CardStatus {
+    let output = match Command::new("gpg").args(["--batch", "--card-status"]).output() {
+        Ok(output) => output,
+        Err(_) => return CardStatus::Unavailable("gpg not found".to_string()),
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if output.status.success() {
+        let serial = stdout
+            .lines()
+            .find(|l| l.starts_with("Serial number"))
+            .and_then(|l| l.split(':').nth(1))
+            .map(|s| s.trim().to_string());
+        return CardStatus::Present(serial);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+    if stderr.contains("no such device") || stderr.contains("card not present") || stderr.contains("no card") {
+        CardStatus::Absent
+    } else {
+        // gpg prints notices (e.g. "directory created") before the actual error
+        let reason = stderr
+            .lines()
+            .rfind(|l| l.contains("error") || l.contains("fail"))
+            .or_else(|| stderr.lines().last())
+            .unwrap_or("unknown error");
+        CardStatus::Unavailable(reason.trim().trim_start_matches("gpg: ").to_string())
+    }
+}
+
+/// Before an intercepted commit or tag that signs on a smartcard, make sure the card is there
+///
+/// Without the card, gpg would hang on a pinentry "insert card" dialog or fail with an
+/// opaque error; stop early with guidance instead, and remind the user to touch the card.
+pub fn check_smartcard(profile: &Profile, values: &[(String, String)], args: &[String]) -> Result<()> {
+    let Some(signing) = profile.signing.as_ref().filter(|s| s.smartcard) else {
+        return Ok(());
+    };
+    let key = match args.first().map(|a| a.as_str()) {
+        Some("commit") => "commit.gpgsign",
+        Some("tag") => "tag.gpgsign",
+        _ => return Ok(()),
+    };
+    let signs = values.iter().any(|(k, v)| k == key && v == "true")
+        || args.iter().any(|a| a == "-S" || a.starts_with("--gpg-sign") || (a == "-s" && key == "tag.gpgsign"));
+    if !signs || args.iter().any(|a| a == "--no-gpg-sign" || a == "--no-sign") {
+        return Ok(());
+    }
+
+    match card_status() {
+        CardStatus::Present(_) => {
+            eprintln!("\x1b[1;36m👆 Signing with the smartcard key {}: touch the card if it blinks.\x1b[0m", signing.key_path);
+            Ok(())
+        }
+        CardStatus::Absent => anyhow::bail!(
+            "The signing key {} of profile '{}' is on a smartcard, but no card is inserted.\n  \
+             Insert the card and retry, or run this once with --no-gpg-sign.",
+            signing.key_path,
+            profile.profile_name
+        ),
+        CardStatus::Unavailable(reason) => anyhow::bail!(
+            "Cannot reach the smartcard for signing key {} ({}).\n  \
+             Check that gpg and scdaemon are installed ('gpg --card-status'), or run this once with --no-gpg-sign.",
+            signing.key_path,
+            reason
+        ),
+    }
+}
+
+/// Fingerprint (`SHA256:...`) of a public key file
+fn key_fingerprint(public_key_path: &str) -> Option<String> {
+    let out = Command::new("ssh-keygen")
+        .args(["-l", "-f", public_key_path])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&out.stdout)
+        .split_whitespace()
+        .nth(1)
+        .map(|s| s.to_string())
+}
+
+/// Handle the 'gix verify' command
+pub fn handle_verify_command(
+    range: Option<String>,
+    count: usize,
+    report_path: Option<std::path::PathBuf>,
+    format: Option<crate::cli::ReportFormat>,
+) -> Result<()> {
+    if !crate::git::is_inside_git_repo() {
+        println!("\x1b[1;33m⚠ Not inside a git repository.\x1b[0m");
+        return Ok(());
+    }
+
+    let config = crate::config::load_config()?;
+    let profile = crate::git::detect_profile(&config)
+        .context("No profile detected for this repository. Run 'gix use' first.")?;
+    let signing = profile
+        .signing
+        .as_ref()
+        .with_context(|| format!("Profile '{}' has no signing key configured", profile.profile_name))?;
+
+    let expected = match signing.format {
+        SigningFormat::Ssh => key_fingerprint(&signing.public_key_path())
+            .with_context(|| format!("Could not read signing key {}", signing.public_key_path()))?,
+        SigningFormat::Openpgp => signing.key_path.trim_start_matches("0x").to_ascii_uppercase(),
+    };
+    // OpenPGP key IDs are suffixes of the signing (sub)key or primary key fingerprint
+    let is_expected_key = |fingerprint: &str, primary: &str| match signing.format {
+        SigningFormat::Ssh => fingerprint == expected,
+        SigningFormat::Openpgp => fingerprint.ends_with(&expected) || primary.ends_with(&expected),
+    };
+
+    println!("\x1b[1;36m🔏 Verifying commit signatures\x1b[0m\n");
+    println!("   👤 Profile: {} <{}>", profile.profile_name, profile.email);
+    println!("   🔑 Expected key: {}\n", expected);
+
+    let mut cmd = Command::new("git");
+    if let Ok(path) = allowed_signers_path() {
+        cmd.arg("-c").arg(format!("gpg.ssh.allowedSignersFile={}", path.display()));
+    }
+    cmd.args(["log", "--format=%H%x1f%ae%x1f%G?%x1f%GF%x1f%GP%x1f%s"]);
+    match &range {
+        Some(r) => {
+            cmd.arg(r);
+        }
+        None => {
+            cmd.arg(format!("-n{}", count));
+        }
+    }
+
+    let out = cmd.output().context("Failed to run git log")?;
+    if !out.status.success() {
+        anyhow::bail!("git log failed: {}", String::from_utf8_lossy(&out.stderr).trim());
+    }
+
+    let (mut ok, mut skipped) = (0, 0);
+    let mut problems = Vec::new();
+
+    for line in String::from_utf8_lossy(&out.stdout).lines() {
+        let fields: Vec<&str> = line.split('\x1f').collect();
+        let [hash, email, status, fingerprint, primary, subject] = fields[..] else {
+            continue;
+        };
+        let short = &hash[..hash.len().min(8)];
+
+        // Only commits made under this identity are expected to carry its signature
+        if !profile.owns_email(email) {
+            skipped += 1;
+            continue;
+        }
+
+        let verdict = match status {
+            "N" => Some("unsigned"),
+            "B" => Some("bad signature"),
+            "R" => Some("signed with a revoked key"),
+            _ if !fingerprint.is_empty() && !is_expected_key(fingerprint, primary) => Some("signed with a different key"),
+            "E" => Some("signature could not be checked"),
+            _ => None,
+        };
+
+        match verdict {
+            None => {
+                ok += 1;
+                println!("   \x1b[1;32m✓\x1b[0m {} {}", short, subject);
+            }
+            Some(reason) => {
+                println!("   \x1b[1;31m✗\x1b[0m {} {} \x1b[1;31m({})\x1b[0m", short, subject, reason);
+                if !fingerprint.is_empty() && !is_expected_key(fingerprint, primary) {
+                    println!("       key: {}", fingerprint);
+                }
+                problems.push(crate::report::OffendingCommit {
+                    hash: hash.to_string(),
+                    author_email: email.to_string(),
+                    subject: subject.to_string(),
+                    reason: reason.to_string(),
+                });
+            }
+        }
+    }
+
+    println!(
+        "\n   {} verified, {} problem(s), {} by other authors skipped",
+        ok,
+        problems.len(),
+        skipped
+    );
+
+    if let Some(path) = &report_path {
+        let root = crate::git::get_git_root().unwrap_or_else(|| std::path::PathBuf::from("."));
+        let entry = crate::report::ReportEntry {
+            repository: root.display().to_string(),
+            remote: crate::git::git_config_in(&root, "remote.origin.url"),
+            expected_profile: Some(profile.profile_name.clone()),
+            expected_email: Some(profile.email.clone()),
+            actual_email: crate::git::git_config_in(&root, "user.email"),
+            status: if problems.is_empty() { "ok" } else { "unverified_commits" }.to_string(),
+            offending_commits: problems.clone(),
+        };
+        crate::report::write_report(&crate::report::Report::new("verify", vec![entry]), path, format)?;
+    }
+
+    if !problems.is_empty() {
+        anyhow::bail!("{} commit(s) failed signature verification", problems.len());
+    }
+
+    println!("\n\x1b[1;32m✓ All commits by {} are signed with the expected key.\x1b[0m", profile.email);
+    Ok(())
+}
@@ -0,0 +1,604 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::config::Config;
+use crate::profile::{AuthMethod, Profile};
+
+/// Per-profile options folded into the generated ssh command
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SshOptions {
+    /// Add `AddKeysToAgent=yes` and `UseKeychain=yes` on macOS
+    #[serde(default)]
+    pub use_keychain: bool,
+    /// Reuse one ssh connection per identity (`ControlMaster`/`ControlPersist`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multiplex: bool,
+    /// Trust published provider host keys (and `known_hosts`) instead of prompting
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pin_host_keys: bool,
+    /// Extra pinned entries for custom hosts ("host key-type key")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_hosts: Vec<String>,
+    /// `StrictHostKeyChecking` for this identity (unset: whatever ssh_config says)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_key_checking: Option<HostKeyChecking>,
+    /// ssh executable for this identity (unset: the global `ssh_program`, then `ssh`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program: Option<String>,
+}
+
+/// Values of ssh's `StrictHostKeyChecking`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HostKeyChecking {
+    /// Refuse unknown or changed host keys
+    Yes,
+    /// Trust unknown hosts on first use, refuse changed keys
+    AcceptNew,
+    /// Accept any host key (insecure)
+    No,
+}
+
+impl HostKeyChecking {
+    pub fn as_ssh_value(&self) -> &'static str {
+        match self {
+            HostKeyChecking::Yes => "yes",
+            HostKeyChecking::AcceptNew => "accept-new",
+            HostKeyChecking::No => "no",
+        }
+    }
+}
+
+impl SshOptions {
+    pub fn is_default(&self) -> bool {
+        self == &SshOptions::default()
+    }
+}
+
+/// Check if gix is running inside Windows Subsystem for Linux
+pub fn is_wsl() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+
+    if std::env::var_os("WSL_DISTRO_NAME").is_some() {
+        return true;
+    }
+
+    fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|r| r.to_lowercase().contains("microsoft"))
+        .unwrap_or(false)
+}
+
+/// Split a Windows drive path (`C:\...` or `C:/...`) into drive letter and remainder
+fn split_windows_drive(path: &str) -> Option<(char, &str)> {
+    let mut chars = path.chars();
+    let drive = chars.next()?;
+    if !drive.is_ascii_alphabetic() || chars.next()? != ':' {
+        return None;
+    }
+    let rest = &path[2..];
+    if !rest.is_empty() && !rest.starts_with(['\\', '/']) {
+        return None;
+    }
+    Some((drive.to_ascii_lowercase(), rest))
+}
+
+/// Split a WSL mount path (`/mnt/c/...`) into drive letter and remainder
+fn split_wsl_mount(path: &str) -> Option<(char, &str)> {
+    let rest = path.strip_prefix("/mnt/")?;
+    let mut chars = rest.chars();
+    let drive = chars.next()?;
+    if !drive.is_ascii_alphabetic() {
+        return None;
+    }
+    let after = &rest[1..];
+    if !(after.is_empty() || after.starts_with('/')) {
+        return None;
+    }
+    Some((drive.to_ascii_lowercase(), after))
+}
+
+/// Translate a key path to the form usable on the current platform
+///
+/// Inside WSL, `C:\Users\me\.ssh\id` becomes `/mnt/c/Users/me/.ssh/id`.
+/// On Windows, `/mnt/c/Users/me/.ssh/id` becomes `C:/Users/me/.ssh/id`.
+pub fn translate_key_path(path: &str) -> String {
+    if cfg!(windows) {
+        if let Some((drive, rest)) = split_wsl_mount(path) {
+            return format!("{}:{}", drive.to_ascii_uppercase(), rest);
+        }
+    } else if is_wsl() {
+        if let Some((drive, rest)) = split_windows_drive(path) {
+            return format!("/mnt/{}{}", drive, rest.replace('\\', "/"));
+        }
+    }
+    path.to_string()
+}
+
+/// Resolve a key path as a filesystem path for the current platform
+pub fn resolve_key_path(path: &str) -> PathBuf {
+    PathBuf::from(translate_key_path(path))
+}
+
+/// Check whether a (translated) path lives on a Windows drive mounted into WSL
+pub fn is_windows_mounted(path: &Path) -> bool {
+    is_wsl() && split_wsl_mount(&path.to_string_lossy()).is_some()
+}
+
+/// Quote a path for use inside a shell command line when needed
+fn shell_quote(value: &str) -> String {
+    if value.chars().any(|c| c.is_whitespace() || "'\"\\$`".contains(c)) {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The global `ssh_program` setting, read once per run
+static GLOBAL_SSH_PROGRAM: OnceLock<Option<String>> = OnceLock::new();
+
+/// ssh executable for an identity: the profile's, else the global `ssh_program`, else `ssh`
+pub fn ssh_program(options: &SshOptions) -> String {
+    options
+        .program
+        .clone()
+        .or_else(|| {
+            GLOBAL_SSH_PROGRAM
+                .get_or_init(|| crate::config::load_config().ok().and_then(|c| c.ssh_program))
+                .clone()
+        })
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "ssh".to_string())
+}
+
+/// Real host names of ssh_config aliases already looked up in this run
+static HOST_ALIASES: Mutex<Option<HashMap<String, Option<String>>>> = Mutex::new(None);
+
+/// Host name an ssh_config alias stands for (`Host github-work` → `HostName github.com`)
+///
+/// Asks `ssh -G`, so `Include`, `Match` and wildcard blocks resolve like they would for git.
+/// None when the host is not an alias or ssh cannot tell.
+pub fn resolve_host_alias(host: &str) -> Option<String> {
+    if host.is_empty() || host.starts_with('-') {
+        return None;
+    }
+    let mut cache = HOST_ALIASES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(resolved) = cache.as_ref().and_then(|c| c.get(host)) {
+        return resolved.clone();
+    }
+
+    let resolved = std::process::Command::new(ssh_program(&SshOptions::default()))
+        .args(["-G", host])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .find_map(|l| l.strip_prefix("hostname ").map(|h| h.trim().to_ascii_lowercase()))
+        })
+        .filter(|real| !real.is_empty() && !real.eq_ignore_ascii_case(host));
+
+    cache.get_or_insert_with(HashMap::new).insert(host.to_string(), resolved.clone());
+    resolved
+}
+
+/// Check that an ssh executable exists and runs, returning its version line
+pub fn check_ssh_program(program: &str) -> Result<String, String> {
+    match std::process::Command::new(program).arg("-V").output() {
+        // OpenSSH prints its version on stderr
+        Ok(output) => {
+            let text = String::from_utf8_lossy(if output.stderr.is_empty() { &output.stdout } else { &output.stderr })
+                .trim()
+                .to_string();
+            if output.status.success() || !text.is_empty() {
+                Ok(text)
+            } else {
+                Err(format!("{} -V exited with {}", program, output.status))
+            }
+        }
+        Err(e) => Err(format!("cannot run {}: {}", program, e)),
+    }
+}
+
+/// Build the ssh command used for a key (`core.sshCommand` / `GIT_SSH_COMMAND`)
+///
+/// With `create`, the control socket directory the command refers to is created.
+fn build_ssh_command(key_path: &str, options: &SshOptions, create: bool) -> String {
+    let identity = format!("-i {}", shell_quote(&translate_key_path(key_path)));
+    let mut cmd = ssh_command_base(&identity, options);
+
+    // Control sockets need unix domain sockets (not available to Windows OpenSSH)
+    if options.multiplex && cfg!(unix) {
+        if let Some(dir) = control_socket_dir(key_path, create) {
+            let path = format!("ControlPath={}", dir.join("%C").display());
+            cmd.push_str(&format!(
+                " -o ControlMaster=auto -o {} -o ControlPersist={}",
+                shell_quote(&path),
+                CONTROL_PERSIST
+            ));
+        }
+    }
+
+    cmd
+}
+
+/// ssh command for a profile, reaching `host` (when known) with the right key
+///
+/// A profile with per-host keys gets a generated ssh_config (`-F`) that picks the key by
+/// host, so every remote a command reaches (other remotes, `fetch --all`, submodules)
+/// gets its own key, not just the one the command was started for. The files the
+/// command refers to are created.
+pub fn profile_ssh_command(profile: &Profile, host: Option<&str>) -> String {
+    ssh_command(profile, host, true)
+}
+
+/// The command `profile_ssh_command` returns, without creating anything (for display
+/// and comparisons)
+pub fn preview_ssh_command(profile: &Profile, host: Option<&str>) -> String {
+    ssh_command(profile, host, false)
+}
+
+fn ssh_command(profile: &Profile, host: Option<&str>, create: bool) -> String {
+    let key_path = profile.auth.key_for(host).unwrap_or_default();
+    match &profile.auth {
+        AuthMethod::SSH { host_keys, .. } if !host_keys.is_empty() => match write_profile_config(profile, create) {
+            Ok(path) => {
+                let identity = format!("-F {}", shell_quote(&translate_key_path(&path.to_string_lossy())));
+                ssh_command_base(&identity, &profile.ssh)
+            }
+            Err(e) => {
+                if create {
+                    eprintln!("\x1b[1;33m⚠ Could not write the ssh config of '{}': {}\x1b[0m", profile.profile_name, e);
+                }
+                build_ssh_command(key_path, &profile.ssh, create)
+            }
+        },
+        _ => build_ssh_command(key_path, &profile.ssh, create),
+    }
+}
+
+/// ssh program, identity argument and the options shared by every identity
+fn ssh_command_base(identity: &str, options: &SshOptions) -> String {
+    let mut cmd = format!("{} {} -o IdentitiesOnly=yes", shell_quote(&ssh_program(options)), identity);
+
+    // UseKeychain is only understood by Apple's OpenSSH build
+    if options.use_keychain && cfg!(target_os = "macos") {
+        cmd.push_str(" -o AddKeysToAgent=yes -o UseKeychain=yes");
+    }
+
+    if let Some(checking) = options.host_key_checking {
+        cmd.push_str(&format!(" -o StrictHostKeyChecking={}", checking.as_ssh_value()));
+    }
+
+    // Pinned keys live in gix's own file, consulted after the user's
+    if options.pin_host_keys {
+        if let Some(path) = crate::known_hosts::known_hosts_path() {
+            let files = format!("UserKnownHostsFile=~/.ssh/known_hosts {}", path.display());
+            cmd.push_str(&format!(" -o {}", shell_quote(&files)));
+        }
+    }
+
+    cmd
+}
+
+/// Longest unix socket path (`sun_path` less its terminating NUL)
+const MAX_SOCKET_PATH: usize = if cfg!(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd")) { 103 } else { 107 };
+
+/// What ssh appends to the control directory: `/%C` (40 hex digits), then a
+/// `.XXXXXXXXXXXXXXXX` suffix while the socket is being created
+const SOCKET_NAME_LEN: usize = 1 + 40 + 17;
+
+/// Control socket directory of a key, restricted to the user; created with `create`
+///
+/// None when the sockets would not fit a unix socket path (a long home directory):
+/// multiplexing is then left off rather than have ssh complain on every connection.
+pub fn control_socket_dir(key_path: &str, create: bool) -> Option<PathBuf> {
+    let dir = control_dir(key_path)?;
+    if dir.as_os_str().len() + SOCKET_NAME_LEN > MAX_SOCKET_PATH {
+        if create {
+            eprintln!(
+                "\x1b[1;33m⚠ {} is too long for ssh control sockets; not sharing connections\x1b[0m",
+                dir.display()
+            );
+        }
+        return None;
+    }
+    if create {
+        fs::create_dir_all(&dir).ok()?;
+        restrict_dir(&dir);
+    }
+    Some(dir)
+}
+
+/// Path of the generated ssh_config of a profile with per-host keys
+fn profile_config_path(profile: &Profile) -> anyhow::Result<PathBuf> {
+    let name: String = profile
+        .profile_name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    Ok(crate::config::get_gix_home_dir()?.join("ssh").join(format!("{}.config", name)))
+}
+
+/// ssh_config picking the profile's key by host
+///
+/// `-F` replaces the user's and the system's config files, so they are included after
+/// gix's blocks: ssh keeps the first value of an option, and tries identities in order.
+fn profile_config(profile: &Profile, create: bool) -> String {
+    let AuthMethod::SSH { key_path, host_keys } = &profile.auth else {
+        return String::new();
+    };
+    let multiplex = profile.ssh.multiplex && cfg!(unix);
+    let block = |patterns: String, key: &str| {
+        let mut lines = vec![
+            format!("Host {}", patterns),
+            format!("    IdentityFile \"{}\"", translate_key_path(key)),
+        ];
+        if let Some(dir) = control_socket_dir(key, create).filter(|_| multiplex) {
+            lines.push(format!("    ControlPath \"{}\"", dir.join("%C").display()));
+        }
+        lines.join("\n")
+    };
+
+    let mut blocks = vec![format!(
+        "# Written by gix for profile '{}'; regenerated from the profile, edits are replaced",
+        profile.profile_name
+    )];
+    let mut others = vec!["*".to_string()];
+    for (host, key) in host_keys.iter().filter(|(h, _)| crate::profile::is_key_host(h)) {
+        // A remote may name the host through the profile's ssh_config alias
+        let patterns = [host.to_lowercase(), crate::ssh_config::host_alias(host, profile)];
+        blocks.push(block(patterns.join(" "), key));
+        others.extend(patterns.iter().map(|p| format!("!{}", p)));
+    }
+    blocks.push(block(others.join(" "), key_path));
+
+    let mut last = vec!["Host *".to_string()];
+    if multiplex {
+        last.push("    ControlMaster auto".to_string());
+        last.push(format!("    ControlPersist {}", CONTROL_PERSIST));
+    }
+    last.push("    Include ~/.ssh/config".to_string());
+    if cfg!(unix) {
+        last.push("    Include /etc/ssh/ssh_config".to_string());
+    }
+    blocks.push(last.join("\n"));
+    blocks.join("\n\n") + "\n"
+}
+
+/// Write the generated ssh_config of a profile (only when it changed) and return its path
+///
+/// Without `create`, only the path it would have.
+fn write_profile_config(profile: &Profile, create: bool) -> anyhow::Result<PathBuf> {
+    let path = profile_config_path(profile)?;
+    if let Some(key) = profile.auth.ssh_keys().into_iter().find(|k| k.contains(|c: char| c == '"' || c.is_control())) {
+        anyhow::bail!("the key path {:?} cannot be written to an ssh_config", key);
+    }
+    if !create {
+        return Ok(path);
+    }
+    let contents = profile_config(profile, true);
+    if fs::read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+            restrict_dir(dir);
+        }
+        crate::config::write_atomic(&path, &contents, true)?;
+    }
+    Ok(path)
+}
+
+/// Regenerate the ssh_config of every profile with per-host keys
+///
+/// Repositories keep `core.sshCommand` pointing at these files, so they follow profile
+/// edits without running `gix use` again.
+pub fn sync_profile_configs(config: &Config) -> anyhow::Result<()> {
+    for profile in &config.profiles {
+        if matches!(&profile.auth, AuthMethod::SSH { host_keys, .. } if !host_keys.is_empty()) {
+            write_profile_config(profile, true)?;
+        }
+    }
+    Ok(())
+}
+
+/// How long an idle master connection stays open
+pub const CONTROL_PERSIST: &str = "10m";
+
+/// Directory holding the control sockets of one identity
+///
+/// `%C` only hashes host, port and user, so sockets are separated per key:
+/// otherwise two profiles on the same host would share one authenticated
+/// connection. The directory is named by a short hash of the key path, as unix
+/// socket paths are limited to about 100 bytes.
+pub fn control_dir(key_path: &str) -> Option<PathBuf> {
+    if key_path.is_empty() {
+        return None;
+    }
+    // FNV-1a: stable across runs and gix versions, unlike std's hasher
+    let hash = translate_key_path(key_path)
+        .bytes()
+        .fold(0x811c9dc5u32, |h, b| (h ^ u32::from(b)).wrapping_mul(0x01000193));
+    crate::config::get_gix_home_dir().ok().map(|d| d.join("sockets").join(format!("{:08x}", hash)))
+}
+
+pub fn restrict_dir(_dir: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(_dir, fs::Permissions::from_mode(0o700)).ok();
+    }
+}
+
+/// Control sockets currently present for an identity
+pub fn control_sockets(key_path: &str) -> Vec<PathBuf> {
+    let Some(dir) = control_dir(key_path) else {
+        return vec![];
+    };
+    fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_default()
+}
+
+/// Ask the master behind each control socket to exit and remove the socket
+///
+/// Returns the number of sockets cleaned up.
+pub fn close_control_sockets(key_path: &str, options: &SshOptions) -> usize {
+    let sockets = control_sockets(key_path);
+    for socket in &sockets {
+        // The destination is ignored for control commands, but ssh requires one
+        std::process::Command::new(ssh_program(options))
+            .arg("-o")
+            .arg(format!("ControlPath={}", socket.display()))
+            .args(["-O", "exit", "gix"])
+            .output()
+            .ok();
+        // A stale socket (master already gone) is left behind by ssh
+        fs::remove_file(socket).ok();
+    }
+    sockets.len()
+}
+
+/// Handle the 'gix ssh close' command
+pub fn handle_ssh_close(name: Option<String>) -> anyhow::Result<()> {
+    let config = crate::config::load_config()?;
+
+    let profiles: Vec<&Profile> = match &name {
+        Some(name) => vec![config.find_profile(name).with_context(|| format!("Profile '{}' not found", name))?],
+        None => config.profiles.iter().collect(),
+    };
+
+    let mut closed = 0;
+    for profile in profiles {
+        let count: usize = profile.auth.ssh_keys().into_iter().map(|k| close_control_sockets(k, &profile.ssh)).sum();
+        if count > 0 {
+            println!("   🔌 {}: closed {} connection(s)", profile.profile_name, count);
+        }
+        closed += count;
+    }
+
+    if closed == 0 {
+        println!("\x1b[1;33mNo shared SSH connections open.\x1b[0m");
+    } else {
+        println!("\x1b[1;32m✓ Closed {} shared SSH connection(s).\x1b[0m", closed);
+    }
+    Ok(())
+}
+
+/// A file or directory whose mode is more open than ssh tolerates
+#[derive(Debug, Clone)]
+pub struct PermissionIssue {
+    pub path: PathBuf,
+    pub mode: u32,
+    pub expected: u32,
+}
+
+/// Collect permission problems for a private key, its `.pub` and its parent directory
+#[cfg(unix)]
+pub fn key_permission_issues(key: &Path) -> Vec<PermissionIssue> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode_of = |p: &Path| fs::metadata(p).ok().map(|m| m.permissions().mode() & 0o777);
+    let mut issues = Vec::new();
+
+    // Private key must not be accessible by group/others
+    if let Some(mode) = mode_of(key) {
+        if mode & 0o077 != 0 {
+            issues.push(PermissionIssue { path: key.to_path_buf(), mode, expected: 0o600 });
+        }
+    }
+
+    // Public key must not be writable by group/others
+    let public = PathBuf::from(format!("{}.pub", key.display()));
+    if let Some(mode) = mode_of(&public) {
+        if mode & 0o022 != 0 {
+            issues.push(PermissionIssue { path: public, mode, expected: 0o644 });
+        }
+    }
+
+    // The directory holding the key (usually ~/.ssh) should be private
+    if let Some(dir) = key.parent().filter(|d| d.file_name().map(|n| n == ".ssh").unwrap_or(false)) {
+        if let Some(mode) = mode_of(dir) {
+            if mode & 0o077 != 0 {
+                issues.push(PermissionIssue { path: dir.to_path_buf(), mode, expected: 0o700 });
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(not(unix))]
+pub fn key_permission_issues(_key: &Path) -> Vec<PermissionIssue> {
+    vec![]
+}
+
+/// Apply the expected modes for the given issues
+pub fn fix_permission_issues(issues: &[PermissionIssue]) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use anyhow::Context;
+        use std::os::unix::fs::PermissionsExt;
+
+        for issue in issues {
+            fs::set_permissions(&issue.path, fs::Permissions::from_mode(issue.expected))
+                .with_context(|| format!("Failed to chmod {}", issue.path.display()))?;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = issues;
+
+    Ok(())
+}
+
+/// Report permission problems for a key and offer to fix them
+///
+/// With `fix` set the modes are corrected without asking; otherwise the user is
+/// prompted when a terminal is available. Returns true if no problems remain.
+pub fn check_key_permissions(key: &Path, fix: bool) -> anyhow::Result<bool> {
+    let issues = key_permission_issues(key);
+    if issues.is_empty() {
+        return Ok(true);
+    }
+
+    // chmod has no effect on drvfs mounts without the metadata option
+    if is_windows_mounted(key) {
+        println!("\x1b[1;33m⚠ Warning: SSH key {} is stored on a Windows drive. ssh inside WSL will refuse it as too open; copy it to ~/.ssh in WSL or mount the drive with the 'metadata' option.\x1b[0m", key.display());
+        return Ok(false);
+    }
+
+    for issue in &issues {
+        println!(
+            "\x1b[1;33m⚠ Warning: {} has insecure permissions ({:o}), expected {:o}\x1b[0m",
+            issue.path.display(),
+            issue.mode,
+            issue.expected
+        );
+    }
+
+    let apply = fix
+        || (crate::settings::interactive()
+            && dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                .with_prompt("Fix these permissions now?")
+                .default(true)
+                .interact()?);
+
+    if apply {
+        fix_permission_issues(&issues)?;
+        println!("\x1b[1;32m✓ Permissions fixed.\x1b[0m");
+        Ok(true)
+    } else {
+        for issue in &issues {
+            println!("   Run: chmod {:o} {}", issue.expected, issue.path.display());
+        }
+        Ok(false)
+    }
+}
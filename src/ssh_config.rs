@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use directories::BaseDirs;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{load_config, Config};
+use crate::managed_block::{self, Position};
+use crate::profile::{AuthMethod, Profile};
+
+const BLOCK_START: &str = "# >>> gix ssh profiles >>>";
+const BLOCK_END: &str = "# <<< gix ssh profiles <<<";
+
+fn ssh_config_path() -> Result<PathBuf> {
+    let home = BaseDirs::new()
+        .map(|dirs| dirs.home_dir().to_path_buf())
+        .context("Could not determine home directory")?;
+    Ok(home.join(".ssh").join("config"))
+}
+
+/// Whether a profile can authenticate through an ssh_config alias
+///
+/// A custom ssh program cannot be expressed in ssh_config, so those profiles keep
+/// `core.sshCommand`.
+pub fn can_alias(config: &Config, profile: &Profile) -> bool {
+    config.ssh_aliases
+        && matches!(profile.auth, AuthMethod::SSH { .. })
+        && crate::ssh::ssh_program(&profile.ssh) == "ssh"
+}
+
+/// Profile name as used in an alias: lowercase, `-` for anything else
+fn profile_slug(profile: &Profile) -> String {
+    profile
+        .profile_name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Alias of a host for a profile, e.g. `github.com-work`
+pub fn host_alias(host: &str, profile: &Profile) -> String {
+    format!("{}-{}", host.to_lowercase(), profile_slug(profile))
+}
+
+/// Real host behind a gix alias, and the profile it belongs to
+fn unalias<'a>(config: &'a Config, host: &str) -> Option<(String, &'a Profile)> {
+    config
+        .profiles
+        .iter()
+        .filter(|p| matches!(p.auth, AuthMethod::SSH { .. }))
+        .find_map(|p| {
+            let real = host.strip_suffix(&format!("-{}", profile_slug(p)))?;
+            Some((real.to_string(), p)).filter(|(r, _)| r.contains('.'))
+        })
+}
+
+/// Host behind a gix alias, or the host itself
+pub fn real_host(config: &Config, host: &str) -> String {
+    unalias(config, host).map(|(real, _)| real).unwrap_or_else(|| host.to_string())
+}
+
+/// The URL with its host replaced, keeping user, port and path
+fn replace_host(url: &str, host: &str, new_host: &str) -> String {
+    let (prefix, rest) = match url.find("://") {
+        Some(i) => url.split_at(i + 3),
+        None => ("", url),
+    };
+    let end = if prefix.is_empty() { rest.find(':') } else { rest.find('/') }.unwrap_or(rest.len());
+    let (authority, tail) = rest.split_at(end);
+    let (user, host_port) = match authority.rsplit_once('@') {
+        Some((user, host_port)) => (format!("{}@", user), host_port),
+        None => (String::new(), authority),
+    };
+    format!("{}{}{}{}", prefix, user, host_port.replacen(host, new_host, 1), tail)
+}
+
+/// Hosts each profile gets an alias for: those of its host patterns, plus the hosts of
+/// remotes already using one of its aliases (registered repositories and `also`)
+fn alias_hosts<'a>(config: &'a Config, also: Option<&Path>) -> BTreeMap<String, (String, &'a Profile)> {
+    let mut aliases = BTreeMap::new();
+    for profile in config.profiles.iter().filter(|p| can_alias(config, p)) {
+        for pattern in &profile.hosts {
+            let host = crate::rules::pattern_host(pattern);
+            if !host.is_empty() && !host.contains(['*', '?', '[']) {
+                aliases.insert(host_alias(&host, profile), (host.clone(), profile));
+            }
+        }
+    }
+
+    let dirs = crate::registry::load_registry().repos.into_iter().map(|r| r.path).chain(also.map(Path::to_path_buf));
+    for dir in dirs.filter(|d| d.exists()) {
+        for (_, url) in crate::git::remote_url_entries(&dir) {
+            let Some((host, _)) = crate::submodule::split_remote_url(&url).filter(|_| crate::rules::is_ssh_url(&url)) else {
+                continue;
+            };
+            if let Some((real, profile)) = unalias(config, &host).filter(|(_, p)| can_alias(config, p)) {
+                aliases.insert(host, (real, profile));
+            }
+        }
+    }
+    aliases
+}
+
+/// ssh_config options equivalent to the profile's ssh command
+fn host_block(alias: &str, host: &str, profile: &Profile) -> String {
+    let Some(key_path) = profile.auth.key_for(Some(host)) else {
+        return String::new();
+    };
+    let options = &profile.ssh;
+    let mut lines = vec![
+        format!("Host {}", alias),
+        format!("    HostName {}", host),
+        format!("    IdentityFile \"{}\"", crate::ssh::translate_key_path(key_path)),
+        "    IdentitiesOnly yes".to_string(),
+    ];
+    if options.use_keychain && cfg!(target_os = "macos") {
+        lines.push("    AddKeysToAgent yes".to_string());
+        lines.push("    UseKeychain yes".to_string());
+    }
+    if let Some(checking) = options.host_key_checking {
+        lines.push(format!("    StrictHostKeyChecking {}", checking.as_ssh_value()));
+    }
+    if options.pin_host_keys {
+        if let Some(path) = crate::known_hosts::known_hosts_path() {
+            lines.push(format!("    UserKnownHostsFile ~/.ssh/known_hosts \"{}\"", path.display()));
+        }
+    }
+    if options.multiplex && cfg!(unix) {
+        if let Some(dir) = crate::ssh::control_socket_dir(key_path, true) {
+            lines.push("    ControlMaster auto".to_string());
+            lines.push(format!("    ControlPath \"{}\"", dir.join("%C").display()));
+            lines.push(format!("    ControlPersist {}", crate::ssh::CONTROL_PERSIST));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Regenerate the managed block of ~/.ssh/config; returns the aliases it holds
+///
+/// Idempotent: the file is only written when the block changes.
+pub fn sync(config: &Config, also: Option<&Path>) -> Result<Vec<String>> {
+    let aliases = alias_hosts(config, also);
+    let path = ssh_config_path()?;
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+
+    let block = (!aliases.is_empty()).then(|| {
+        let mut blocks = vec!["# Written by gix ('gix ssh sync'); edits inside this block are replaced".to_string()];
+        blocks.extend(aliases.iter().map(|(alias, (host, profile))| host_block(alias, host, profile)));
+        blocks.join("\n\n")
+    });
+    // A new block goes first: ssh uses the first value it finds for an option
+    let contents = managed_block::replace(&existing, BLOCK_START, BLOCK_END, block.as_deref(), Position::First)
+        .with_context(|| format!("Cannot update {}", path.display()))?;
+    if contents != existing {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+            crate::ssh::restrict_dir(dir);
+        }
+        crate::config::write_atomic(&path, &contents, true)?;
+    }
+    Ok(aliases.into_keys().collect())
+}
+
+/// Point a repository's SSH remotes at the profile's aliases, or back at the real host
+/// when the profile does not use one; returns how many URLs changed
+pub fn rewrite_remotes(config: &Config, profile: &Profile, dir: &Path) -> Result<usize> {
+    let mut changed = 0;
+    for (key, url) in crate::git::remote_url_entries(dir) {
+        if !crate::rules::is_ssh_url(&url) {
+            continue;
+        }
+        let Some((host, _)) = crate::submodule::split_remote_url(&url) else {
+            continue;
+        };
+        let real = real_host(config, &host);
+        let target = if can_alias(config, profile) { host_alias(&real, profile) } else { real };
+        if target != host {
+            let new_url = replace_host(&url, &host, &target);
+            crate::git::run_local_git_config(dir, &["--fixed-value", "--replace-all", &key, &new_url, &url])?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Handle the 'gix ssh sync' command
+pub fn handle_ssh_sync() -> Result<()> {
+    let config = load_config()?;
+    let path = ssh_config_path()?;
+    let aliases = sync(&config, None)?;
+
+    if aliases.is_empty() {
+        println!("\x1b[1;32m✓ No host aliases to manage; {} has no gix block.\x1b[0m", path.display());
+    } else {
+        println!("\x1b[1;32m✓ {} is up to date with {} host alias(es):\x1b[0m", path.display(), aliases.len());
+        for alias in &aliases {
+            println!("   • {}", alias);
+        }
+    }
+    if !config.ssh_aliases {
+        println!("\n   Host aliases are off. Turn them on with '\x1b[1mgix settings set ssh_aliases true\x1b[0m',");
+        println!("   then 'gix use' points a repository's SSH remotes at its profile's alias.");
+    }
+    Ok(())
+}
@@ -0,0 +1,297 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::config::get_gix_home_dir;
+
+/// One parsed line of ~/.gix/usage.log
+///
+/// Older entries predate exit status and duration logging, so those are optional.
+#[derive(Debug, Clone, Default)]
+pub struct UsageEntry {
+    pub timestamp: String,
+    pub profile: String,
+    pub command: String,
+    pub dir: String,
+    pub remote: Option<String>,
+    pub exit: Option<String>,
+    pub duration: Option<f64>,
+    pub success: Option<bool>,
+}
+
+impl UsageEntry {
+    /// The git subcommand (e.g. "push")
+    pub fn subcommand(&self) -> &str {
+        self.command
+            .strip_prefix("git ")
+            .unwrap_or(&self.command)
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+    }
+}
+
+/// Parse a usage log line: `[ts] Profile: p | Cmd: git ... | Dir: "..." | ...`
+pub fn parse_usage_line(line: &str) -> Option<UsageEntry> {
+    let rest = line.strip_prefix('[')?;
+    let (timestamp, rest) = rest.split_once("] ")?;
+
+    let mut entry = UsageEntry {
+        timestamp: timestamp.to_string(),
+        ..Default::default()
+    };
+
+    for field in rest.split(" | ") {
+        let Some((key, value)) = field.split_once(": ") else {
+            continue;
+        };
+        match key {
+            "Profile" => entry.profile = value.to_string(),
+            "Cmd" => entry.command = value.to_string(),
+            "Dir" => entry.dir = value.trim_matches('"').to_string(),
+            "Remote" => entry.remote = Some(value.to_string()),
+            "Exit" => entry.exit = Some(value.to_string()),
+            "Duration" => entry.duration = value.trim_end_matches('s').parse().ok(),
+            "Result" => entry.success = Some(value == "ok"),
+            _ => {}
+        }
+    }
+
+    if entry.profile.is_empty() {
+        return None;
+    }
+    // Entries with an exit status but no explicit result
+    if entry.success.is_none() {
+        entry.success = entry.exit.as_deref().map(|e| e == "0");
+    }
+    Some(entry)
+}
+
+/// Read every parseable entry of the usage log
+pub fn read_usage_log() -> Result<Vec<UsageEntry>> {
+    let path = get_gix_home_dir()?.join("usage.log");
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    Ok(contents.lines().filter_map(parse_usage_line).collect())
+}
+
+/// Aggregated numbers for a group of entries
+#[derive(Debug, Default, Clone)]
+struct Totals {
+    runs: usize,
+    failures: usize,
+    timed: usize,
+    total_secs: f64,
+    max_secs: f64,
+}
+
+impl Totals {
+    fn add(&mut self, entry: &UsageEntry) {
+        self.runs += 1;
+        if entry.success == Some(false) {
+            self.failures += 1;
+        }
+        if let Some(secs) = entry.duration {
+            self.timed += 1;
+            self.total_secs += secs;
+            self.max_secs = self.max_secs.max(secs);
+        }
+    }
+
+    fn average(&self) -> Option<f64> {
+        (self.timed > 0).then(|| self.total_secs / self.timed as f64)
+    }
+
+    fn describe(&self) -> String {
+        let timing = match self.average() {
+            Some(avg) => format!(", avg {:.2}s, max {:.2}s", avg, self.max_secs),
+            None => String::new(),
+        };
+        let failures = if self.failures > 0 {
+            format!(", \x1b[1;31m{} failed\x1b[0m", self.failures)
+        } else {
+            String::new()
+        };
+        format!("{} run(s){}{}", self.runs, failures, timing)
+    }
+}
+
+fn print_group(title: &str, groups: &BTreeMap<String, Totals>) {
+    if groups.is_empty() {
+        return;
+    }
+    println!("   \x1b[1m{}\x1b[0m", title);
+    for (name, totals) in groups {
+        println!("      {:<24} {}", name, totals.describe());
+    }
+    println!();
+}
+
+/// Handle the 'gix stats' command
+pub fn handle_stats_command() -> Result<()> {
+    let entries = read_usage_log()?;
+
+    println!("\x1b[1;36m📊 gix Usage Statistics\x1b[0m\n");
+
+    if entries.is_empty() {
+        println!("   No intercepted commands logged yet.");
+        return Ok(());
+    }
+
+    let mut by_profile: BTreeMap<String, Totals> = BTreeMap::new();
+    let mut by_remote: BTreeMap<String, Totals> = BTreeMap::new();
+    let mut by_command: BTreeMap<String, Totals> = BTreeMap::new();
+    let mut failing: BTreeMap<String, usize> = BTreeMap::new();
+
+    for entry in &entries {
+        by_profile.entry(entry.profile.clone()).or_default().add(entry);
+        by_command.entry(entry.subcommand().to_string()).or_default().add(entry);
+        if let Some(remote) = &entry.remote {
+            by_remote.entry(remote.clone()).or_default().add(entry);
+        }
+        if entry.success == Some(false) {
+            let key = format!(
+                "{} → {} ({})",
+                entry.profile,
+                entry.remote.as_deref().unwrap_or("local"),
+                entry.subcommand()
+            );
+            *failing.entry(key).or_default() += 1;
+        }
+    }
+
+    println!("   {} command(s) since {}\n", entries.len(), entries[0].timestamp);
+
+    print_group("By profile", &by_profile);
+    print_group("By command", &by_command);
+
+    // Slowest remotes first
+    if !by_remote.is_empty() {
+        let mut remotes: Vec<(&String, &Totals)> = by_remote.iter().collect();
+        remotes.sort_by(|a, b| b.1.average().unwrap_or(0.0).total_cmp(&a.1.average().unwrap_or(0.0)));
+        println!("   \x1b[1mBy remote (slowest first)\x1b[0m");
+        for (name, totals) in remotes {
+            println!("      {:<24} {}", name, totals.describe());
+        }
+        println!();
+    }
+
+    // Repeated failures of one profile against one remote usually mean broken auth
+    if !failing.is_empty() {
+        let mut patterns: Vec<(String, usize)> = failing.into_iter().collect();
+        patterns.sort_by_key(|p| std::cmp::Reverse(p.1));
+        println!("   \x1b[1mFailure patterns\x1b[0m");
+        for (pattern, count) in patterns.iter().take(5) {
+            println!("      \x1b[1;31m✗\x1b[0m {} - {} failure(s)", pattern, count);
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Commits of one author email in the repository history
+struct AuthorTotals {
+    name: String,
+    commits: usize,
+    /// Oldest commit (hash, date), i.e. when the identity was introduced
+    first: (String, String),
+    last_date: String,
+}
+
+/// How an author email relates to the profile expected in this repository
+fn classify(config: &crate::config::Config, expected: Option<&crate::profile::Profile>, name: &str, email: &str) -> Option<String> {
+    let expected = expected?;
+    if expected.owns_email(email) {
+        return None;
+    }
+    if let Some(other) = config.profiles.iter().find(|p| p.owns_email(email)) {
+        return Some(format!("identity of profile '{}'", other.profile_name));
+    }
+    if name.eq_ignore_ascii_case(&expected.name) {
+        return Some("your name with another email".to_string());
+    }
+    None
+}
+
+/// Handle 'gix stats --authors': author emails in this repository versus the expected profile
+pub fn handle_repo_stats_command() -> Result<()> {
+    use anyhow::Context;
+
+    if !crate::git::is_inside_git_repo() {
+        anyhow::bail!("Not inside a git repository");
+    }
+    let config = crate::config::load_config()?;
+    let expected = crate::git::detect_profile(&config);
+
+    let output = std::process::Command::new("git")
+        .args(["log", "--reverse", "--format=%h%x1f%an%x1f%ae%x1f%as"])
+        .output()
+        .context("Failed to run git log")?;
+    if !output.status.success() {
+        anyhow::bail!("git log failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    let mut authors: BTreeMap<String, AuthorTotals> = BTreeMap::new();
+    let mut total = 0;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let [hash, name, email, date] = line.split('\x1f').collect::<Vec<_>>()[..] else {
+            continue;
+        };
+        total += 1;
+        let entry = authors.entry(email.to_ascii_lowercase()).or_insert_with(|| AuthorTotals {
+            name: name.to_string(),
+            commits: 0,
+            first: (hash.to_string(), date.to_string()),
+            last_date: date.to_string(),
+        });
+        entry.commits += 1;
+        entry.last_date = date.to_string();
+    }
+
+    println!("\x1b[1;36m📊 Commit identities in this repository\x1b[0m\n");
+    match expected {
+        Some(p) => println!("   Expected profile: \x1b[1;32m{}\x1b[0m ({})\n", p.profile_name, p.email),
+        None => println!("   \x1b[1;33m⚠ No profile detected; run 'gix use' to compare against one.\x1b[0m\n"),
+    }
+    if total == 0 {
+        println!("   No commits yet.");
+        return Ok(());
+    }
+
+    let mut rows: Vec<(&String, &AuthorTotals)> = authors.iter().collect();
+    rows.sort_by_key(|(_, a)| std::cmp::Reverse(a.commits));
+
+    let mut foreign = Vec::new();
+    println!("   \x1b[1mBy author email\x1b[0m ({} commit(s))", total);
+    for (email, author) in &rows {
+        let share = author.commits as f64 * 100.0 / total as f64;
+        let mark = match classify(&config, expected, &author.name, email) {
+            Some(reason) => {
+                foreign.push((email, author, reason));
+                "\x1b[1;31m✗\x1b[0m"
+            }
+            None if expected.is_some_and(|p| p.owns_email(email)) => "\x1b[1;32m✓\x1b[0m",
+            None => " ",
+        };
+        println!("    {} {:<36} {:>5} {:>5.1}%  {}", mark, email, author.commits, share, author.name);
+    }
+    println!();
+
+    if foreign.is_empty() {
+        if expected.is_some() {
+            println!("\x1b[1;32m✓ No commits under your other identities.\x1b[0m");
+        }
+        return Ok(());
+    }
+
+    println!("   \x1b[1mForeign identities\x1b[0m");
+    for (email, author, reason) in &foreign {
+        println!(
+            "      \x1b[1;31m✗\x1b[0m {} - {}: {} commit(s), introduced in {} on {} (last {})",
+            email, reason, author.commits, author.first.0, author.first.1, author.last_date
+        );
+    }
+    println!("\n   Run 'gix verify' or see 'git log --author=<email>' for the affected commits.");
+
+    Ok(())
+}
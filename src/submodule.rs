@@ -0,0 +1,217 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::git::set_local_git_config;
+use crate::profile::{AuthMethod, Profile};
+use crate::ssh;
+
+/// A submodule declared in `.gitmodules`
+#[derive(Debug, Clone)]
+pub struct Submodule {
+    pub name: String,
+    pub path: Option<String>,
+    pub url: String,
+}
+
+/// Read the submodules declared in a repository's `.gitmodules`
+pub fn list_submodules(dir: &Path) -> Vec<Submodule> {
+    if !dir.join(".gitmodules").exists() {
+        return vec![];
+    }
+
+    let out = Command::new("git")
+        .current_dir(dir)
+        .args(["config", "-f", ".gitmodules", "--get-regexp", r"^submodule\..*\.(url|path)$"])
+        .output();
+    let Ok(out) = out else {
+        return vec![];
+    };
+
+    let mut submodules: Vec<Submodule> = Vec::new();
+    for line in String::from_utf8_lossy(&out.stdout).lines() {
+        let Some((key, value)) = line.split_once(' ') else {
+            continue;
+        };
+        // Names may contain dots: strip the fixed prefix and suffix only
+        let Some(rest) = key.strip_prefix("submodule.") else {
+            continue;
+        };
+        let (name, field) = match rest.rsplit_once('.') {
+            Some(parts) => parts,
+            None => continue,
+        };
+
+        let index = match submodules.iter().position(|s| s.name == name) {
+            Some(i) => i,
+            None => {
+                submodules.push(Submodule { name: name.to_string(), path: None, url: String::new() });
+                submodules.len() - 1
+            }
+        };
+        match field {
+            "url" => submodules[index].url = value.to_string(),
+            "path" => submodules[index].path = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    submodules.retain(|s| !s.url.is_empty());
+    submodules
+}
+
+/// Checked-out submodules of a repository, nested ones included
+pub fn checked_out_submodules(dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for submodule in list_submodules(dir) {
+        let Some(checkout) = submodule.path.map(|p| dir.join(p)) else {
+            continue;
+        };
+        // A checked-out submodule has a `.git` file pointing into the superproject
+        if checkout.join(".git").exists() {
+            found.extend(checked_out_submodules(&checkout));
+            found.push(checkout);
+        }
+    }
+    found
+}
+
+/// HTTPS hosts an intercepted `git submodule` command may fetch from
+///
+/// Covers the URLs of `.gitmodules` (as overridden in `.git/config`), nested
+/// submodules already checked out, and the URL given to `submodule add`.
+pub fn submodule_https_hosts(dir: &Path, args: &[String]) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut repos = vec![dir.to_path_buf()];
+    repos.extend(checked_out_submodules(dir));
+    for repo in &repos {
+        for submodule in list_submodules(repo) {
+            let url = crate::git::get_local_git_config(repo, &format!("submodule.{}.url", submodule.name))
+                .unwrap_or(submodule.url);
+            urls.push(url);
+        }
+    }
+    let positional = crate::git::positional_args(args);
+    if positional.first() == Some(&"add") {
+        if let Some(url) = positional.get(1) {
+            urls.push(url.to_string());
+        }
+    }
+
+    let mut hosts: Vec<String> = Vec::new();
+    for url in urls.iter().filter(|u| u.starts_with("https://")) {
+        if let Some((host, _)) = split_remote_url(url) {
+            if !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+    }
+    hosts
+}
+
+/// Give checked-out submodules the profile's identity and SSH key
+///
+/// Submodules have their own `.git/config`, so git run inside them later
+/// (without gix) would otherwise use the global identity and default key.
+pub fn configure_submodule_checkouts(profile: &Profile, dir: &Path) -> Result<usize> {
+    let checkouts = checked_out_submodules(dir);
+    for checkout in &checkouts {
+        set_local_git_config(checkout, "user.name", &profile.name)?;
+        set_local_git_config(checkout, "user.email", &profile.email)?;
+        if matches!(profile.auth, AuthMethod::SSH { .. }) {
+            let host = crate::git::origin_host(checkout);
+            set_local_git_config(checkout, "core.sshCommand", &ssh::profile_ssh_command(profile, host.as_deref()))?;
+        }
+    }
+    Ok(checkouts.len())
+}
+
+/// Split a remote URL into host and repository path, if it is a network URL
+///
+/// Understands `http(s)://[user@]host[:port]/path`, `ssh://[user@]host[:port]/path` and
+/// scp-like `user@host:path`. Relative and local URLs (`C:/repo` included) yield `None`.
+/// The host comes without its port.
+pub fn split_remote_url(url: &str) -> Option<(String, String)> {
+    if let Some(rest) = ["https://", "http://", "ssh://"].iter().find_map(|p| url.strip_prefix(p)) {
+        let (authority, path) = rest.split_once('/')?;
+        let host_port = authority.rsplit('@').next()?;
+        let host = match host_port.strip_prefix('[') {
+            // IPv6 literal: [::1]:22
+            Some(v6) => v6.split(']').next()?,
+            None => host_port.split(':').next()?,
+        };
+        return Some((host.to_string(), path.to_string())).filter(|(h, _)| !h.is_empty());
+    }
+
+    if url.contains("://") || url.starts_with('.') || url.starts_with('/') {
+        return None;
+    }
+
+    // Like git: a colon after a slash makes it a local path, and so does a drive letter
+    let (authority, path) = url.split_once(':')?;
+    if authority.contains(['/', '\\']) || (authority.len() == 1 && authority.chars().all(|c| c.is_ascii_alphabetic())) {
+        return None;
+    }
+    let host = authority.rsplit('@').next()?;
+    let host = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+    Some((host.to_string(), path.trim_start_matches('/').to_string())).filter(|(h, _)| !h.is_empty())
+}
+
+/// Whether a URL names a port (`ssh://host:7999/...`), which the scp-like form cannot carry
+fn has_port(url: &str) -> bool {
+    let Some(rest) = url.split_once("://").map(|(_, rest)| rest) else {
+        return false;
+    };
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or_default();
+    let after_host = host_port.rsplit_once(']').map_or(host_port, |(_, port)| port);
+    after_host.contains(':')
+}
+
+/// URL a submodule should use so it authenticates like the profile
+///
+/// SSH profiles get scp-like `git@host:path`, token profiles get HTTPS.
+/// Returns `None` when the URL already matches or cannot be rewritten: a URL with a
+/// port is left alone, as the port belongs to its protocol.
+pub fn rewrite_url(url: &str, auth: &AuthMethod) -> Option<String> {
+    if has_port(url) {
+        return None;
+    }
+    let (host, path) = split_remote_url(url)?;
+    let host = if host.contains(':') { format!("[{}]", host) } else { host };
+    let rewritten = match auth {
+        AuthMethod::SSH { .. } => format!("git@{}:{}", host, path),
+        AuthMethod::Token { .. } => format!("https://{}/{}", host, path),
+    };
+    Some(rewritten).filter(|r| r != url)
+}
+
+/// Point the repository's submodules at the profile's protocol
+///
+/// Writes `submodule.<name>.url` (used by `git submodule update`) and, for
+/// submodules already checked out, their `remote.origin.url` and ssh command.
+/// Returns the number of submodules rewritten.
+pub fn rewrite_submodule_urls(profile: &Profile, dir: &Path) -> Result<usize> {
+    let mut rewritten = 0;
+
+    for submodule in list_submodules(dir) {
+        let Some(url) = rewrite_url(&submodule.url, &profile.auth) else {
+            continue;
+        };
+
+        set_local_git_config(dir, &format!("submodule.{}.url", submodule.name), &url)?;
+
+        let checkout = submodule.path.as_ref().map(|p| dir.join(p));
+        if let Some(checkout) = checkout.filter(|c| c.join(".git").exists()) {
+            set_local_git_config(&checkout, "remote.origin.url", &url)?;
+            let host = split_remote_url(&url).map(|(host, _)| host);
+            if matches!(profile.auth, AuthMethod::SSH { .. }) {
+                set_local_git_config(&checkout, "core.sshCommand", &ssh::profile_ssh_command(profile, host.as_deref()))?;
+            }
+        }
+
+        rewritten += 1;
+    }
+
+    Ok(rewritten)
+}
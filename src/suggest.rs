@@ -0,0 +1,281 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, Select};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{get_gix_home_dir, load_config, save_config, write_atomic, Config};
+use crate::rules::Rule;
+use crate::stats::UsageEntry;
+
+/// Commands a group needs before a rule is worth proposing
+const MIN_RUNS: usize = 5;
+
+/// The hint after intercepted commands looks at the log at most this often
+const HINT_INTERVAL_DAYS: i64 = 7;
+
+/// What ~/.gix/suggestions.json remembers between runs
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct SuggestState {
+    /// RFC 3339 time the usage log was last checked for the hint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_hint: Option<String>,
+    /// Suggestions answered with "never" (`pattern → profile`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dismissed: Vec<String>,
+}
+
+fn state_path() -> Result<PathBuf> {
+    Ok(get_gix_home_dir()?.join("suggestions.json"))
+}
+
+fn load_state() -> SuggestState {
+    state_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &SuggestState) -> Result<()> {
+    write_atomic(&state_path()?, &serde_json::to_string_pretty(state)?, false)
+}
+
+/// A host rule the usage log argues for
+#[derive(Serialize, Debug, Clone)]
+pub struct Suggestion {
+    /// `host/owner/*` pattern, as taken by 'gix rule add --host'
+    pub pattern: String,
+    pub profile: String,
+    /// Commands run with the profile under the pattern
+    pub runs: usize,
+    /// Distinct repositories they ran in
+    pub repos: usize,
+}
+
+impl Suggestion {
+    fn key(&self) -> String {
+        format!("{} → {}", self.pattern, self.profile)
+    }
+
+    fn rule(&self) -> Rule {
+        Rule::Host { pattern: self.pattern.clone(), profile: self.profile.clone() }
+    }
+}
+
+/// Remote URL an entry worked with: a clone's URL argument, else the origin of its directory
+fn entry_url(entry: &UsageEntry, origins: &mut BTreeMap<String, Option<String>>) -> Option<String> {
+    if entry.subcommand() == "clone" {
+        let args: Vec<String> = entry.command.split_whitespace().skip(1).map(|a| a.to_string()).collect();
+        return crate::git::clone_url(&args).map(|u| u.to_string());
+    }
+    origins
+        .entry(entry.dir.clone())
+        .or_insert_with(|| {
+            let dir = Path::new(&entry.dir);
+            dir.exists().then(|| crate::git::git_config_in(dir, "remote.origin.url")).flatten()
+        })
+        .clone()
+}
+
+/// `host/owner/*` pattern of a remote URL, through gix's and ssh_config's host aliases
+fn owner_pattern(config: &Config, url: &str) -> Option<String> {
+    let (host, path) = crate::submodule::split_remote_url(url)?;
+    let mut host = crate::ssh_config::real_host(config, &host);
+    if crate::rules::is_ssh_url(url) {
+        host = crate::ssh::resolve_host_alias(&host).unwrap_or(host);
+    }
+    let owner = path.trim_start_matches('/').split('/').next().filter(|o| !o.is_empty())?;
+    // A repository directly under the host has no owner to generalize to
+    if path.trim_start_matches('/').trim_end_matches('/') == owner {
+        return None;
+    }
+    Some(format!("{}/{}/*", host.to_ascii_lowercase(), owner.to_ascii_lowercase()))
+}
+
+/// Rules the usage log argues for: owners whose repositories were always used with one
+/// profile, at least `MIN_RUNS` times, that no rule or profile host covers yet
+pub fn suggestions(config: &Config, entries: &[UsageEntry]) -> Vec<Suggestion> {
+    // pattern → profile → (runs, repository directories), plus the URLs seen under the pattern
+    let mut groups: BTreeMap<String, BTreeMap<String, (usize, Vec<String>)>> = BTreeMap::new();
+    let mut urls: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut origins = BTreeMap::new();
+    for entry in entries {
+        let Some(url) = entry_url(entry, &mut origins) else {
+            continue;
+        };
+        let Some(pattern) = owner_pattern(config, &url) else {
+            continue;
+        };
+        let (runs, dirs) = groups.entry(pattern.clone()).or_default().entry(entry.profile.clone()).or_default();
+        *runs += 1;
+        // A clone ran in the parent directory: the URL is what identifies the repository
+        let repo = if entry.subcommand() == "clone" { url.clone() } else { entry.dir.clone() };
+        if !dirs.contains(&repo) {
+            dirs.push(repo);
+        }
+        let seen = urls.entry(pattern).or_default();
+        if !seen.contains(&url) {
+            seen.push(url);
+        }
+    }
+
+    let mut found = Vec::new();
+    for (pattern, profiles) in groups {
+        // "Always": a single profile under the pattern
+        if profiles.len() != 1 {
+            continue;
+        }
+        let Some((name, (runs, dirs))) = profiles.into_iter().next() else {
+            continue;
+        };
+        let Some(profile) = config.find_profile(&name).filter(|_| runs >= MIN_RUNS) else {
+            continue;
+        };
+        // Already decided: every URL is covered, or one maps elsewhere
+        let matched: Vec<Vec<&crate::profile::Profile>> =
+            urls[&pattern].iter().map(|u| crate::rules::profiles_for_url(config, u)).collect();
+        let elsewhere = matched.iter().flatten().any(|p| !p.is_named(&profile.profile_name));
+        if elsewhere || matched.iter().all(|m| !m.is_empty()) {
+            continue;
+        }
+        found.push(Suggestion { pattern, profile: profile.profile_name.clone(), runs, repos: dirs.len() });
+    }
+    found.sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.pattern.cmp(&b.pattern)));
+    found
+}
+
+fn pending_suggestions(config: &Config, state: &SuggestState, all: bool) -> Result<Vec<Suggestion>> {
+    let entries = crate::stats::read_usage_log()?;
+    Ok(suggestions(config, &entries)
+        .into_iter()
+        .filter(|s| all || !state.dismissed.contains(&s.key()))
+        .collect())
+}
+
+fn describe(suggestion: &Suggestion) -> String {
+    format!(
+        "You always use '{}' for {} ({} command(s) in {} repositor{})",
+        suggestion.profile,
+        suggestion.pattern,
+        suggestion.runs,
+        suggestion.repos,
+        if suggestion.repos == 1 { "y" } else { "ies" }
+    )
+}
+
+/// A gentle hint after an intercepted command, at most once every `HINT_INTERVAL_DAYS`
+///
+/// Only in a terminal, and never when the usage log or the hint is turned off.
+pub fn hint_after_command(config: &Config) {
+    if !config.suggestions || !config.logging || !crate::settings::interactive() {
+        return;
+    }
+    let mut state = load_state();
+    let now = chrono::Local::now();
+    let due = state
+        .last_hint
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .is_none_or(|t| (now.fixed_offset() - t).num_days() >= HINT_INTERVAL_DAYS);
+    if !due {
+        return;
+    }
+    // Recorded first: a read-only install simply gets no hints
+    state.last_hint = Some(now.to_rfc3339());
+    if save_state(&state).is_err() {
+        return;
+    }
+
+    let Ok(pending) = pending_suggestions(config, &state, false) else {
+        return;
+    };
+    let Some(first) = pending.first() else {
+        return;
+    };
+    let more = match pending.len() {
+        1 => String::new(),
+        n => format!(" (+{} more)", n - 1),
+    };
+    crate::settings::banner(format!(
+        "\x1b[1;36m💡 {}: create a rule?{} Run 'gix suggest'.\x1b[0m",
+        describe(first),
+        more
+    ));
+}
+
+/// Handle the 'gix suggest' command: propose host rules learned from the usage log
+pub fn handle_suggest_command(yes: bool, all: bool) -> Result<()> {
+    let mut config = load_config()?;
+    let mut state = load_state();
+    let pending = pending_suggestions(&config, &state, all)?;
+
+    if crate::settings::json_output() {
+        return crate::settings::print_json(&pending);
+    }
+    if pending.is_empty() {
+        println!("\x1b[1;32m✓ No rules to suggest.\x1b[0m");
+        if !config.logging {
+            println!("   Suggestions come from the usage log, which is off ('gix settings set logging true').");
+        } else {
+            println!(
+                "   gix proposes a rule once an owner's repositories were used {} times, always with the same profile.",
+                MIN_RUNS
+            );
+        }
+        return Ok(());
+    }
+
+    println!("\x1b[1;36m💡 Rules suggested by your usage:\x1b[0m\n");
+    let interactive = crate::settings::interactive();
+    let mut added = 0;
+    let mut dismissed = false;
+    for suggestion in &pending {
+        println!("   {}", describe(suggestion));
+        println!("      rule: {}", suggestion.rule().describe());
+
+        let choice = if yes {
+            0
+        } else if interactive {
+            Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Create this rule?")
+                .items(&["Yes", "Not now", "Never suggest it again"])
+                .default(0)
+                .interact()?
+        } else {
+            println!("      add it with: gix rule add --host '{}' '{}'", suggestion.pattern, suggestion.profile);
+            1
+        };
+        match choice {
+            0 => {
+                let rule = suggestion.rule();
+                if !config.rules.contains(&rule) {
+                    config.rules.push(rule);
+                    added += 1;
+                }
+                println!("      \x1b[1;32m✓ Rule added\x1b[0m");
+            }
+            2 => {
+                if !state.dismissed.contains(&suggestion.key()) {
+                    state.dismissed.push(suggestion.key());
+                    dismissed = true;
+                }
+                println!("      Not suggested again (see them all with 'gix suggest --all').");
+            }
+            _ => {}
+        }
+        println!();
+    }
+
+    if added > 0 {
+        save_config(&config)?;
+        println!("\x1b[1;32m✓ Added {} rule(s). New clones under them pick the profile by themselves.\x1b[0m", added);
+    } else if !yes && !interactive {
+        println!("Pass --yes to add them all.");
+    }
+    if dismissed {
+        save_state(&state)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,262 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cli::TelemetryAction;
+use crate::config::{get_gix_home_dir, load_config, save_config, write_atomic};
+
+/// Where aggregated reports go (GIX_TELEMETRY_URL overrides it)
+const TELEMETRY_ENDPOINT: &str = "https://telemetry.gix.dev/v1/report";
+
+/// Reports are sent at most this often, whatever happens to the attempt
+const SEND_INTERVAL_DAYS: i64 = 7;
+
+/// Git subcommands counted by name; anything else (aliases, typos) is counted as "git other"
+const GIT_COMMANDS: [&str; 24] = [
+    "add", "am", "bisect", "branch", "checkout", "cherry-pick", "clone", "commit", "diff", "fetch", "init", "log",
+    "merge", "pull", "push", "rebase", "remote", "reset", "restore", "revert", "send-email", "stash", "status",
+    "submodule",
+];
+
+/// Counts kept in ~/.gix/telemetry.json until they are sent
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct TelemetryData {
+    /// Date of the first count since the last successful send
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    since: Option<String>,
+    #[serde(default)]
+    commands: BTreeMap<String, u64>,
+    #[serde(default)]
+    errors: BTreeMap<String, u64>,
+    /// RFC 3339 time of the last send attempt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_attempt: Option<String>,
+}
+
+/// Exactly what leaves the machine: no paths, names, emails, hosts or identifiers
+#[derive(Serialize, Debug)]
+struct Payload<'a> {
+    version: &'a str,
+    os: &'a str,
+    arch: &'a str,
+    since: Option<&'a str>,
+    commands: &'a BTreeMap<String, u64>,
+    errors: &'a BTreeMap<String, u64>,
+}
+
+fn telemetry_path() -> Result<PathBuf> {
+    Ok(get_gix_home_dir()?.join("telemetry.json"))
+}
+
+fn load_data() -> TelemetryData {
+    telemetry_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_data(data: &TelemetryData) -> Result<()> {
+    write_atomic(&telemetry_path()?, &serde_json::to_string_pretty(data)?, false)
+}
+
+fn payload(data: &TelemetryData) -> Payload<'_> {
+    Payload {
+        version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        since: data.since.as_deref(),
+        commands: &data.commands,
+        errors: &data.errors,
+    }
+}
+
+fn enabled() -> bool {
+    load_config().is_ok_and(|c| c.telemetry)
+}
+
+/// Counted name of a command: the gix subcommand (with its action), or `git <subcommand>`
+pub fn command_label(matches: &clap::ArgMatches, git_args: &[String]) -> String {
+    match matches.subcommand() {
+        Some((name, sub)) => match sub.subcommand_name() {
+            Some(action) => format!("{} {}", name, action),
+            None => name.to_string(),
+        },
+        None => match git_args.first().map(|a| a.as_str()) {
+            Some(command) if GIT_COMMANDS.contains(&command) => format!("git {}", command),
+            Some(_) => "git other".to_string(),
+            None => "help".to_string(),
+        },
+    }
+}
+
+/// Category of an error, without its message (which may carry paths or names)
+fn error_class(error: &anyhow::Error) -> String {
+    for cause in error.chain() {
+        if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+            return format!("io:{:?}", io.kind()).to_lowercase();
+        }
+        if cause.downcast_ref::<serde_json::Error>().is_some() {
+            return "json".to_string();
+        }
+        if cause.downcast_ref::<dialoguer::Error>().is_some() {
+            return "prompt".to_string();
+        }
+    }
+    "other".to_string()
+}
+
+/// Whether this process counted its command, and so may count its error
+static COUNTED: AtomicBool = AtomicBool::new(false);
+
+/// Count a command run, then send the week's counts if they are due
+///
+/// Does nothing unless telemetry was turned on. Prompt helpers and completion are not
+/// counted: they run constantly and would drown everything else. Neither are the
+/// telemetry commands, which would count themselves (and the background send).
+pub fn record_command(label: &str) {
+    if matches!(
+        label,
+        "whoami" | "complete" | "credential-helper get" | "credential-helper store" | "credential-helper erase"
+    ) || label.starts_with("telemetry")
+        || !enabled()
+    {
+        return;
+    }
+    COUNTED.store(true, Ordering::Relaxed);
+    let mut data = load_data();
+    data.since.get_or_insert_with(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+    *data.commands.entry(label.to_string()).or_default() += 1;
+
+    // The first report covers a full week rather than the first command
+    let now = chrono::Local::now();
+    let last_attempt = data.last_attempt.get_or_insert_with(|| now.to_rfc3339());
+    let due = chrono::DateTime::parse_from_rfc3339(last_attempt)
+        .map_or(true, |t| (now.fixed_offset() - t).num_days() >= SEND_INTERVAL_DAYS);
+    if due {
+        // Recorded before sending so an unreachable endpoint costs one try a week
+        data.last_attempt = Some(now.to_rfc3339());
+    }
+    if save_data(&data).is_ok() && due {
+        send_in_background();
+    }
+}
+
+/// Run 'gix telemetry send' detached: the command being counted never waits on the network
+fn send_in_background() {
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+    let mut command = Command::new(exe);
+    command
+        .args(["telemetry", "send"])
+        .env_remove(crate::settings::NESTED_ENV)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Out of the terminal's process group, so Ctrl-C on the git command doesn't reach it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    #[cfg(windows)]
+    std::os::windows::process::CommandExt::creation_flags(&mut command, 0x0000_0008); // DETACHED_PROCESS
+    let _ = command.spawn();
+}
+
+/// Count the class of an error a command failed with
+pub fn record_error(error: &anyhow::Error) {
+    if !COUNTED.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut data = load_data();
+    *data.errors.entry(error_class(error)).or_default() += 1;
+    let _ = save_data(&data);
+}
+
+/// POST the aggregated counts; the body goes to curl on stdin
+fn send(data: &TelemetryData) -> Result<()> {
+    let url = std::env::var("GIX_TELEMETRY_URL")
+        .ok()
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| TELEMETRY_ENDPOINT.to_string());
+    let mut child = Command::new("curl")
+        .args(["-fsS", "--max-time", "3", "-X", "POST", "-H", "Content-Type: application/json", "--data-binary", "@-"])
+        .arg(&url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to run curl")?;
+    child
+        .stdin
+        .take()
+        .context("Failed to talk to curl")?
+        .write_all(serde_json::to_string(&payload(data))?.as_bytes())?;
+    if !child.wait()?.success() {
+        anyhow::bail!("Could not reach {}", url);
+    }
+    Ok(())
+}
+
+fn set_enabled(enabled: bool) -> Result<()> {
+    let mut config = load_config()?;
+    config.telemetry = enabled;
+    save_config(&config)
+}
+
+/// Handle the 'gix telemetry' command
+pub fn handle_telemetry_command(action: TelemetryAction) -> Result<()> {
+    match action {
+        TelemetryAction::On => {
+            set_enabled(true)?;
+            println!("\x1b[1;32m✓ Telemetry enabled.\x1b[0m");
+            println!("   gix counts commands and error classes locally and sends the totals once a week,");
+            println!("   with the gix version and platform. Paths, names, emails and hosts are never included.");
+            println!("   See what would be sent with '\x1b[1mgix telemetry show\x1b[0m'; turn it off with '\x1b[1mgix telemetry off\x1b[0m'.");
+        }
+        TelemetryAction::Off => {
+            set_enabled(false)?;
+            if let Ok(path) = telemetry_path() {
+                let _ = fs::remove_file(path);
+            }
+            println!("\x1b[1;32m✓ Telemetry disabled and unsent counts deleted.\x1b[0m");
+        }
+        TelemetryAction::Show => {
+            let data = load_data();
+            if !crate::settings::json_output() {
+                let state = if enabled() { "\x1b[1;32menabled\x1b[0m" } else { "\x1b[1;33mdisabled\x1b[0m" };
+                println!("📡 Telemetry is {}. Next report, exactly as it would be sent:\n", state);
+            }
+            crate::settings::print_json(&payload(&data))?;
+        }
+        TelemetryAction::Send => {
+            if !enabled() {
+                anyhow::bail!("Telemetry is disabled. Enable it with 'gix telemetry on'.");
+            }
+            let mut data = load_data();
+            data.last_attempt = Some(chrono::Local::now().to_rfc3339());
+            let result = send(&data);
+            if result.is_ok() {
+                data.since = None;
+                data.commands.clear();
+                data.errors.clear();
+            }
+            save_data(&data)?;
+            result?;
+            println!("\x1b[1;32m✓ Report sent.\x1b[0m");
+        }
+        TelemetryAction::Reset => {
+            let mut data = load_data();
+            data.since = None;
+            data.commands.clear();
+            data.errors.clear();
+            save_data(&data)?;
+            println!("\x1b[1;32m✓ Unsent counts deleted.\x1b[0m");
+        }
+    }
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Current version of gix (from Cargo.toml)
@@ -8,6 +10,127 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 /// GitHub repository for updates
 pub const REPO_URL: &str = "https://github.com/elmanci2/gix";
 pub const RELEASES_API: &str = "https://api.github.com/repos/elmanci2/gix/releases/latest";
+/// All releases, newest first (includes pre-releases)
+pub const ALL_RELEASES_API: &str = "https://api.github.com/repos/elmanci2/gix/releases?per_page=1";
+/// Release asset listing the SHA-256 of every other asset (written by the release workflow)
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+const INSTALL_COMMAND: &str =
+    "curl -fsSL https://raw.githubusercontent.com/elmanci2/gix/refs/heads/master/install.sh | bash";
+
+/// A GitHub release, as far as updating needs it
+#[derive(Deserialize, Debug)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    /// Version without the tag's `v` prefix
+    fn version(&self) -> &str {
+        self.tag_name.strip_prefix('v').unwrap_or(&self.tag_name)
+    }
+
+    fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+/// How the running binary was installed, judged from where it lives
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InstallMethod {
+    /// A keg under Homebrew's Cellar (`<prefix>/Cellar/<formula>/<version>/bin/gix`)
+    Homebrew { formula: String },
+    /// A Scoop app (`<root>/apps/<app>/<version>/gix.exe`)
+    Scoop { app: String },
+    /// install.sh, a release archive or `cargo install`: gix replaces itself
+    Standalone,
+}
+
+impl InstallMethod {
+    fn detect(exe: &Path) -> InstallMethod {
+        let parts: Vec<String> = exe.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+        let after = |marker: &str| {
+            parts
+                .iter()
+                .position(|p| p.eq_ignore_ascii_case(marker))
+                .and_then(|i| parts.get(i + 1))
+                .cloned()
+        };
+
+        // Keg: <Cellar>/<formula>/<version>/bin/gix, with Homebrew's receipt in <version>
+        let keg = exe.parent().and_then(|bin| bin.parent());
+        if let Some(formula) = after("Cellar") {
+            if keg.is_some_and(|k| k.join("INSTALL_RECEIPT.json").is_file()) || parts.iter().any(|p| p.contains("brew")) {
+                return InstallMethod::Homebrew { formula };
+            }
+        }
+        // App: <scoop>/apps/<app>/<version>/gix.exe, with Scoop's install.json next to it
+        if let Some(app) = after("apps") {
+            let receipt = exe.parent().is_some_and(|d| d.join("install.json").is_file() && d.join("manifest.json").is_file());
+            if receipt || parts.iter().any(|p| p.eq_ignore_ascii_case("scoop")) {
+                return InstallMethod::Scoop { app };
+            }
+        }
+        InstallMethod::Standalone
+    }
+
+    /// The package manager and its upgrade command, for managed installs
+    fn upgrade_command(&self) -> Option<(&'static str, Vec<String>)> {
+        match self {
+            InstallMethod::Homebrew { formula } => Some(("Homebrew", vec!["brew".into(), "upgrade".into(), formula.clone()])),
+            InstallMethod::Scoop { app } => Some(("Scoop", vec!["scoop".into(), "update".into(), app.clone()])),
+            InstallMethod::Standalone => None,
+        }
+    }
+}
+
+/// Update through the package manager that owns the binary instead of overwriting it
+///
+/// Returns false for standalone installs, which update themselves.
+fn update_with_package_manager(dry_run: bool, yes: bool) -> Result<bool> {
+    let exe = std::env::current_exe()
+        .and_then(|p| p.canonicalize())
+        .context("Could not locate the running gix binary")?;
+    let Some((manager, command)) = InstallMethod::detect(&exe).upgrade_command() else {
+        return Ok(false);
+    };
+    let shown = command.join(" ");
+    println!("\n   gix was installed with {}, which manages {}.", manager, exe.display());
+    if dry_run {
+        println!("\n\x1b[1;33mDry run:\x1b[0m would run '{}'.", shown);
+        return Ok(true);
+    }
+
+    if !yes && !crate::settings::interactive() {
+        anyhow::bail!("Not asking without a terminal: run 'gix update --yes' or '{}'", shown);
+    }
+    if !yes
+        && !dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt(format!("Run '{}' now?", shown))
+            .default(true)
+            .interact()?
+    {
+        println!("\nTo update, run:\n   {}", shown);
+        return Ok(true);
+    }
+    println!("\n\x1b[1;36m▶️  {}\x1b[0m", shown);
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .status()
+        .with_context(|| format!("Failed to run {}; update with:\n   {}", command[0], shown))?;
+    if !status.success() {
+        anyhow::bail!("'{}' failed", shown);
+    }
+    println!("\n\x1b[1;32m✓ Updated with {}\x1b[0m", manager);
+    Ok(true)
+}
 
 /// Show version information
 pub fn show_version() {
@@ -18,15 +141,16 @@ pub fn show_version() {
 }
 
 /// Check for updates and optionally update
-pub fn handle_update(force: bool) -> Result<()> {
+pub fn handle_update(force: bool, dry_run: bool, yes: bool) -> Result<()> {
     println!("\x1b[1;36m🔄 Checking for updates...\x1b[0m\n");
 
-    // Try to get latest version from GitHub API
-    match get_latest_version() {
-        Ok(latest) => {
+    // Try to get latest release from GitHub API
+    match get_latest_release() {
+        Ok(release) => {
+            let latest = release.version();
             let current = semver::Version::parse(VERSION)
                 .unwrap_or_else(|_| semver::Version::new(0, 0, 0));
-            let latest_ver = semver::Version::parse(&latest)
+            let latest_ver = semver::Version::parse(latest)
                 .unwrap_or_else(|_| semver::Version::new(0, 0, 0));
 
             println!("   Current version: \x1b[1m{}\x1b[0m", VERSION);
@@ -39,17 +163,29 @@ pub fn handle_update(force: bool) -> Result<()> {
                     println!("\n\x1b[1;32m✓ Already on latest version.\x1b[0m (force update requested)");
                 }
 
-                println!("\nTo update manually, run:");
-                println!("   curl -fsSL https://raw.githubusercontent.com/elmanci2/gix/refs/heads/master/install.sh | bash");
-                println!();
+                // Homebrew and Scoop would be left with a binary they no longer recognise
+                if update_with_package_manager(dry_run, yes)? {
+                    return Ok(());
+                }
+
+                if dry_run {
+                    return perform_self_update(&release, true);
+                }
 
-                // Ask to update automatically
-                if dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
-                    .with_prompt(format!("Do you want to install version {} automatically now?", latest))
-                    .default(true)
-                    .interact()?
+                // Replacing the binary unattended (cron, CI) has to be asked for
+                if !yes && !crate::settings::interactive() {
+                    anyhow::bail!("Not asking without a terminal: run 'gix update --yes' to install version {}", latest);
+                }
+                if yes
+                    || dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                        .with_prompt(format!("Do you want to install version {} automatically now?", latest))
+                        .default(true)
+                        .interact()?
                 {
-                    perform_self_update()?;
+                    perform_self_update(&release, false)?;
+                } else {
+                    println!("\nTo update manually, run:");
+                    println!("   {}", INSTALL_COMMAND);
                 }
             } else {
                 println!("\n\x1b[1;32m✓ You are running the latest version!\x1b[0m");
@@ -67,38 +203,255 @@ pub fn handle_update(force: bool) -> Result<()> {
     Ok(())
 }
 
-/// Perform self-update by running the install script
-fn perform_self_update() -> Result<()> {
-    println!("\n\x1b[1;36m⬇️  Downloading and installing update...\x1b[0m");
+/// Name of the release asset built for this platform, as in the release workflow
+fn platform_asset_name(tag: &str) -> Result<String> {
+    let os = match std::env::consts::OS {
+        "linux" => "linux",
+        "macos" => "darwin",
+        "windows" => "windows",
+        other => anyhow::bail!("No release builds for {}", other),
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "x86_64",
+        "aarch64" => "aarch64",
+        "arm" => "armv7",
+        other => anyhow::bail!("No release builds for {}", other),
+    };
+    let extension = if os == "windows" { "zip" } else { "tar.gz" };
+    Ok(format!("gix-{}-{}-{}.{}", tag, os, arch, extension))
+}
 
-    let script_url = "https://raw.githubusercontent.com/elmanci2/gix/refs/heads/master/install.sh";
-    
-    // We execute the install script just like the manual command
-    let status = Command::new("bash")
-        .arg("-c")
-        .arg(format!("curl -fsSL {} | bash", script_url))
+fn download(url: &str, dest: &Path) -> Result<()> {
+    let status = Command::new("curl")
+        .args(["-fsSL", "-H", "User-Agent: gix-cli", "-o"])
+        .arg(dest)
+        .arg(url)
         .status()
-        .context("Failed to execute update script")?;
+        .context("Failed to download the update. Make sure curl is installed.")?;
+    if !status.success() {
+        anyhow::bail!("Download failed: {}", url);
+    }
+    Ok(())
+}
 
-    if status.success() {
-        println!("\n\x1b[1;32m✓ Update completed successfully!\x1b[0m");
-        println!("Please restart your terminal or run 'source ~/.zshrc' (or equivalent) if the command is not found.");
-    } else {
-        anyhow::bail!("Update script failed with exit code: {}", status.code().unwrap_or(1));
+/// SHA-256 of a file, from whichever checksum tool the system has
+fn sha256_file(path: &Path) -> Result<String> {
+    let path = path.to_string_lossy();
+    let tools: [(&str, Vec<&str>); 3] = [
+        ("sha256sum", vec![&path]),
+        ("shasum", vec!["-a", "256", &path]),
+        ("certutil", vec!["-hashfile", &path, "SHA256"]),
+    ];
+    for (tool, args) in tools {
+        let Ok(output) = Command::new(tool).args(&args).output() else {
+            continue;
+        };
+        if !output.status.success() {
+            continue;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if let Some(hash) = stdout
+            .split_whitespace()
+            .find(|t| t.len() == 64 && t.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Ok(hash.to_ascii_lowercase());
+        }
     }
+    anyhow::bail!("No SHA-256 tool found (sha256sum, shasum or certutil)")
+}
+
+/// Expected SHA-256 of `asset` in a `sha256sum`-style listing
+fn expected_checksum(listing: &str, asset: &str) -> Option<String> {
+    listing.lines().find_map(|line| {
+        let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+        (name.trim().trim_start_matches('*') == asset).then(|| hash.to_ascii_lowercase())
+    })
+}
 
+/// Put `new` in place of the running binary `exe`
+///
+/// A rename within the same directory is atomic: the binary is either the old or the
+/// new one, never half-written. Windows cannot replace a running executable, but can
+/// rename it, so the old binary is moved aside first.
+fn replace_binary(new: &Path, exe: &Path) -> Result<()> {
+    #[cfg(windows)]
+    {
+        let old = exe.with_extension("exe.old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old).with_context(|| format!("Failed to move {} aside", exe.display()))?;
+        if let Err(e) = fs::rename(new, exe) {
+            let _ = fs::rename(&old, exe);
+            return Err(e).with_context(|| format!("Failed to replace {}", exe.display()));
+        }
+    }
+    #[cfg(not(windows))]
+    fs::rename(new, exe).with_context(|| format!("Failed to replace {}", exe.display()))?;
     Ok(())
 }
 
-/// Get latest version from GitHub releases
-fn get_latest_version() -> Result<String> {
+/// Download the release asset for this platform, verify its checksum and replace the
+/// running binary with it (`dry_run` stops before replacing)
+fn perform_self_update(release: &Release, dry_run: bool) -> Result<()> {
+    let exe = std::env::current_exe()
+        .and_then(|p| p.canonicalize())
+        .context("Could not locate the running gix binary")?;
+    let target = install_target(&exe)?;
+    let install_dir = target.parent().context("Invalid gix binary path")?.to_path_buf();
+
+    let asset_name = platform_asset_name(&release.tag_name)?;
+    let asset = release
+        .asset(&asset_name)
+        .with_context(|| format!("Release {} has no {}; update with:\n   {}", release.tag_name, asset_name, INSTALL_COMMAND))?;
+    let checksums = release.asset(CHECKSUMS_ASSET).with_context(|| {
+        format!("Release {} publishes no checksums, so it cannot be verified; update with:\n   {}", release.tag_name, INSTALL_COMMAND)
+    })?;
+
+    println!("\n\x1b[1;36m⬇️  Downloading {}...\x1b[0m", asset_name);
+    let tmp = std::env::temp_dir().join(format!("gix-update-{}", std::process::id()));
+    fs::create_dir_all(&tmp)?;
+    let result = download_and_verify(asset, checksums, &tmp, &install_dir).and_then(|new_binary| {
+        println!("   \x1b[1;32m✓\x1b[0m SHA-256 verified");
+        if dry_run {
+            let action = if target.exists() { "replace" } else { "install" };
+            println!("\n\x1b[1;33mDry run:\x1b[0m would {} {} with version {}.", action, target.display(), release.version());
+            let _ = fs::remove_file(&new_binary);
+            return Ok(());
+        }
+        let installed = if target.exists() {
+            replace_binary(&new_binary, &target)
+        } else {
+            fs::rename(&new_binary, &target).with_context(|| format!("Failed to install {}", target.display()))
+        };
+        installed.inspect_err(|_| {
+            let _ = fs::remove_file(&new_binary);
+        })?;
+        println!("\n\x1b[1;32m✓ Updated {} to version {}\x1b[0m", target.display(), release.version());
+        if target != exe {
+            warn_if_shadowed(&target);
+        }
+        Ok(())
+    });
+    let _ = fs::remove_dir_all(&tmp);
+    result
+}
+
+/// The user's own bin directory: $XDG_BIN_HOME or ~/.local/bin
+fn user_bin_dir() -> Result<PathBuf> {
+    let dirs = directories::BaseDirs::new().context("Could not determine home directory")?;
+    Ok(dirs
+        .executable_dir()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| dirs.home_dir().join(".local").join("bin")))
+}
+
+/// Binary the update writes: the running one, or with `--user` a copy in the user's bin
+/// directory when the running one belongs to a system package or shared install
+fn install_target(exe: &Path) -> Result<PathBuf> {
+    if crate::config::is_writable(exe) {
+        return Ok(exe.to_path_buf());
+    }
+    let user_bin = user_bin_dir()?;
+    if !crate::config::user_scope_requested() {
+        anyhow::bail!(
+            "{} is read-only for you (a system package or shared install?).\n\
+             Ask an administrator to update it, or install your own copy into {} with 'gix update --user'.",
+            exe.display(),
+            user_bin.display()
+        );
+    }
+    fs::create_dir_all(&user_bin).with_context(|| format!("Failed to create {}", user_bin.display()))?;
+    let name = exe.file_name().context("Invalid gix binary path")?;
+    let target = user_bin.join(name);
+    if !crate::config::is_writable(&target) {
+        anyhow::bail!("{} is not writable either; nothing was changed", user_bin.display());
+    }
+    println!("   Installing your own copy into \x1b[1m{}\x1b[0m ({} is read-only)", user_bin.display(), exe.display());
+    Ok(target)
+}
+
+/// Point out when PATH still finds another gix before the copy just installed
+fn warn_if_shadowed(installed: &Path) {
+    let name = installed.file_name().unwrap_or_default();
+    let first = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).map(|dir| dir.join(name)).find(|p| p.is_file()))
+        .unwrap_or_default();
+    let dir = installed.parent().unwrap_or(installed);
+    match first {
+        Some(found) if found.canonicalize().ok().as_deref() == Some(installed) => {}
+        Some(found) => println!(
+            "\x1b[1;33m⚠ 'gix' still runs {}: put {} before it in PATH\x1b[0m",
+            found.display(),
+            dir.display()
+        ),
+        None => println!("\x1b[1;33m⚠ {} is not in PATH: add it to run the new copy as 'gix'\x1b[0m", dir.display()),
+    }
+}
+
+/// Fetch and check the asset; returns the new binary, staged next to the current one
+fn download_and_verify(asset: &ReleaseAsset, checksums: &ReleaseAsset, tmp: &Path, install_dir: &Path) -> Result<PathBuf> {
+    let archive = tmp.join(&asset.name);
+    download(&asset.browser_download_url, &archive)?;
+    let listing_path = tmp.join(CHECKSUMS_ASSET);
+    download(&checksums.browser_download_url, &listing_path)?;
+
+    let listing = fs::read_to_string(&listing_path)?;
+    let expected = expected_checksum(&listing, &asset.name)
+        .with_context(|| format!("{} does not list {}", CHECKSUMS_ASSET, asset.name))?;
+    let actual = sha256_file(&archive)?;
+    if actual != expected {
+        anyhow::bail!("Checksum mismatch for {} (expected {}, got {}); nothing was changed", asset.name, expected, actual);
+    }
+
+    // bsdtar, which Windows ships as tar, also reads zip files
+    let status = Command::new("tar")
+        .arg("-xf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(tmp)
+        .status()
+        .context("Failed to run tar")?;
+    if !status.success() {
+        anyhow::bail!("Failed to extract {}", asset.name);
+    }
+    let binary = tmp.join(if cfg!(windows) { "gix.exe" } else { "gix" });
+    if !binary.exists() {
+        anyhow::bail!("{} does not contain the gix binary", asset.name);
+    }
+
+    // Staged in the install directory so the final rename never crosses filesystems
+    let staged = install_dir.join(format!(".gix-update-{}", std::process::id()));
+    fs::copy(&binary, &staged)
+        .with_context(|| format!("Cannot write to {} (reinstall with sudo, or with:\n   {})", install_dir.display(), INSTALL_COMMAND))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+
+    // A binary that doesn't start must not replace a working one
+    let runs = Command::new(&staged).arg("--version").output().is_ok_and(|o| o.status.success());
+    if !runs {
+        let _ = fs::remove_file(&staged);
+        anyhow::bail!("The downloaded binary does not run on this system; nothing was changed");
+    }
+    Ok(staged)
+}
+
+/// Get the latest release from GitHub, following the update channel
+fn get_latest_release() -> Result<Release> {
     // Use curl to fetch from GitHub API (avoids needing reqwest dependency)
+    let channel = crate::config::load_config().map(|c| c.update_channel).unwrap_or_default();
+    let api = match channel {
+        crate::settings::UpdateChannel::Stable => RELEASES_API,
+        crate::settings::UpdateChannel::Prerelease => ALL_RELEASES_API,
+    };
+
     let output = Command::new("curl")
         .args([
             "-sS",
             "-H", "Accept: application/vnd.github.v3+json",
             "-H", "User-Agent: gix-cli",
-            RELEASES_API,
+            api,
         ])
         .output()
         .context("Failed to check for updates. Make sure curl is installed.")?;
@@ -107,78 +460,264 @@ fn get_latest_version() -> Result<String> {
         anyhow::bail!("Failed to fetch release information");
     }
 
-    let body = String::from_utf8_lossy(&output.stdout);
-    
-    // Simple JSON parsing for tag_name
-    if let Some(start) = body.find("\"tag_name\"") {
-        let rest = &body[start..];
-        if let Some(colon) = rest.find(':') {
-            let after_colon = &rest[colon + 1..];
-            let trimmed = after_colon.trim();
-            if let Some(quote_start) = trimmed.find('"') {
-                let after_quote = &trimmed[quote_start + 1..];
-                if let Some(quote_end) = after_quote.find('"') {
-                    let version = &after_quote[..quote_end];
-                    // Remove 'v' prefix if present
-                    return Ok(version.strip_prefix('v').unwrap_or(version).to_string());
-                }
+    // The list endpoint returns an array, the latest endpoint a single release
+    let body: serde_json::Value = serde_json::from_slice(&output.stdout).context("Could not parse release information")?;
+    let release = match body {
+        serde_json::Value::Array(mut releases) if !releases.is_empty() => releases.swap_remove(0),
+        serde_json::Value::Array(_) => anyhow::bail!("No releases published yet"),
+        release => release,
+    };
+    serde_json::from_value(release).context("Could not parse version from response")
+}
+
+/// Outcome of one doctor check
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    /// Worth knowing, but nothing is broken
+    Notice,
+    Warning,
+    Error,
+}
+
+/// One check of 'gix doctor'
+#[derive(Serialize, Debug)]
+pub struct CheckResult {
+    pub check: String,
+    pub status: CheckStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+/// Collects the doctor's checks, printing each as it completes unless `--json` is set
+pub struct Doctor {
+    json: bool,
+    checks: Vec<CheckResult>,
+}
+
+impl Doctor {
+    fn new(json: bool) -> Self {
+        Doctor { json, checks: Vec::new() }
+    }
+
+    /// Begin a check; its result follows with one of the status methods
+    pub fn start(&mut self, check: &str) {
+        if !self.json {
+            print!("   Checking {}... ", check);
+        }
+        self.checks.push(CheckResult {
+            check: check.to_string(),
+            status: CheckStatus::Ok,
+            message: String::new(),
+            details: Vec::new(),
+        });
+    }
+
+    fn finish(&mut self, status: CheckStatus, message: String) {
+        if !self.json {
+            let symbol = match status {
+                CheckStatus::Ok => "\x1b[1;32m✓\x1b[0m",
+                CheckStatus::Notice | CheckStatus::Warning => "\x1b[1;33m⚠\x1b[0m",
+                CheckStatus::Error => "\x1b[1;31m✗\x1b[0m",
+            };
+            if message.is_empty() {
+                println!("{}", symbol);
+            } else {
+                println!("{} {}", symbol, message);
             }
         }
+        if let Some(check) = self.checks.last_mut() {
+            check.status = check.status.max(status);
+            check.message = message;
+        }
+    }
+
+    pub fn ok(&mut self, message: impl Into<String>) {
+        self.finish(CheckStatus::Ok, message.into());
+    }
+
+    /// Something to know about that is not a problem (e.g. a file not created yet)
+    pub fn notice(&mut self, message: impl Into<String>) {
+        self.finish(CheckStatus::Notice, message.into());
+    }
+
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.finish(CheckStatus::Warning, message.into());
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.finish(CheckStatus::Error, message.into());
+    }
+
+    /// An extra line under the current check
+    pub fn detail(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        if !self.json {
+            println!("      {}", message);
+        }
+        if let Some(check) = self.checks.last_mut() {
+            check.details.push(message);
+        }
     }
 
-    anyhow::bail!("Could not parse version from response")
+    /// A problem found within the current check, which then counts as a warning
+    pub fn detail_warning(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        if !self.json {
+            println!("      \x1b[1;33m⚠ {}\x1b[0m", message);
+        }
+        if let Some(check) = self.checks.last_mut() {
+            check.status = check.status.max(CheckStatus::Warning);
+            check.details.push(message);
+        }
+    }
+
+    /// No warnings or errors so far
+    fn all_ok(&self) -> bool {
+        self.checks.iter().all(|c| c.status <= CheckStatus::Notice)
+    }
 }
 
-/// Run diagnostics
-pub fn handle_doctor() -> Result<()> {
-    println!("\x1b[1;36m🩺 gix Doctor - System Diagnostics\x1b[0m\n");
+/// Report (and with `fix`, clear) references to profiles that no longer exist
+fn check_profile_references(doctor: &mut Doctor, fix: bool) -> Result<()> {
+    let mut config = crate::config::load_config()?;
+    let names: Vec<String> = config.profiles.iter().map(|p| p.profile_name.clone()).collect();
+    let exists = |name: &str| names.iter().any(|n| crate::profile::same_profile_name(n, name));
+    let mut dangling = vec![];
 
-    let mut all_ok = true;
+    if let Some(name) = config.default_profile.as_deref().filter(|n| !exists(n)) {
+        dangling.push(format!("default_profile names missing profile '{}'", name));
+    }
+    for rule in config.rules.iter().filter(|r| !exists(r.profile())) {
+        dangling.push(format!("rule {} names a missing profile", rule.describe()));
+    }
+    let global_dangling = !dangling.is_empty();
+
+    let mut local = crate::config::load_local_config();
+    if let Some(name) = local.as_ref().and_then(|l| l.selected_profile.as_deref()).filter(|n| !exists(n)) {
+        dangling.push(format!("this repository selects missing profile '{}'", name));
+    }
+
+    if dangling.is_empty() {
+        doctor.ok("All references point to existing profiles");
+        return Ok(());
+    }
+
+    if fix {
+        if global_dangling {
+            config.default_profile = config.default_profile.filter(|n| exists(n));
+            config.rules.retain(|r| exists(r.profile()));
+            crate::config::save_config(&config)?;
+        }
+        if let Some(local) = local.as_mut().filter(|l| l.selected_profile.as_deref().is_some_and(|n| !exists(n))) {
+            local.selected_profile = None;
+            crate::config::save_local_config(local)?;
+        }
+        // Fixed references no longer count as failures
+        doctor.notice(format!("{} stale reference(s)", dangling.len()));
+        for issue in &dangling {
+            doctor.detail(format!("✓ Removed: {}", issue));
+        }
+    } else {
+        doctor.warning(format!("{} stale reference(s)", dangling.len()));
+        for issue in &dangling {
+            doctor.detail_warning(format!("{} (run 'gix doctor --fix')", issue));
+        }
+    }
+    Ok(())
+}
+
+/// Run diagnostics
+pub fn handle_doctor(fix: bool) -> Result<()> {
+    let json = crate::settings::json_output();
+    if json && fix {
+        anyhow::bail!("--fix reports what it repairs as text; run it without --json");
+    }
+    if !json {
+        println!("\x1b[1;36m🩺 gix Doctor - System Diagnostics\x1b[0m\n");
+    }
+    let mut doctor = Doctor::new(json);
 
     // Check git installation
-    print!("   Checking git... ");
+    doctor.start("git");
     match Command::new("git").arg("--version").output() {
         Ok(output) if output.status.success() => {
             let version = String::from_utf8_lossy(&output.stdout);
-            println!("\x1b[1;32m✓\x1b[0m {}", version.trim());
-        }
-        _ => {
-            println!("\x1b[1;31m✗ Git not found!\x1b[0m");
-            all_ok = false;
+            doctor.ok(version.trim());
         }
+        _ => doctor.error("Git not found!"),
     }
 
     // Check ssh installation
-    print!("   Checking ssh... ");
-    match Command::new("ssh").arg("-V").output() {
-        Ok(output) => {
-            let version = String::from_utf8_lossy(&output.stderr);
-            println!("\x1b[1;32m✓\x1b[0m {}", version.trim());
+    doctor.start("ssh");
+    let ssh_program = crate::ssh::ssh_program(&Default::default());
+    match crate::ssh::check_ssh_program(&ssh_program) {
+        Ok(version) if ssh_program == "ssh" => doctor.ok(version),
+        Ok(version) => doctor.ok(format!("{} ({})", version, ssh_program)),
+        Err(e) => doctor.error(format!("SSH not usable: {}", e)),
+    }
+
+    // Check which git config files git (and so gix) reads
+    doctor.start("git config files");
+    let global: Vec<String> = crate::git::global_git_config_files()
+        .iter()
+        .filter(|p| p.exists())
+        .map(|p| p.display().to_string())
+        .collect();
+    let global_source = if std::env::var_os("GIT_CONFIG_GLOBAL").is_some() { " (GIT_CONFIG_GLOBAL)" } else { "" };
+    let system = if crate::git::system_git_config_disabled() {
+        "disabled (GIT_CONFIG_NOSYSTEM)".to_string()
+    } else if let Some(path) = std::env::var_os("GIT_CONFIG_SYSTEM").filter(|v| !v.is_empty()) {
+        format!("{} (GIT_CONFIG_SYSTEM)", std::path::Path::new(&path).display())
+    } else {
+        "git default".to_string()
+    };
+    doctor.ok("");
+    if global.is_empty() {
+        doctor.detail(format!("global: none yet{}", global_source));
+    } else {
+        doctor.detail(format!("global: {}{}", global.join(", "), global_source));
+    }
+    doctor.detail(format!("system: {}", system));
+
+    // Check credential helper chain
+    doctor.start("credential helpers");
+    let helpers = crate::credential::configured_helpers();
+    if helpers.is_empty() {
+        doctor.notice("No credential helper configured (tokens are not cached by git)");
+    } else {
+        doctor.ok(format!("{} helper(s), consulted in this order:", helpers.len()));
+        for helper in &helpers {
+            let gcm = if helper.is_gcm() { " (Git Credential Manager)" } else { "" };
+            doctor.detail(format!("{} = {}{}  [{}]", helper.key, helper.value, gcm, helper.origin));
         }
-        _ => {
-            println!("\x1b[1;31m✗ SSH not found!\x1b[0m");
-            all_ok = false;
+    }
+    if let Ok(config) = crate::config::load_config() {
+        let mode = config.credential_mode;
+        if mode == crate::credential::CredentialMode::Helper {
+            doctor.detail("Token profiles are served by 'gix credential-helper' when git asks (Helper mode)");
+        } else if crate::credential::use_gcm_interop(mode) {
+            doctor.detail(format!(
+                "Token profiles set credential.<host>.username and let GCM select the account ({:?} mode)",
+                mode
+            ));
+        } else {
+            doctor.detail(format!("Token profiles are stored with 'git credential approve' ({:?} mode)", mode));
         }
     }
 
     // Check config directory
-    print!("   Checking config directory... ");
+    doctor.start("config directory");
     match crate::config::get_gix_home_dir() {
-        Ok(path) => {
-            if path.exists() {
-                println!("\x1b[1;32m✓\x1b[0m {}", path.display());
-            } else {
-                println!("\x1b[1;33m⚠\x1b[0m Not created yet (will be created on first use)");
-            }
-        }
-        Err(_) => {
-            println!("\x1b[1;31m✗ Could not determine config path\x1b[0m");
-            all_ok = false;
-        }
+        Ok(path) if path.exists() => doctor.ok(path.display().to_string()),
+        Ok(_) => doctor.notice("Not created yet (will be created on first use)"),
+        Err(_) => doctor.error("Could not determine config path"),
     }
 
     // Check config file
-    print!("   Checking config file... ");
+    doctor.start("config file");
     match crate::config::get_global_config_path() {
         Ok(path) => {
             if path.exists() {
@@ -189,66 +728,188 @@ pub fn handle_doctor() -> Result<()> {
                     if let Ok(meta) = fs::metadata(&path) {
                         let mode = meta.permissions().mode() & 0o777;
                         if mode <= 0o600 {
-                            println!("\x1b[1;32m✓\x1b[0m {} (permissions: {:o})", path.display(), mode);
+                            doctor.ok(format!("{} (permissions: {:o})", path.display(), mode));
                         } else {
-                            println!(
-                                "\x1b[1;33m⚠\x1b[0m {} (permissions {:o} - consider chmod 600)",
-                                path.display(),
-                                mode
-                            );
+                            doctor.notice(format!("{} (permissions {:o} - consider chmod 600)", path.display(), mode));
                         }
                     }
                 }
                 #[cfg(not(unix))]
-                println!("\x1b[1;32m✓\x1b[0m {}", path.display());
+                doctor.ok(path.display().to_string());
             } else {
-                println!("\x1b[1;33m⚠\x1b[0m Not created yet");
+                doctor.notice("Not created yet");
             }
         }
-        Err(_) => {
-            println!("\x1b[1;31m✗ Could not determine config path\x1b[0m");
-            all_ok = false;
+        Err(_) => doctor.error("Could not determine config path"),
+    }
+
+    // Shared workstations and system packages may leave gix's files read-only
+    doctor.start("write access");
+    if crate::config::user_scope() {
+        doctor.ok("Using your own copy of the gix config (user scope)");
+    } else if crate::config::get_global_config_path().is_ok_and(|p| crate::config::is_writable(&p)) {
+        doctor.ok("gix can save its config");
+    } else {
+        doctor.notice("The gix config is read-only: settings are used but not saved; pass --user to keep your own copy");
+    }
+    if let Ok(exe) = std::env::current_exe() {
+        if !crate::config::is_writable(&exe) {
+            doctor.detail(format!("{} is read-only: 'gix update --user' installs your own copy", exe.display()));
         }
     }
 
     // Check profiles
-    print!("   Checking profiles... ");
+    doctor.start("profiles");
     match crate::config::load_config() {
         Ok(config) => {
             if config.profiles.is_empty() {
-                println!("\x1b[1;33m⚠\x1b[0m No profiles configured");
+                doctor.notice("No profiles configured");
             } else {
-                println!("\x1b[1;32m✓\x1b[0m {} profile(s) configured", config.profiles.len());
-                
-                // Validate each profile's SSH key
+                doctor.ok(format!("{} profile(s) configured", config.profiles.len()));
+
+                // Validate each profile's SSH key and token
                 for profile in &config.profiles {
-                    if let crate::profile::AuthMethod::SSH { key_path } = &profile.auth {
-                        let path = std::path::PathBuf::from(key_path);
+                    if profile.ssh.host_key_checking == Some(crate::ssh::HostKeyChecking::No) {
+                        doctor.detail_warning(format!(
+                            "Profile '{}': SSH host key checking is disabled (man-in-the-middle risk)",
+                            profile.profile_name
+                        ));
+                    }
+                    if let Some(program) = &profile.ssh.program {
+                        if let Err(e) = crate::ssh::check_ssh_program(program) {
+                            doctor.detail_warning(format!("Profile '{}': ssh program unusable: {}", profile.profile_name, e));
+                        }
+                    }
+                    for token in profile.auth.tokens() {
+                        if let Err(e) = crate::profile::resolve_token(token) {
+                            doctor.detail_warning(format!("Profile '{}': {}", profile.profile_name, e));
+                        }
+                    }
+                    for key_path in profile.auth.ssh_keys() {
+                        let path = crate::ssh::resolve_key_path(key_path);
                         if !path.exists() {
-                            println!(
-                                "      \x1b[1;33m⚠ Profile '{}': SSH key not found at {}\x1b[0m",
+                            doctor.detail_warning(format!(
+                                "Profile '{}': SSH key not found at {}",
                                 profile.profile_name, key_path
-                            );
-                            all_ok = false;
+                            ));
+                        } else if fix {
+                            if !crate::ssh::check_key_permissions(&path, true)? {
+                                doctor.detail_warning(format!(
+                                    "Profile '{}': key permissions could not be fixed",
+                                    profile.profile_name
+                                ));
+                            }
+                        } else {
+                            for issue in crate::ssh::key_permission_issues(&path) {
+                                doctor.detail_warning(format!(
+                                    "Profile '{}': {} has permissions {:o} (expected {:o}, run 'gix doctor --fix')",
+                                    profile.profile_name,
+                                    issue.path.display(),
+                                    issue.mode,
+                                    issue.expected
+                                ));
+                            }
                         }
                     }
                 }
             }
         }
-        Err(e) => {
-            println!("\x1b[1;31m✗ Error loading config: {}\x1b[0m", e);
-            all_ok = false;
+        Err(e) => doctor.error(format!("Error loading config: {}", e)),
+    }
+
+    // Check smartcards holding signing keys
+    let card_profiles: Vec<String> = crate::config::load_config()
+        .map(|c| {
+            c.profiles
+                .iter()
+                .filter(|p| p.signing.as_ref().is_some_and(|s| s.smartcard))
+                .map(|p| p.profile_name.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    if !card_profiles.is_empty() {
+        doctor.start("smartcard");
+        match crate::signing::card_status() {
+            crate::signing::CardStatus::Present(serial) => {
+                doctor.ok(format!("Card present{}", serial.map(|s| format!(" (serial {})", s)).unwrap_or_default()));
+            }
+            crate::signing::CardStatus::Absent => doctor.warning(format!(
+                "No card inserted; signed commits of {} will fail until it is",
+                card_profiles.join(", ")
+            )),
+            crate::signing::CardStatus::Unavailable(reason) => {
+                doctor.error(format!("Cannot query the card: {} (is gpg/scdaemon installed?)", reason));
+            }
         }
     }
 
+    // Check the system policy
+    doctor.start("policy");
+    match crate::policy::load_policy() {
+        Ok(None) => doctor.ok("No host policy installed"),
+        Ok(Some(policy)) => doctor.ok(format!(
+            "{} restricts {} profile(s)",
+            crate::policy::policy_path().display(),
+            policy.profiles.len()
+        )),
+        Err(e) => doctor.error(format!("{:#}", e)),
+    }
+
+    crate::alias::doctor_check(&mut doctor);
+
+    // Check references to profiles that were renamed or deleted
+    doctor.start("profile references");
+    if let Err(e) = check_profile_references(&mut doctor, fix) {
+        doctor.error(e.to_string());
+    }
+
     // Check current repo
-    print!("   Checking current directory... ");
+    doctor.start("current directory");
     if crate::git::is_inside_git_repo() {
-        println!("\x1b[1;32m✓\x1b[0m Inside a git repository");
+        doctor.ok("Inside a git repository");
+
+        // Check that nothing outside gix overrides the repository's profile
+        let config = crate::config::load_config().unwrap_or_default();
+        if let Some(profile) = crate::git::detect_profile(&config) {
+            doctor.start("identity overrides");
+            let cwd = std::env::current_dir().unwrap_or_default();
+            let report = crate::overrides::check_identity(profile, &cwd);
+            if report.conflicts.is_empty() && report.shadowed.is_empty() {
+                doctor.ok(format!("Profile '{}' is what git uses here", profile.profile_name));
+            } else if report.conflicts.is_empty() {
+                doctor.ok(format!("Profile '{}' wins over other identity settings:", profile.profile_name));
+            } else {
+                doctor.error(format!("Profile '{}' is overridden:", profile.profile_name));
+            }
+            if !json {
+                crate::overrides::print_identity_report(&report);
+            } else if let Some(check) = doctor.checks.last_mut() {
+                check.details.extend(report.conflicts.iter().chain(&report.shadowed).cloned());
+            }
+        }
+
+        // Check git-lfs when the repository needs it
+        let root = crate::git::get_git_root().unwrap_or_default();
+        if crate::lfs::uses_lfs(&root) {
+            doctor.start("git-lfs");
+            match crate::lfs::lfs_version() {
+                Some(version) => {
+                    doctor.ok(version);
+                    if let Some(host) = crate::lfs::lfs_https_host(&root) {
+                        doctor.detail(format!("LFS endpoint {} gets the profile's credentials on intercepted commands", host));
+                    }
+                }
+                None => doctor.error("This repository uses Git LFS but git-lfs is not installed"),
+            }
+        }
     } else {
-        println!("\x1b[1;33m⚠\x1b[0m Not inside a git repository");
+        doctor.notice("Not inside a git repository");
     }
 
+    let all_ok = doctor.all_ok();
+    if json {
+        return crate::settings::print_json(&serde_json::json!({ "ok": all_ok, "checks": doctor.checks }));
+    }
     println!();
     if all_ok {
         println!("\x1b[1;32m✓ All checks passed!\x1b[0m");
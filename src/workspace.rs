@@ -0,0 +1,175 @@
+//! Named workspaces: separate sets of profiles, rules and defaults
+//!
+//! The default workspace is ~/.gix/config.json; a workspace `name` keeps its
+//! own config in ~/.gix/workspaces/<name>/config.json. Repository state
+//! (.gix/config.json, history, registry) stays shared.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cli::WorkspaceAction;
+use crate::config::get_gix_home_dir;
+
+/// Name of the workspace that lives in ~/.gix/config.json
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Select the workspace for this process and the git/gix processes it starts (`--workspace`)
+pub fn set_workspace(name: &str) {
+    std::env::set_var("GIX_WORKSPACE", name);
+}
+
+/// File remembering the workspace chosen with 'gix workspace use'
+fn active_file(gix_dir: &Path) -> PathBuf {
+    gix_dir.join("workspace")
+}
+
+fn workspaces_dir(gix_dir: &Path) -> PathBuf {
+    gix_dir.join("workspaces")
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!("Invalid workspace name '{}': use letters, digits, '-' and '_'", name);
+    }
+    Ok(())
+}
+
+/// Whether a name means the default workspace (in any case)
+fn is_default(name: &str) -> bool {
+    name.eq_ignore_ascii_case(DEFAULT_WORKSPACE)
+}
+
+/// Whether a missing selected workspace was already reported in this process
+static FALLBACK_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Workspace in effect: `--workspace`/GIX_WORKSPACE, then 'gix workspace use', then the default
+///
+/// A selected workspace that does not exist falls back to the default with a warning,
+/// so git commands keep working after it is removed.
+pub fn active_workspace() -> String {
+    let gix_dir = get_gix_home_dir().ok();
+    let selected = std::env::var("GIX_WORKSPACE")
+        .ok()
+        .or_else(|| gix_dir.as_ref().and_then(|dir| fs::read_to_string(active_file(dir)).ok()))
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty() && !is_default(n));
+    let Some(name) = selected else {
+        return DEFAULT_WORKSPACE.to_string();
+    };
+
+    let exists = validate_name(&name).is_ok() && gix_dir.is_some_and(|dir| workspaces_dir(&dir).join(&name).is_dir());
+    if exists {
+        return name;
+    }
+    if !FALLBACK_WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!(
+            "\x1b[1;33m⚠ Workspace '{}' does not exist; using the default workspace ('gix workspace use' to pick another)\x1b[0m",
+            name
+        );
+    }
+    DEFAULT_WORKSPACE.to_string()
+}
+
+/// Global config file of the active workspace
+pub fn config_path(gix_dir: &Path) -> Result<PathBuf> {
+    let name = active_workspace();
+    if name == DEFAULT_WORKSPACE {
+        return Ok(gix_dir.join("config.json"));
+    }
+    Ok(workspaces_dir(gix_dir).join(&name).join("config.json"))
+}
+
+/// Every workspace, the default first
+fn list_workspaces(gix_dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(workspaces_dir(gix_dir))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.insert(0, DEFAULT_WORKSPACE.to_string());
+    names
+}
+
+/// Handle the 'gix workspace' command
+pub fn handle_workspace_command(action: Option<WorkspaceAction>) -> Result<()> {
+    let gix_dir = get_gix_home_dir()?;
+
+    match action.unwrap_or(WorkspaceAction::List) {
+        WorkspaceAction::List => {
+            let active = active_workspace();
+            println!("\x1b[1;36m🗂  Workspaces:\x1b[0m\n");
+            for name in list_workspaces(&gix_dir) {
+                if name == active {
+                    println!("  \x1b[1;32m● {}\x1b[0m (active)", name);
+                } else {
+                    println!("  ○ {}", name);
+                }
+            }
+            if std::env::var("GIX_WORKSPACE").is_ok_and(|n| !n.trim().is_empty()) {
+                println!("\n   Selected by --workspace / GIX_WORKSPACE for this command.");
+            }
+        }
+        WorkspaceAction::New { name } => {
+            validate_name(&name)?;
+            if is_default(&name) {
+                anyhow::bail!("'{}' always exists", DEFAULT_WORKSPACE);
+            }
+            let dir = workspaces_dir(&gix_dir).join(&name);
+            if dir.exists() {
+                anyhow::bail!("Workspace '{}' already exists", name);
+            }
+            fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+            println!("\x1b[1;32m✓ Workspace '{}' created\x1b[0m", name);
+            println!("   Switch to it with '\x1b[1mgix workspace use {}\x1b[0m', then add its profiles.", name);
+        }
+        WorkspaceAction::Use { name } => {
+            let name = if is_default(&name) { DEFAULT_WORKSPACE.to_string() } else { name };
+            if name != DEFAULT_WORKSPACE {
+                validate_name(&name)?;
+                if !workspaces_dir(&gix_dir).join(&name).is_dir() {
+                    anyhow::bail!("Workspace '{}' does not exist. Create it with 'gix workspace new {}'.", name, name);
+                }
+            }
+            let file = active_file(&gix_dir);
+            if name == DEFAULT_WORKSPACE {
+                if file.exists() {
+                    fs::remove_file(&file)?;
+                }
+            } else {
+                crate::config::write_atomic(&file, &format!("{}\n", name), false)?;
+            }
+            println!("\x1b[1;32m✓ Using workspace '{}'\x1b[0m", name);
+            if std::env::var("GIX_WORKSPACE").is_ok_and(|n| !n.trim().is_empty() && n.trim() != name) {
+                println!("\x1b[1;33m⚠ GIX_WORKSPACE is set and still wins in this shell.\x1b[0m");
+            }
+        }
+        WorkspaceAction::Remove { name } => {
+            validate_name(&name)?;
+            if is_default(&name) {
+                anyhow::bail!("The default workspace cannot be removed");
+            }
+            let dir = workspaces_dir(&gix_dir).join(&name);
+            if !dir.is_dir() {
+                anyhow::bail!("Workspace '{}' does not exist", name);
+            }
+            println!("\x1b[1;31m⚠ This deletes workspace '{}' with all its profiles and rules.\x1b[0m", name);
+            if !crate::settings::confirm_typed(&name)? {
+                println!("Cancelled.");
+                return Ok(());
+            }
+            fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+            if fs::read_to_string(active_file(&gix_dir)).is_ok_and(|n| n.trim() == name) {
+                fs::remove_file(active_file(&gix_dir))?;
+            }
+            println!("\x1b[1;32m✓ Workspace '{}' removed\x1b[0m", name);
+        }
+    }
+    Ok(())
+}
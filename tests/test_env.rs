@@ -0,0 +1,115 @@
+//! End-to-end tests run inside `gix test-env` sandboxes
+//!
+//! Run with `cargo test --features test-env`.
+#![cfg(feature = "test-env")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const GIX: &str = env!("CARGO_BIN_EXE_gix");
+
+/// A path under the system temp dir, unique to the test and not created yet
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("gix-it-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// A sandbox kept in `dir`, with the variables `gix test-env` exports for it
+struct Env {
+    dir: PathBuf,
+    vars: Vec<(String, String)>,
+}
+
+impl Env {
+    fn create(dir: PathBuf) -> Env {
+        let out = Command::new(GIX).arg("test-env").arg("--dir").arg(&dir).output().unwrap();
+        assert_success(&out, "gix test-env");
+        let vars = String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter_map(|line| line.strip_prefix("export "))
+            .filter_map(|line| line.split_once('='))
+            .map(|(name, value)| (name.to_string(), value.trim_matches('\'').to_string()))
+            .collect();
+        Env { dir, vars }
+    }
+
+    fn var(&self, name: &str) -> &str {
+        self.vars.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str()).unwrap()
+    }
+
+    fn work(&self) -> PathBuf {
+        self.dir.join("work")
+    }
+
+    fn run(&self, program: &str, dir: &Path, args: &[&str]) -> Output {
+        Command::new(program)
+            .args(args)
+            .current_dir(dir)
+            .envs(self.vars.iter().map(|(n, v)| (n, v)))
+            .env_remove("GIX_ACTIVE")
+            .output()
+            .unwrap()
+    }
+
+    fn git_stdout(&self, dir: &Path, args: &[&str]) -> String {
+        let out = self.run("git", dir, args);
+        assert_success(&out, &format!("git {}", args.join(" ")));
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+}
+
+impl Drop for Env {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn assert_success(out: &Output, what: &str) {
+    assert!(out.status.success(), "{} failed: {}", what, String::from_utf8_lossy(&out.stderr));
+}
+
+#[test]
+fn use_applies_the_profile_identity() {
+    let env = Env::create(scratch("use"));
+    let repo = env.work().join("repo");
+    fs::create_dir_all(&repo).unwrap();
+    env.git_stdout(&repo, &["init", "-q"]);
+
+    assert_success(&env.run(GIX, &repo, &["use", "selftest"]), "gix use");
+    assert_eq!(env.git_stdout(&repo, &["config", "--local", "user.email"]), "selftest@gix.invalid");
+    assert!(env.git_stdout(&repo, &["config", "--local", "core.sshCommand"]).contains("id_selftest"));
+}
+
+#[test]
+fn intercepted_clone_of_the_fixture_remote_is_configured() {
+    let env = Env::create(scratch("clone"));
+    let remote = env.var("GIX_TEST_REMOTE").to_string();
+
+    assert_success(&env.run(GIX, &env.work(), &["clone", "-q", &remote, "cloned"]), "gix clone");
+    let cloned = env.work().join("cloned");
+    assert_eq!(env.git_stdout(&cloned, &["config", "--local", "user.email"]), "selftest@gix.invalid");
+    assert_eq!(env.git_stdout(&cloned, &["log", "-1", "--format=%s"]), "initial");
+}
+
+#[test]
+fn refuses_a_non_empty_dir_and_leaves_it_alone() {
+    let dir = scratch("busy");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("keep.txt"), "mine").unwrap();
+
+    let out = Command::new(GIX).arg("test-env").arg("--dir").arg(&dir).output().unwrap();
+    assert!(!out.status.success());
+    assert_eq!(fs::read_to_string(dir.join("keep.txt")).unwrap(), "mine");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn keeps_a_given_dir_after_running_a_command() {
+    let dir = scratch("kept");
+    let out = Command::new(GIX).arg("test-env").arg("--dir").arg(&dir).args([GIX, "--version"]).output().unwrap();
+    assert_success(&out, "gix test-env -- gix --version");
+    assert!(dir.join("home").join(".gix").join("config.json").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}